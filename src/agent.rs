@@ -7,53 +7,239 @@
 
 //! Executes tasks using an agent and records progress in the log.
 
-use crate::store::Task;
-use crate::tools;
+use crate::logging::{self, LogEvent};
+use crate::store::{self, Task, TaskStatus};
+use crate::tools::{self, ToolOutput};
 use anyhow::Result;
-use chrono::Local;
 use reqwest::Client;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::fs;
-use std::fs::OpenOptions;
-use std::io::Write;
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
 use crate::config;
 
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Callback deciding whether a tool call listed in
+/// `tools.require_confirmation` may run. Receives the tool name and arguments.
+pub type Confirmation = Box<dyn Fn(&str, &Value) -> bool + Send + Sync>;
+
+static CONFIRMATION: RwLock<Option<Confirmation>> = RwLock::new(None);
+
+/// Installs (or with `None`, removes) the callback consulted before running a
+/// tool that requires confirmation. Without a callback such tools run
+/// unprompted, which keeps scheduled and non-interactive runs unattended.
+pub fn set_confirmation(confirmation: Option<Confirmation>) {
+    *CONFIRMATION
+        .write()
+        .expect("tool confirmation lock poisoned") = confirmation;
+}
+
+/// Prompts on `output` whether the agent may run `name` with `args` and reads
+/// the answer from `input`. Only `y` or `yes` confirm.
+///
+/// # Errors
+///
+/// Returns an error if the prompt cannot be written or the answer read.
+pub fn ask_confirmation(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    name: &str,
+    args: &Value,
+) -> io::Result<bool> {
+    write!(
+        output,
+        "Agent wants to run {name} with {args}. Proceed? [y/N] "
+    )?;
+    output.flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Returns `false` when the installed confirmation callback declines a tool
+/// call that `tools.require_confirmation` lists.
+fn tool_call_confirmed(name: &str, args: &Value) -> bool {
+    let guard = CONFIRMATION
+        .read()
+        .expect("tool confirmation lock poisoned");
+    let Some(confirm) = guard.as_ref() else {
+        return true;
+    };
+    if !tools::requires_confirmation(name, args) {
+        return true;
+    }
+    confirm(name, args)
+}
+
 /// Result of running an [`Agent`] on a [`Task`].
 #[must_use = "inspect the result to handle success or failure"]
 #[derive(Debug, PartialEq)]
 pub enum ExecutionResult {
-    Success { comment: String },
-    Failure { comment: String },
+    Success { comment: String, stats: RunStats },
+    Failure { comment: String, stats: RunStats },
 }
 
-fn append_log(message: &str) -> anyhow::Result<()> {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(config::log_path()?)?;
-    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-    writeln!(file, "[{timestamp}] {message}")?;
-    Ok(())
+impl ExecutionResult {
+    /// Returns `true` for failures worth running again: a tool call or
+    /// provider request that errored. Rejected tools, invalid tool arguments,
+    /// missing API keys and offline simulations fail the same way on every
+    /// attempt.
+    pub fn is_retriable(&self) -> bool {
+        matches!(
+            self,
+            ExecutionResult::Failure { stats, .. }
+                if matches!(stats.final_action, FinalAction::ToolFailed | FinalAction::ProviderFailed)
+        )
+    }
+
+    /// Returns how the run progressed.
+    pub fn stats(&self) -> RunStats {
+        match self {
+            ExecutionResult::Success { stats, .. } | ExecutionResult::Failure { stats, .. } => {
+                *stats
+            }
+        }
+    }
+}
+
+/// Step metadata of an agent run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunStats {
+    /// Number of tool calls the model requested, including a failing one.
+    pub tool_calls: usize,
+    /// What ended the run.
+    pub final_action: FinalAction,
+}
+
+impl RunStats {
+    /// Stats for a run that ended with `final_action` after `tool_calls` calls.
+    pub fn new(tool_calls: usize, final_action: FinalAction) -> Self {
+        Self {
+            tool_calls,
+            final_action,
+        }
+    }
+}
+
+/// The last step of an agent run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FinalAction {
+    /// The model replied with text.
+    Text,
+    /// A tool call failed.
+    ToolFailed,
+    /// A tool call had arguments that can never succeed, such as a missing
+    /// field or a path outside the agent workdir.
+    ToolInvalid,
+    /// The model called a tool that is unknown or disabled.
+    ToolRejected,
+    /// The provider request failed and simulation is disabled.
+    ProviderFailed,
+    /// No API key was configured and simulation is disabled.
+    MissingApiKey,
+    /// The run was simulated locally because no provider was reachable.
+    Simulated,
+    /// The caller stopped the run, e.g. on timeout.
+    Interrupted,
+}
+
+/// Built-in tools that are safe to run with empty arguments during an offline
+/// simulation: they only read project data.
+const DRY_RUN_TOOLS: &[&str] = &["get_description"];
+
+/// With `providers.simulate_tools`, runs an agent's only tool for real when it
+/// is in [`DRY_RUN_TOOLS`] and returns its output. Returns `None` when the
+/// mode is off or the agent's tools do not qualify.
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be loaded.
+fn simulate_with_dry_tool(agent: &Agent, tool_calls: usize) -> Result<Option<ExecutionResult>> {
+    if !config::simulate_tools()? {
+        return Ok(None);
+    }
+    let [declaration] = agent.tools.as_slice() else {
+        return Ok(None);
+    };
+    let Some(name) = DRY_RUN_TOOLS.iter().copied().find(|name| {
+        *name == declaration.name
+            || tools::builtin_declaration(name).is_some_and(|d| d.name == declaration.name)
+    }) else {
+        return Ok(None);
+    };
+    let stats = RunStats::new(tool_calls + 1, FinalAction::Simulated);
+    let result = match tools::execute_tool_in(name, &json!({}), agent.workdir.as_deref()) {
+        Ok(output) => {
+            let _ = logging::append(
+                &LogEvent::new(
+                    "finished",
+                    format!("Agent {} simulated {name}: {output}", agent.id),
+                )
+                .agent(agent.id)
+                .tool(name),
+            );
+            ExecutionResult::Success {
+                comment: output.to_string(),
+                stats,
+            }
+        }
+        Err(err) => {
+            let comment = format!("Tool {name} failed: {err}");
+            let _ = logging::append(
+                &LogEvent::new("failed", format!("Agent {} failed: {comment}", agent.id))
+                    .agent(agent.id)
+                    .tool(name),
+            );
+            ExecutionResult::Failure { comment, stats }
+        }
+    };
+    Ok(Some(result))
 }
 
-fn simulate_without_api(agent: &Agent, has_send_email_tool: bool) -> ExecutionResult {
+fn simulate_without_api(
+    agent: &Agent,
+    has_send_email_tool: bool,
+    tool_calls: usize,
+) -> Result<ExecutionResult> {
+    if let Some(result) = simulate_with_dry_tool(agent, tool_calls)? {
+        return Ok(result);
+    }
+    let stats = RunStats::new(tool_calls, FinalAction::Simulated);
     if has_send_email_tool {
         let msg = "Tool available. Task considered complete.".to_string();
-        let _ = append_log(&format!(
-            "Agent {} finished successfully: {}",
-            agent.id, msg
-        ));
-        ExecutionResult::Success { comment: msg }
+        let _ = logging::append(
+            &LogEvent::new(
+                "finished",
+                format!("Agent {} finished successfully: {}", agent.id, msg),
+            )
+            .agent(agent.id),
+        );
+        Ok(ExecutionResult::Success {
+            comment: msg,
+            stats,
+        })
     } else {
         let msg = "Required tool not available.".to_string();
-        let _ = append_log(&format!("Agent {} failed: {}", agent.id, msg));
-        ExecutionResult::Failure { comment: msg }
+        let _ = logging::append(
+            &LogEvent::new("failed", format!("Agent {} failed: {}", agent.id, msg)).agent(agent.id),
+        );
+        Ok(ExecutionResult::Failure {
+            comment: msg,
+            stats,
+        })
     }
 }
 
-use crate::providers::{select_provider, ModelAction};
+use crate::providers::{select_provider, ModelAction, ModelProvider};
 
 /// Executes a task with the given agent and records progress in `.taskter/logs.log`.
 ///
@@ -65,8 +251,6 @@ use crate::providers::{select_provider, ModelAction};
 /// captured as [`ExecutionResult::Failure`] so callers can inspect the outcome.
 #[must_use = "use the result to determine task outcome"]
 pub async fn execute_task(agent: &Agent, task: Option<&Task>) -> Result<ExecutionResult> {
-    let _guard = RunningAgentGuard::new(agent.id);
-    let client = Client::builder().no_proxy().build()?;
     let log_message = if let Some(task) = task {
         format!(
             "Agent {} executing task {}: {}",
@@ -75,83 +259,414 @@ pub async fn execute_task(agent: &Agent, task: Option<&Task>) -> Result<Executio
     } else {
         format!("Agent {} executing without a task", agent.id)
     };
-    let _ = append_log(&log_message);
-
-    let provider = select_provider(agent);
-    let has_send_email_tool = agent.tools.iter().any(|t| t.name == "send_email");
-
-    let requires_api_key = provider.requires_api_key();
-    let mut api_key = config::provider_api_key(provider.name())?;
-    if api_key.is_none() && requires_api_key {
-        // Backwards compatibility with legacy environment variables.
-        api_key = std::env::var(provider.api_key_env())
-            .ok()
-            .filter(|k| !k.trim().is_empty());
-    }
+    let _ = logging::append(&LogEvent::new("execution_started", log_message).agent(agent.id));
+
+    let user_prompt = match task.map(task_prompt).transpose() {
+        Ok(prompt) => prompt.unwrap_or_default(),
+        Err(err) => {
+            notify_webhook(agent, task, "failure", &err.to_string()).await;
+            return Err(err);
+        }
+    };
 
-    if requires_api_key && api_key.is_none() {
-        let _ = append_log("Executing without API key");
-        return Ok(simulate_without_api(agent, has_send_email_tool));
+    // A budget too large to express as a deadline is no limit at all.
+    let deadline = agent
+        .execution_budget_secs
+        .and_then(|secs| Instant::now().checked_add(Duration::from_secs(secs)));
+    let mut attempt = 0;
+    let result = loop {
+        let sent = match Conversation::new(agent.clone()) {
+            Ok(mut conversation) => {
+                conversation.deadline = deadline;
+                conversation.send(&user_prompt).await
+            }
+            Err(err) => Err(err),
+        };
+        let result = match sent {
+            Ok(result) => result,
+            Err(err) => {
+                notify_webhook(agent, task, "failure", &err.to_string()).await;
+                return Err(err);
+            }
+        };
+        if attempt >= agent.retry_on_failure || !result.is_retriable() {
+            break result;
+        }
+        attempt += 1;
+        let _ = logging::append(
+            &LogEvent::new(
+                "retry",
+                format!(
+                    "Agent {} failed; retrying (attempt {} of {})",
+                    agent.id,
+                    attempt + 1,
+                    agent.retry_on_failure + 1
+                ),
+            )
+            .agent(agent.id),
+        );
+    };
+    let (outcome, comment) = match &result {
+        ExecutionResult::Success { comment, .. } => ("success", comment),
+        ExecutionResult::Failure { comment, .. } => ("failure", comment),
+    };
+    notify_webhook(agent, task, outcome, comment).await;
+    Ok(result)
+}
+
+/// Builds the user prompt for `task` from its title and description.
+///
+/// With `providers.include_task_comment` enabled, the task's current comment
+/// (often feedback from a previous run) is appended as well.
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be loaded.
+pub fn task_prompt(task: &Task) -> Result<String> {
+    let mut prompt = match &task.description {
+        Some(desc) => format!("Task Title: {}\nTask Description: {}", task.title, desc),
+        None => task.title.clone(),
+    };
+    if config::include_task_comment()? {
+        if let Some(comment) = task.comment.as_deref().filter(|c| !c.trim().is_empty()) {
+            let author = task
+                .comment_author
+                .map(|author| format!(" (from {author})"))
+                .unwrap_or_default();
+            prompt.push_str(&format!("\nPrevious Comment{author}: {comment}"));
+        }
     }
-    let api_key = api_key.unwrap_or_default();
-
-    let user_prompt = match task {
-        Some(task) => match &task.description {
-            Some(desc) => format!("Task Title: {}\nTask Description: {}", task.title, desc),
-            None => task.title.clone(),
-        },
-        None => String::new(),
+    Ok(prompt)
+}
+
+/// Posts the execution outcome to `integrations.webhook_url` when configured.
+/// Like provider requests, delivery bypasses proxies unless
+/// `providers.use_proxy` is set.
+///
+/// Delivery failures are logged and never affect the task outcome.
+async fn notify_webhook(agent: &Agent, task: Option<&Task>, outcome: &str, comment: &str) {
+    let url = match config::webhook_url() {
+        Ok(Some(url)) => url,
+        _ => return,
+    };
+    let payload = json!({
+        "agent_id": agent.id,
+        "task_id": task.map(|t| t.id),
+        "outcome": outcome,
+        "comment": comment,
+        "timestamp": chrono::Local::now().to_rfc3339(),
+    });
+    let delivery = async {
+        let mut builder = Client::builder().timeout(WEBHOOK_TIMEOUT);
+        if !config::providers_use_proxy()? {
+            builder = builder.no_proxy();
+        }
+        builder
+            .build()?
+            .post(&url)
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok::<_, anyhow::Error>(())
     };
+    if let Err(err) = delivery.await {
+        let _ = logging::append(
+            &LogEvent::new(
+                "webhook_failed",
+                format!("Webhook delivery to {url} failed: {err}"),
+            )
+            .agent(agent.id),
+        );
+    }
+}
 
-    let mut history = provider.build_history(agent, &user_prompt);
-
-    loop {
-        let action = match provider
-            .infer(&client, agent, &api_key, &history)
-            .await
-            .inspect_err(|e| {
-                let _ = append_log(&format!(
-                    "API request failed; falling back to local simulation: {e}"
-                ));
-            }) {
-            Ok(a) => a,
-            Err(_) => return Ok(simulate_without_api(agent, has_send_email_tool)),
+/// Runs `agent` on an arbitrary `prompt` until the model produces a final answer.
+///
+/// This drives the provider/tool loop used by [`execute_task`] without touching
+/// the board or sending webhook notifications, so library users can invoke an
+/// agent directly. Without an API key the run is simulated locally.
+///
+/// # Errors
+///
+/// Returns an error if the HTTP client cannot be built or the provider
+/// configuration is invalid. Tool failures are reported as
+/// [`ExecutionResult::Failure`].
+pub async fn run_agent(agent: &Agent, prompt: &str) -> Result<ExecutionResult> {
+    Conversation::new(agent.clone())?.send(prompt).await
+}
+
+/// Replaces `{{board_summary}}` and `{{project_description}}` in a system
+/// prompt with the current task counts and `description.md`. Everything else,
+/// including unknown `{{...}}` placeholders, is kept as written; a value that
+/// cannot be read expands to an empty string.
+#[must_use]
+pub fn expand_prompt_variables(prompt: &str) -> String {
+    if !prompt.contains("{{") {
+        return prompt.to_string();
+    }
+    let mut expanded = prompt.to_string();
+    // The board is only read when asked for, since it may come from stdin.
+    if expanded.contains("{{board_summary}}") {
+        let summary = store::load_board()
+            .map(|board| board_summary(&board))
+            .unwrap_or_default();
+        expanded = expanded.replace("{{board_summary}}", &summary);
+    }
+    if expanded.contains("{{project_description}}") {
+        let description = config::description_path()
+            .and_then(|path| Ok(fs::read_to_string(path)?))
+            .unwrap_or_default();
+        expanded = expanded.replace("{{project_description}}", description.trim_end());
+    }
+    expanded
+}
+
+fn board_summary(board: &store::Board) -> String {
+    let count = |status: TaskStatus| board.tasks.iter().filter(|t| t.status == status).count();
+    format!(
+        "{} tasks: {} to do, {} in progress, {} done",
+        board.tasks.len(),
+        count(TaskStatus::ToDo),
+        count(TaskStatus::InProgress),
+        count(TaskStatus::Done)
+    )
+}
+
+/// Logs and reports a run stopped by its execution budget.
+fn budget_exceeded(agent_id: usize, tool_calls: usize) -> ExecutionResult {
+    let comment = "execution budget exceeded".to_string();
+    let _ = logging::append(
+        &LogEvent::new(
+            "budget_exceeded",
+            format!("Agent {agent_id} stopped: {comment}"),
+        )
+        .agent(agent_id),
+    );
+    ExecutionResult::Failure {
+        comment,
+        stats: RunStats::new(tool_calls, FinalAction::Interrupted),
+    }
+}
+
+/// A chat with an agent that keeps the provider history across messages, so
+/// each reply sees the earlier turns and tool results.
+pub struct Conversation {
+    agent: Agent,
+    provider: Box<dyn ModelProvider + Send + Sync>,
+    client: Client,
+    /// `None` when the provider needs a key and none is configured.
+    api_key: Option<String>,
+    history: Vec<Value>,
+    /// When set, `send` gives up once this instant has passed.
+    deadline: Option<Instant>,
+}
+
+impl Conversation {
+    /// Prepares a conversation with `agent` without contacting the provider.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be loaded, the HTTP client
+    /// cannot be built or the provider configuration is invalid.
+    pub fn new(mut agent: Agent) -> Result<Self> {
+        agent.system_prompt = expand_prompt_variables(&agent.system_prompt);
+        let mut builder = Client::builder();
+        if !config::providers_use_proxy()? {
+            builder = builder.no_proxy();
+        }
+        let client = builder.build()?;
+
+        let provider = select_provider(&agent);
+        let requires_api_key = provider.requires_api_key();
+        let mut api_key = config::provider_api_key(provider.name())?;
+        if api_key.is_none() && requires_api_key {
+            // Backwards compatibility with legacy environment variables.
+            api_key = std::env::var(provider.api_key_env())
+                .ok()
+                .filter(|k| !k.trim().is_empty());
+        }
+        let api_key = match api_key {
+            None if requires_api_key => None,
+            key => Some(key.unwrap_or_default()),
         };
 
-        match action {
-            ModelAction::ToolCall {
-                name,
-                args,
-                call_id,
-            } => {
-                let agent_id = agent.id;
-                let _ = append_log(&format!(
-                    "Agent {agent_id} calling tool {name} with args {args}"
-                ));
-                let tool_response = match tools::execute_tool(&name, &args) {
-                    Ok(response) => response,
-                    Err(err) => {
-                        let message = format!("Tool {name} failed: {err}");
-                        let _ = append_log(&format!("Agent {agent_id} failed: {message}"));
-                        return Ok(ExecutionResult::Failure { comment: message });
-                    }
-                };
-                let _ = append_log(&format!("Tool {name} responded with {tool_response}"));
-                provider.append_tool_result(
-                    agent,
-                    &mut history,
-                    &name,
-                    &args,
-                    &tool_response,
-                    call_id.as_deref(),
+        Ok(Self {
+            agent,
+            provider,
+            client,
+            api_key,
+            history: Vec::new(),
+            deadline: None,
+        })
+    }
+
+    /// Sends `message` as the next user turn and runs the provider/tool loop
+    /// until the model answers with text. Without an API key the reply is
+    /// simulated locally and no history is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be loaded or the request
+    /// cannot be sent. Tool and provider failures are reported as
+    /// [`ExecutionResult::Failure`].
+    pub async fn send(&mut self, message: &str) -> Result<ExecutionResult> {
+        let Self {
+            agent,
+            provider,
+            client,
+            api_key,
+            history,
+            deadline,
+        } = self;
+        let agent = &*agent;
+        let _guard = RunningAgentGuard::new(agent.id);
+        let has_send_email_tool = agent.tools.iter().any(|t| t.name == "send_email");
+        let allow_simulation = config::allow_simulation()?;
+
+        let Some(api_key) = api_key.as_deref() else {
+            if !allow_simulation {
+                let comment = format!(
+                    "No API key configured for provider {}; simulation is disabled",
+                    provider.name()
                 );
+                let _ = logging::append(&LogEvent::new("no_api_key", &comment).agent(agent.id));
+                return Ok(ExecutionResult::Failure {
+                    comment,
+                    stats: RunStats::new(0, FinalAction::MissingApiKey),
+                });
             }
-            ModelAction::Text { content } => {
-                let _ = append_log(&format!(
-                    "Agent {} finished successfully: {}",
-                    agent.id, content
-                ));
-                return Ok(ExecutionResult::Success { comment: content });
+            let _ = logging::append(
+                &LogEvent::new("no_api_key", "Executing without API key").agent(agent.id),
+            );
+            return simulate_without_api(agent, has_send_email_tool, 0);
+        };
+
+        if history.is_empty() {
+            *history = provider.build_history(agent, message);
+        } else {
+            provider.append_user_turn(agent, history, message);
+        }
+        let mut tool_calls = 0;
+
+        loop {
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Ok(budget_exceeded(agent.id, tool_calls));
+            }
+            let request = provider.infer(client, agent, api_key, history);
+            let inferred = match *deadline {
+                Some(deadline) => match tokio::time::timeout_at(deadline.into(), request).await {
+                    Ok(inferred) => inferred,
+                    Err(_) => return Ok(budget_exceeded(agent.id, tool_calls)),
+                },
+                None => request.await,
+            };
+            let action = match inferred {
+                Ok(a) => a,
+                Err(e) if allow_simulation => {
+                    let _ = logging::append(
+                        &LogEvent::new(
+                            "api_error",
+                            format!("API request failed; falling back to local simulation: {e}"),
+                        )
+                        .agent(agent.id),
+                    );
+                    return simulate_without_api(agent, has_send_email_tool, tool_calls);
+                }
+                Err(e) => {
+                    let comment = format!("API request failed: {e}");
+                    let _ = logging::append(&LogEvent::new("api_error", &comment).agent(agent.id));
+                    return Ok(ExecutionResult::Failure {
+                        comment,
+                        stats: RunStats::new(tool_calls, FinalAction::ProviderFailed),
+                    });
+                }
+            };
+
+            match action {
+                ModelAction::ToolCall {
+                    name,
+                    args,
+                    call_id,
+                } => {
+                    tool_calls += 1;
+                    let agent_id = agent.id;
+                    let _ = logging::append(
+                        &LogEvent::new(
+                            "tool_call",
+                            format!("Agent {agent_id} calling tool {name} with args {args}"),
+                        )
+                        .agent(agent_id)
+                        .tool(&name),
+                    );
+                    let tool_response = if !tool_call_confirmed(&name, &args) {
+                        let _ = logging::append(
+                            &LogEvent::new(
+                                "tool_declined",
+                                format!("User declined agent {agent_id} calling tool {name}"),
+                            )
+                            .agent(agent_id)
+                            .tool(&name),
+                        );
+                        ToolOutput::Text(format!("User declined to run {name}."))
+                    } else {
+                        match tools::execute_tool_in(&name, &args, agent.workdir.as_deref()) {
+                            Ok(response) => response,
+                            Err(err) => {
+                                let message = format!("Tool {name} failed: {err}");
+                                let _ = logging::append(
+                                    &LogEvent::new(
+                                        "tool_failed",
+                                        format!("Agent {agent_id} failed: {message}"),
+                                    )
+                                    .agent(agent_id)
+                                    .tool(&name),
+                                );
+                                let final_action = if tools::builtin_declaration(&name).is_none() {
+                                    FinalAction::ToolRejected
+                                } else if err.is::<tools::InvalidArguments>() {
+                                    FinalAction::ToolInvalid
+                                } else {
+                                    FinalAction::ToolFailed
+                                };
+                                return Ok(ExecutionResult::Failure {
+                                    comment: message,
+                                    stats: RunStats::new(tool_calls, final_action),
+                                });
+                            }
+                        }
+                    };
+                    let _ = logging::append(
+                        &LogEvent::new(
+                            "tool_result",
+                            format!("Tool {name} responded with {tool_response}"),
+                        )
+                        .agent(agent_id)
+                        .tool(&name),
+                    );
+                    provider.append_tool_result(
+                        agent,
+                        history,
+                        &name,
+                        &args,
+                        &tool_response,
+                        call_id.as_deref(),
+                    );
+                }
+                ModelAction::Text { content } => {
+                    provider.append_text_reply(agent, history, &content);
+                    let _ = logging::append(
+                        &LogEvent::new(
+                            "finished",
+                            format!("Agent {} finished successfully: {}", agent.id, content),
+                        )
+                        .agent(agent.id),
+                    );
+                    return Ok(ExecutionResult::Success {
+                        comment: content,
+                        stats: RunStats::new(tool_calls, FinalAction::Text),
+                    });
+                }
             }
         }
     }
@@ -159,7 +674,7 @@ pub async fn execute_task(agent: &Agent, task: Option<&Task>) -> Result<Executio
 
 /// Describes an available tool for the language model.
 #[must_use = "register the declaration so the tool can be used"]
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct FunctionDeclaration {
     pub name: String,
     pub description: Option<String>,
@@ -172,7 +687,7 @@ fn empty_params() -> Value {
 }
 
 /// Configuration for an autonomous agent stored in `.taskter/agents.json`.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Default, Clone, Debug, JsonSchema)]
 pub struct Agent {
     pub id: usize,
     pub system_prompt: String,
@@ -184,6 +699,18 @@ pub struct Agent {
     pub schedule: Option<String>,
     #[serde(default)]
     pub repeat: bool,
+    /// Directory, relative to the project, that confines the agent's file
+    /// tools and serves as the shell tools' working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workdir: Option<PathBuf>,
+    /// How many times a task is re-run from scratch after a retriable
+    /// failure (see [`ExecutionResult::is_retriable`]).
+    #[serde(default)]
+    pub retry_on_failure: u32,
+    /// Upper bound in seconds on a whole `execute_task` run, across model
+    /// round-trips, tool calls and retries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_budget_secs: Option<u64>,
 }
 
 /// Loads the list of agents from `.taskter/agents.json`.
@@ -220,28 +747,59 @@ pub fn save_agents(agents: &[Agent]) -> anyhow::Result<()> {
     Ok(())
 }
 
-pub fn load_running_agents() -> anyhow::Result<Vec<usize>> {
+/// Opens `running_agents.json` and takes an advisory lock on it.
+///
+/// The lock is released when the returned handle is dropped, so concurrent
+/// guards and processes serialize their read-modify-write cycles.
+fn open_running_agents(exclusive: bool) -> anyhow::Result<fs::File> {
     let path = config::running_agents_path()?;
-    if !path.exists() {
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        fs::write(&path, "[]")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
     }
-    let content = fs::read_to_string(&path)?;
-    let ids: Vec<usize> = serde_json::from_str(&content)?;
-    Ok(ids)
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)?;
+    if exclusive {
+        file.lock()?;
+    } else {
+        file.lock_shared()?;
+    }
+    Ok(file)
 }
 
-pub fn save_running_agents(ids: &[usize]) -> anyhow::Result<()> {
-    let path = config::running_agents_path()?;
+fn read_running_agents(file: &mut fs::File) -> anyhow::Result<Vec<usize>> {
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn write_running_agents(file: &mut fs::File, ids: &[usize]) -> anyhow::Result<()> {
     let content = serde_json::to_string_pretty(ids)?;
-    fs::write(path, content)?;
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(content.as_bytes())?;
     Ok(())
 }
 
+pub fn load_running_agents() -> anyhow::Result<Vec<usize>> {
+    let mut file = open_running_agents(false)?;
+    read_running_agents(&mut file)
+}
+
+pub fn save_running_agents(ids: &[usize]) -> anyhow::Result<()> {
+    let mut file = open_running_agents(true)?;
+    write_running_agents(&mut file, ids)
+}
+
 pub fn set_agent_running(id: usize, running: bool) -> anyhow::Result<()> {
-    let mut ids = load_running_agents()?;
+    let mut file = open_running_agents(true)?;
+    let mut ids = read_running_agents(&mut file)?;
     if running {
         if !ids.contains(&id) {
             ids.push(id);
@@ -249,7 +807,7 @@ pub fn set_agent_running(id: usize, running: bool) -> anyhow::Result<()> {
     } else {
         ids.retain(|&x| x != id);
     }
-    save_running_agents(&ids)
+    write_running_agents(&mut file, &ids)
 }
 
 pub struct RunningAgentGuard {
@@ -269,6 +827,23 @@ impl Drop for RunningAgentGuard {
     }
 }
 
+/// Returns the names of tools referenced by `agent` that are neither built-in
+/// tools nor paths to existing tool declaration files.
+///
+/// Such tools would be rejected at execution time, so callers can use this to
+/// warn users before running the agent.
+pub fn unknown_tools(agent: &Agent) -> Vec<String> {
+    agent
+        .tools
+        .iter()
+        .filter(|t| {
+            tools::builtin_declaration(&t.name).is_none()
+                && !std::path::Path::new(&t.name).is_file()
+        })
+        .map(|t| t.name.clone())
+        .collect()
+}
+
 /// Convenience wrapper around [`load_agents`].
 ///
 /// # Errors
@@ -292,32 +867,51 @@ pub fn delete_agent(id: usize) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Changes applied by [`update_agent`]. Fields left as `None` keep their
+/// current value.
+#[derive(Debug, Default)]
+pub struct AgentUpdate {
+    pub prompt: Option<String>,
+    pub tools: Option<Vec<FunctionDeclaration>>,
+    pub model: Option<String>,
+    /// `Some(None)` clears the provider so it is inferred from the model.
+    pub provider: Option<Option<String>>,
+    /// Replaces the cron expression; not validated here.
+    pub schedule: Option<String>,
+    /// Whether the schedule recurs.
+    pub repeat: Option<bool>,
+    pub retry_on_failure: Option<u32>,
+}
+
 /// Updates an existing agent in `.taskter/agents.json`.
 ///
 /// # Errors
 ///
 /// Returns an error if the agent list cannot be loaded or saved.
-pub fn update_agent(
-    id: usize,
-    prompt: Option<String>,
-    tools: Option<Vec<FunctionDeclaration>>,
-    model: Option<String>,
-    provider: Option<Option<String>>,
-) -> anyhow::Result<()> {
+pub fn update_agent(id: usize, update: AgentUpdate) -> anyhow::Result<()> {
     let mut agents = load_agents()?;
     if let Some(agent) = agents.iter_mut().find(|a| a.id == id) {
-        if let Some(p) = prompt {
+        if let Some(p) = update.prompt {
             agent.system_prompt = p;
         }
-        if let Some(t) = tools {
+        if let Some(t) = update.tools {
             agent.tools = t;
         }
-        if let Some(m) = model {
+        if let Some(m) = update.model {
             agent.model = m;
         }
-        if let Some(pv) = provider {
+        if let Some(pv) = update.provider {
             agent.provider = pv;
         }
+        if let Some(s) = update.schedule {
+            agent.schedule = Some(s);
+        }
+        if let Some(r) = update.repeat {
+            agent.repeat = r;
+        }
+        if let Some(retries) = update.retry_on_failure {
+            agent.retry_on_failure = retries;
+        }
         save_agents(&agents)?;
     }
     Ok(())
@@ -343,6 +937,7 @@ mod tests {
             provider: Some("gemini".into()),
             schedule: None,
             repeat: false,
+            ..Default::default()
         };
         let provider = GeminiProvider;
         let history = provider.build_history(&agent, "hi");
@@ -362,13 +957,14 @@ mod tests {
             provider: None,
             schedule: None,
             repeat: false,
+            ..Default::default()
         };
         assert!(matches!(
-            simulate_without_api(&agent, true),
+            simulate_without_api(&agent, true, 0).unwrap(),
             ExecutionResult::Success { .. }
         ));
         assert!(matches!(
-            simulate_without_api(&agent, false),
+            simulate_without_api(&agent, false, 0).unwrap(),
             ExecutionResult::Failure { .. }
         ));
     }
@@ -383,6 +979,7 @@ mod tests {
             provider: None,
             schedule: None,
             repeat: false,
+            ..Default::default()
         };
         let provider = GeminiProvider;
         let mut history = Vec::new();
@@ -408,7 +1005,14 @@ mod tests {
                 call_id: _,
             } => {
                 let agent_ref = &agent;
-                provider.append_tool_result(agent_ref, &mut history, &name, &args, "ok", None);
+                provider.append_tool_result(
+                    agent_ref,
+                    &mut history,
+                    &name,
+                    &args,
+                    &"ok".into(),
+                    None,
+                );
                 assert_eq!(history.len(), 2);
             }
             _ => panic!("expected tool call"),
@@ -1,14 +1,23 @@
 //! Command-line interface definitions for Taskter.
 
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
 
 use crate::config::ConfigOverrides;
+use crate::store::TaskStatus;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
     #[command(flatten)]
     pub config: ConfigOverrides,
+    /// Suppress informational output such as success messages
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+    /// Initialize the board automatically instead of failing when it is missing
+    #[arg(long, global = true)]
+    pub auto_init: bool,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -17,7 +26,14 @@ pub struct Cli {
 #[command(rename_all = "kebab-case")]
 pub enum Commands {
     /// Initializes a new Taskter board
-    Init,
+    Init {
+        /// Reset existing project files to their defaults, keeping a `.bak` copy
+        #[arg(long)]
+        force: bool,
+        /// Project name shown by `show stats` and the board title
+        #[arg(long)]
+        name: Option<String>,
+    },
     /// Task management commands
     Task {
         #[command(subcommand)]
@@ -50,6 +66,11 @@ pub enum Commands {
         #[command(subcommand)]
         action: ToolCommands,
     },
+    /// Inspect model provider settings
+    Providers {
+        #[command(subcommand)]
+        action: ProviderCommands,
+    },
     /// Run the agent scheduler
     Scheduler {
         #[command(subcommand)]
@@ -64,6 +85,21 @@ pub enum Commands {
         /// The project description
         description: String,
     },
+    /// Prints JSON Schemas for Taskter's data files
+    Schema {
+        /// Only emit the schema for this type
+        #[arg(value_parser = ["board", "task", "agent", "okr", "function-declaration"])]
+        name: Option<String>,
+        /// Write one `<name>.schema.json` file per type into this directory
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+    },
+    /// Chat with an agent interactively; type `:quit` to exit
+    Repl {
+        /// The id of the agent to chat with
+        #[arg(short, long)]
+        agent_id: usize,
+    },
     /// Run the MCP (Model Context Protocol) server
     Mcp {
         #[command(subcommand)]
@@ -71,11 +107,103 @@ pub enum Commands {
     },
 }
 
+impl Commands {
+    /// Returns `true` for commands that write project data and therefore
+    /// require an initialized board.
+    pub fn mutates_project(&self) -> bool {
+        match self {
+            Commands::Task { action } => !matches!(action, TaskCommands::List { .. }),
+            Commands::Agent { action } => match action {
+                AgentCommands::List { .. } | AgentCommands::Running => false,
+                AgentCommands::Schedule { action } => {
+                    !matches!(action, ScheduleCommands::List { .. })
+                }
+                _ => true,
+            },
+            Commands::Okrs { action } => {
+                matches!(action, OkrCommands::Add { .. } | OkrCommands::Snapshot)
+            }
+            Commands::Logs { action } => {
+                matches!(action, LogCommands::Add { .. } | LogCommands::Prune { .. })
+            }
+            Commands::Scheduler { .. }
+            | Commands::Board
+            | Commands::Description { .. }
+            | Commands::Repl { .. } => true,
+            Commands::Init { .. }
+            | Commands::Show { .. }
+            | Commands::Tools { .. }
+            | Commands::Providers { .. }
+            | Commands::Schema { .. }
+            | Commands::Mcp { .. } => false,
+        }
+    }
+
+    /// Returns `true` when the command was asked for `--format json`, in
+    /// which case errors are reported as JSON objects as well.
+    pub fn json_output(&self) -> bool {
+        match self {
+            Commands::Show { what } => matches!(
+                what,
+                ShowCommands::Description {
+                    format: ShowFormat::Json
+                } | ShowCommands::Stats {
+                    format: ShowFormat::Json
+                } | ShowCommands::Velocity {
+                    format: ShowFormat::Json,
+                    ..
+                }
+            ),
+            Commands::Okrs {
+                action:
+                    OkrCommands::List {
+                        format: OkrFormat::Json,
+                    },
+            } => true,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 #[command(rename_all = "kebab-case")]
 pub enum ShowCommands {
     /// Shows the project description
-    Description,
+    Description {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ShowFormat::Text)]
+        format: ShowFormat,
+    },
+    /// Shows the board name, creation date and task counts per status
+    Stats {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ShowFormat::Text)]
+        format: ShowFormat,
+    },
+    /// Shows story points completed recently versus still open
+    Velocity {
+        /// Count tasks completed within this many days
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ShowFormat::Text)]
+        format: ShowFormat,
+    },
+    /// Prints a static snapshot of the board columns, for terminals without the TUI
+    Board {
+        /// Total width in characters (defaults to the terminal width, or 80)
+        #[arg(long)]
+        width: Option<usize>,
+    },
+}
+
+/// Output formats for the `show` commands.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ShowFormat {
+    /// Human-readable lines
+    Text,
+    /// A single JSON object
+    Json,
 }
 
 #[derive(Subcommand)]
@@ -91,7 +219,50 @@ pub enum OkrCommands {
         key_results: Vec<String>,
     },
     /// Lists all OKRs
-    List,
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OkrFormat::Json)]
+        format: OkrFormat,
+    },
+    /// Saves the current OKRs as a timestamped snapshot
+    Snapshot,
+    /// Lists OKR snapshots, or compares two of them
+    History {
+        /// Older snapshot to compare from
+        #[arg(long, requires = "to")]
+        from: Option<String>,
+        /// Newer snapshot to compare to (`current` for the live OKRs)
+        #[arg(long, requires = "from")]
+        to: Option<String>,
+    },
+    /// Shows per-key-result progress changes between two snapshots
+    Diff {
+        /// Older snapshot
+        a: String,
+        /// Newer snapshot (`current` for the live OKRs)
+        b: String,
+        /// Print arrows without ANSI colors
+        #[arg(long)]
+        no_color: bool,
+    },
+}
+
+/// Sections used by `task list --group-by`.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum TaskGrouping {
+    /// One section per column
+    Status,
+    /// One section per assigned agent, then "Unassigned"
+    Agent,
+}
+
+/// Output formats for `okrs list`.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OkrFormat {
+    /// The stored JSON
+    Json,
+    /// Objectives as headings with key-result checklists
+    Markdown,
 }
 
 #[derive(Subcommand)]
@@ -104,6 +275,12 @@ pub enum LogCommands {
     },
     /// Lists log entries
     List,
+    /// Removes log entries and provider response logs older than a given age
+    Prune {
+        /// Age such as `30d`, `12h` or `2w`
+        #[arg(long, value_parser = crate::logging::parse_age)]
+        older_than: chrono::Duration,
+    },
 }
 
 #[derive(Subcommand)]
@@ -111,6 +288,26 @@ pub enum LogCommands {
 pub enum ToolCommands {
     /// Lists built-in tools
     List,
+    /// Shows call counts, failures and timings recorded for each tool
+    Stats,
+    /// Runs a built-in tool once and prints its output
+    Call {
+        /// The tool name
+        name: String,
+        /// JSON object with the tool arguments
+        #[arg(long, conflicts_with = "args_stdin")]
+        args: Option<String>,
+        /// Read the JSON arguments from stdin
+        #[arg(long)]
+        args_stdin: bool,
+    },
+}
+
+#[derive(Subcommand)]
+#[command(rename_all = "kebab-case")]
+pub enum ProviderCommands {
+    /// Lists each provider's resolved endpoints and whether an API key is set
+    List,
 }
 
 #[derive(Subcommand)]
@@ -119,20 +316,56 @@ pub enum AgentCommands {
     /// Adds a new agent
     Add {
         /// The system prompt for the agent
-        #[arg(short, long)]
-        prompt: String,
+        #[arg(
+            short,
+            long,
+            required_unless_present_any = ["prompt_file", "interactive", "from_template"]
+        )]
+        prompt: Option<String>,
+        /// Read the system prompt from a file instead of `--prompt`
+        #[arg(long, conflicts_with = "prompt")]
+        prompt_file: Option<PathBuf>,
         /// The tools the agent can use
         #[arg(short, long, num_args = 1..)]
         tools: Vec<String>,
         /// The model to use for the agent
-        #[arg(short, long)]
-        model: String,
+        #[arg(short, long, required_unless_present_any = ["interactive", "from_template"])]
+        model: Option<String>,
         /// The provider to use for the agent (e.g. openai, gemini, ollama)
         #[arg(long)]
         provider: Option<String>,
+        /// Start from a built-in template (coder, researcher, emailer); explicit
+        /// `--prompt`, `--tools` and `--model` override its values
+        #[arg(long, value_name = "NAME")]
+        from_template: Option<String>,
+        /// Ask for any missing values step by step (requires a terminal)
+        #[arg(long)]
+        interactive: bool,
+        /// Confine the agent's file tools to this project subdirectory
+        #[arg(long)]
+        workdir: Option<PathBuf>,
+        /// Re-run a task up to this many times after a failed tool call
+        #[arg(long, default_value_t = 0)]
+        retry_on_failure: u32,
+        /// Stop a task run after this many seconds in total
+        #[arg(long, value_name = "SECS")]
+        execution_budget_secs: Option<u64>,
     },
     /// Lists all agents
-    List,
+    List {
+        /// Show at most this many agents
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Skip this many agents before listing
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+        /// Append how many board tasks each agent is assigned and has done
+        #[arg(long)]
+        with_tasks: bool,
+        /// Print one tab-separated line per agent in stable columns, for scripts
+        #[arg(long, conflicts_with = "with_tasks")]
+        porcelain: bool,
+    },
     /// Lists running agents
     Running,
     /// Removes an agent by id
@@ -149,6 +382,9 @@ pub enum AgentCommands {
         /// The new system prompt for the agent
         #[arg(short, long)]
         prompt: Option<String>,
+        /// Read the new system prompt from a file instead of `--prompt`
+        #[arg(long, conflicts_with = "prompt")]
+        prompt_file: Option<PathBuf>,
         /// The new tools the agent can use
         #[arg(short, long, num_args = 1..)]
         tools: Option<Vec<String>>,
@@ -158,6 +394,15 @@ pub enum AgentCommands {
         /// The new provider for the agent
         #[arg(long)]
         provider: Option<String>,
+        /// A new cron schedule, validated like `agent schedule set`
+        #[arg(long)]
+        cron: Option<String>,
+        /// Run the schedule only once instead of repeating
+        #[arg(long)]
+        once: bool,
+        /// Re-run a task up to this many times after a failed tool call
+        #[arg(long)]
+        retry_on_failure: Option<u32>,
     },
     /// Schedule operations for an agent
     Schedule {
@@ -179,7 +424,11 @@ pub enum ScheduleCommands {
         once: bool,
     },
     /// List scheduled agents
-    List,
+    List {
+        /// Also list agents without a schedule
+        #[arg(long)]
+        all: bool,
+    },
     /// Remove a schedule from an agent
     Remove {
         #[arg(long)]
@@ -191,7 +440,19 @@ pub enum ScheduleCommands {
 #[command(rename_all = "kebab-case")]
 pub enum SchedulerCommands {
     /// Run the scheduler loop
-    Run,
+    Run {
+        /// Serve Prometheus metrics at http://ADDR/metrics (e.g. 127.0.0.1:9464)
+        #[arg(long, value_name = "ADDR")]
+        metrics: Option<std::net::SocketAddr>,
+        /// Exit after this long, such as `90s`, `30m` or `1h`
+        #[arg(long = "for", value_name = "DURATION", value_parser = crate::logging::parse_age)]
+        run_for: Option<chrono::Duration>,
+        /// Run at most this many tasks at once (overrides `scheduler.max_concurrent_tasks`)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        concurrency: Option<u64>,
+    },
+    /// Ask a running scheduler to finish and exit
+    Stop,
 }
 
 #[derive(Subcommand)]
@@ -205,15 +466,67 @@ pub enum TaskCommands {
         /// The description of the task
         #[arg(short, long)]
         description: Option<String>,
+        /// Size of the task in story points
+        #[arg(long)]
+        estimate: Option<u32>,
+    },
+    /// Updates a task's title or description. Each field is optional.
+    Update {
+        /// The id of the task to update
+        #[arg(long)]
+        id: usize,
+        /// The new title of the task
+        #[arg(short, long)]
+        title: Option<String>,
+        /// The new description of the task
+        #[arg(short, long, conflicts_with = "clear_description")]
+        description: Option<String>,
+        /// Removes the task description
+        #[arg(long)]
+        clear_description: bool,
+        /// The new story point estimate
+        #[arg(long, conflicts_with = "clear_estimate")]
+        estimate: Option<u32>,
+        /// Removes the story point estimate
+        #[arg(long)]
+        clear_estimate: bool,
     },
     /// Lists all tasks
-    List,
+    List {
+        /// Show at most this many tasks
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Skip this many tasks before listing
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+        /// Section the list by status or by assigned agent
+        #[arg(long, value_enum, default_value_t = TaskGrouping::Status)]
+        group_by: TaskGrouping,
+        /// Print one tab-separated line per task in stable columns, for scripts
+        #[arg(long)]
+        porcelain: bool,
+    },
     /// Marks a task as complete
     Complete {
         /// The id of the task to mark as done
         #[arg(long)]
         id: usize,
     },
+    /// Moves several tasks from one column to another
+    Move {
+        /// Only move tasks currently in this status (ToDo, InProgress, Done)
+        #[arg(long)]
+        status_from: TaskStatus,
+        /// The status to move the tasks to
+        #[arg(long)]
+        status_to: TaskStatus,
+        /// The ids of the tasks to move
+        #[arg(long, num_args = 1.., required_unless_present = "all", conflicts_with = "all")]
+        ids: Vec<usize>,
+        /// Move every task in the source status
+        #[arg(long)]
+        all: bool,
+    },
     /// Adds a comment to a task
     Comment {
         /// The id of the task to comment on
@@ -222,18 +535,36 @@ pub enum TaskCommands {
         /// The comment text
         #[arg(short, long)]
         comment: String,
+        /// Attribute the comment to this agent id instead of the user
+        #[arg(long)]
+        from_agent: Option<usize>,
     },
     /// Executes a task with an agent
     Execute {
         /// The id of the task to execute
         #[arg(short, long)]
         task_id: usize,
+        /// Stream the agent's log lines to stderr while it runs
+        #[arg(long)]
+        follow: bool,
+        /// Give up after this many seconds and return the task to ToDo
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+        /// Run tools listed in `tools.require_confirmation` without prompting
+        #[arg(short, long)]
+        yes: bool,
     },
     /// Assigns an agent to a task
     Assign {
-        /// The id of the task to assign
-        #[arg(short, long)]
-        task_id: usize,
+        /// The ids of the tasks to assign
+        #[arg(
+            short,
+            long = "task-id",
+            visible_alias = "task-ids",
+            num_args = 1..,
+            required = true
+        )]
+        task_ids: Vec<usize>,
         /// The id of the agent to assign
         #[arg(short, long)]
         agent_id: usize,
@@ -250,5 +581,15 @@ pub enum TaskCommands {
 #[command(rename_all = "kebab-case")]
 pub enum McpCommands {
     /// Serve MCP over stdio
-    Serve,
+    Serve {
+        /// Append an MCP traffic trace to this file
+        #[arg(long, value_name = "PATH")]
+        trace: Option<PathBuf>,
+        /// Refuse this JSON-RPC method (e.g. `tools/call`); repeatable
+        #[arg(long = "disable-method", value_name = "METHOD")]
+        disable_methods: Vec<String>,
+        /// Reject requests other than `initialize`/`ping`/`shutdown` until `initialize` succeeds
+        #[arg(long)]
+        strict: bool,
+    },
 }
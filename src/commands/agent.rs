@@ -1,14 +1,19 @@
 #![allow(clippy::missing_errors_doc)]
 
 use std::fs;
-use std::path::Path;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
+use super::{agent_templates, porcelain_escape};
 use crate::agent::FunctionDeclaration;
 use crate::cli::{AgentCommands, ScheduleCommands};
+use crate::store::{self, Board, TaskStatus};
 use crate::{agent as agent_model, providers, tools};
 
+/// Resolves `--tools` values: JSON file paths, built-in names, and `all` or
+/// glob patterns such as `taskter_*` that expand to every matching built-in.
 pub fn parse_tool_specs(specs: &[String]) -> anyhow::Result<Vec<FunctionDeclaration>> {
-    let mut function_declarations = Vec::new();
+    let mut function_declarations: Vec<FunctionDeclaration> = Vec::new();
     for spec in specs {
         let decl = if Path::new(spec).exists() {
             let tool_content = fs::read_to_string(spec)?;
@@ -16,29 +21,284 @@ pub fn parse_tool_specs(specs: &[String]) -> anyhow::Result<Vec<FunctionDeclarat
             serde_json::from_value(tool_json)?
         } else if let Some(built) = tools::builtin_declaration(spec) {
             built
+        } else if let Some(names) = tools::matching_builtins(spec) {
+            if names.is_empty() {
+                anyhow::bail!("No built-in tools match `{spec}`");
+            }
+            // Aliases such as `email` share a declaration with their target.
+            for decl in names.into_iter().filter_map(tools::builtin_declaration) {
+                if !function_declarations.iter().any(|d| d.name == decl.name) {
+                    function_declarations.push(decl);
+                }
+            }
+            continue;
         } else {
-            return Err(anyhow::anyhow!(format!("Unknown tool: {spec}")));
+            return Err(anyhow::anyhow!(tools::unknown_tool_message(spec)));
         };
         function_declarations.push(decl);
     }
     Ok(function_declarations)
 }
 
+/// Resolves the system prompt from either the inline value or a prompt file.
+fn read_prompt(
+    prompt: Option<&String>,
+    prompt_file: Option<&PathBuf>,
+) -> anyhow::Result<Option<String>> {
+    match (prompt, prompt_file) {
+        (Some(text), _) => Ok(Some(text.clone())),
+        (None, Some(path)) => fs::read_to_string(path)
+            .map(Some)
+            .map_err(|e| anyhow::anyhow!("Failed to read prompt file {}: {e}", path.display())),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Values collected by the `agent add --interactive` wizard.
+struct AgentDraft {
+    prompt: String,
+    tools: Vec<String>,
+    model: String,
+    provider: Option<String>,
+}
+
+/// Prints `question` and returns the trimmed answer.
+fn ask(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+) -> anyhow::Result<String> {
+    write!(output, "{question}")?;
+    output.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        anyhow::bail!("Input ended before the agent was configured");
+    }
+    Ok(line.trim().to_string())
+}
+
+/// Asks `question` until a non-empty answer is given.
+fn ask_required(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+) -> anyhow::Result<String> {
+    loop {
+        let answer = ask(input, output, question)?;
+        if !answer.is_empty() {
+            return Ok(answer);
+        }
+    }
+}
+
+/// Maps a comma-separated answer of tool numbers or names to tool specs.
+fn select_tools(answer: &str, names: &[&str]) -> anyhow::Result<Vec<String>> {
+    let selected = answer
+        .split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(|token| match token.parse::<usize>() {
+            Ok(n) => n
+                .checked_sub(1)
+                .and_then(|i| names.get(i))
+                .map(|name| (*name).to_string())
+                .ok_or_else(|| anyhow::anyhow!("No tool numbered {n}")),
+            Err(_) => Ok(token.to_string()),
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    parse_tool_specs(&selected)?;
+    Ok(selected)
+}
+
+/// Prompts for every value not already supplied on the command line.
+fn run_wizard(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: Option<String>,
+    tools: &[String],
+    model: Option<&String>,
+    provider: Option<&String>,
+) -> anyhow::Result<AgentDraft> {
+    let prompt = match prompt {
+        Some(prompt) => prompt,
+        None => ask_required(input, output, "System prompt: ")?,
+    };
+
+    let tools = if tools.is_empty() {
+        let names = tools::builtin_names();
+        writeln!(output, "Available tools:")?;
+        for (i, name) in names.iter().enumerate() {
+            writeln!(output, "  {}. {name}", i + 1)?;
+        }
+        loop {
+            let answer = ask(
+                input,
+                output,
+                "Tools (numbers or names, comma-separated; blank for none): ",
+            )?;
+            match select_tools(&answer, &names) {
+                Ok(selected) => break selected,
+                Err(err) => writeln!(output, "{err}")?,
+            }
+        }
+    } else {
+        tools.to_vec()
+    };
+
+    let model = match model {
+        Some(model) => model.clone(),
+        None => ask_required(input, output, "Model (e.g. gpt-4.1, gemini-2.5-pro): ")?,
+    };
+
+    let provider = match provider {
+        Some(provider) => Some(provider.clone()),
+        None => loop {
+            let answer = ask(
+                input,
+                output,
+                "Provider (openai, gemini, ollama; blank to infer from the model): ",
+            )?;
+            if answer.is_empty() {
+                break None;
+            }
+            match providers::normalize_provider_id(&answer) {
+                Ok(_) => break Some(answer),
+                Err(err) => writeln!(output, "{err}")?,
+            }
+        },
+    };
+
+    Ok(AgentDraft {
+        prompt,
+        tools,
+        model,
+        provider,
+    })
+}
+
+/// Column order of `agent list --porcelain`. Scripts rely on it, so new
+/// columns may only be appended at the end:
+///
+/// 1. `id`
+/// 2. `provider`, as resolved for the agent
+/// 3. `model`
+/// 4. `running` (`yes` or `no`)
+/// 5. `tools`, comma-separated declaration names
+/// 6. `system_prompt`
+///
+/// Fields are escaped like those of `task list --porcelain`.
+pub const PORCELAIN_COLUMNS: [&str; 6] = [
+    "id",
+    "provider",
+    "model",
+    "running",
+    "tools",
+    "system_prompt",
+];
+
+/// Formats `agent` as a `--porcelain` line in [`PORCELAIN_COLUMNS`] order.
+fn porcelain_line(agent: &agent_model::Agent, running: bool) -> String {
+    let tools: Vec<_> = agent.tools.iter().map(|t| t.name.as_str()).collect();
+    [
+        agent.id.to_string(),
+        porcelain_escape(&providers::resolve_provider_name(agent)),
+        porcelain_escape(&agent.model),
+        if running { "yes" } else { "no" }.to_string(),
+        porcelain_escape(&tools.join(",")),
+        porcelain_escape(&agent.system_prompt),
+    ]
+    .join("\t")
+}
+
+/// Describes the tasks assigned to `agent_id`, e.g. `2 tasks (1 done)`.
+fn task_workload(board: &Board, agent_id: usize) -> String {
+    let assigned: Vec<_> = board
+        .tasks
+        .iter()
+        .filter(|t| t.agent_id == Some(agent_id))
+        .collect();
+    let done = assigned
+        .iter()
+        .filter(|t| t.status == TaskStatus::Done)
+        .count();
+    let noun = if assigned.len() == 1 { "task" } else { "tasks" };
+    format!("{} {noun} ({done} done)", assigned.len())
+}
+
+/// Whether the scheduler accepts `cron` as a job schedule.
+fn is_valid_cron(cron: &str) -> bool {
+    tokio_cron_scheduler::Job::new_async(cron, |_id, _| Box::pin(async {})).is_ok()
+}
+
 pub async fn handle(action: &AgentCommands) -> anyhow::Result<()> {
     match action {
         AgentCommands::Add {
             prompt,
+            prompt_file,
             tools,
             model,
             provider,
+            from_template,
+            interactive,
+            workdir,
+            retry_on_failure,
+            execution_budget_secs,
         } => {
+            let mut prompt = read_prompt(prompt.as_ref(), prompt_file.as_ref())?;
+            let mut tools = tools.clone();
+            let mut model = model.clone();
+            if let Some(name) = from_template {
+                let template = agent_templates::find(name)?;
+                prompt.get_or_insert_with(|| template.prompt.to_string());
+                if tools.is_empty() {
+                    tools = template.tools.iter().map(|t| (*t).to_string()).collect();
+                }
+                model.get_or_insert_with(|| template.model.to_string());
+            }
+            let draft = if *interactive {
+                if !io::stdin().is_terminal() {
+                    anyhow::bail!(
+                        "--interactive requires a terminal; pass --prompt, --tools and --model instead"
+                    );
+                }
+                run_wizard(
+                    &mut io::stdin().lock(),
+                    &mut io::stdout(),
+                    prompt,
+                    &tools,
+                    model.as_ref(),
+                    provider.as_ref(),
+                )?
+            } else {
+                let (Some(prompt), Some(model)) = (prompt, model) else {
+                    anyhow::bail!("--prompt (or --prompt-file) and --model are required");
+                };
+                AgentDraft {
+                    prompt,
+                    tools,
+                    model,
+                    provider: provider.clone(),
+                }
+            };
+            let AgentDraft {
+                prompt,
+                tools,
+                model,
+                provider,
+            } = draft;
             let mut agents = agent_model::load_agents()?;
-            let function_declarations = parse_tool_specs(tools)?;
+            let function_declarations = parse_tool_specs(&tools)?;
+            if let Some(dir) = workdir {
+                crate::tools::workdir::check(dir)?;
+            }
             let provider = if let Some(p) = provider {
                 Some(providers::normalize_provider_id(p.trim())?)
             } else {
                 None
             };
+            if let Some(warning) = providers::model_warning(&model, provider.as_deref()) {
+                eprintln!("{warning}");
+            }
             let next_id = agents
                 .iter()
                 .map(|a| a.id)
@@ -47,21 +307,42 @@ pub async fn handle(action: &AgentCommands) -> anyhow::Result<()> {
                 .saturating_add(1);
             let new_agent = agent_model::Agent {
                 id: next_id,
-                system_prompt: prompt.clone(),
+                system_prompt: prompt,
                 tools: function_declarations,
-                model: model.clone(),
+                model,
                 provider,
                 schedule: None,
                 repeat: false,
+                workdir: workdir.clone(),
+                retry_on_failure: *retry_on_failure,
+                execution_budget_secs: *execution_budget_secs,
             };
             agents.push(new_agent);
             agent_model::save_agents(&agents)?;
-            println!("Agent added successfully.");
+            info!("Agent added successfully.");
         }
-        AgentCommands::List => {
+        AgentCommands::List {
+            limit,
+            offset,
+            with_tasks,
+            porcelain,
+        } => {
             let agents = agent_model::list_agents()?;
             let running = agent_model::load_running_agents().unwrap_or_default();
-            for a in agents {
+            let board = if *with_tasks {
+                Some(store::load_board()?)
+            } else {
+                None
+            };
+            let (page, total) = super::paginate(agents, *offset, *limit);
+            let shown = page.len();
+            if *porcelain {
+                for a in &page {
+                    println!("{}", porcelain_line(a, running.contains(&a.id)));
+                }
+                return Ok(());
+            }
+            for a in page {
                 let tool_names = a
                     .tools
                     .iter()
@@ -74,10 +355,23 @@ pub async fn handle(action: &AgentCommands) -> anyhow::Result<()> {
                     ""
                 };
                 let provider_name = providers::resolve_provider_name(&a);
+                let workload = board
+                    .as_ref()
+                    .map(|b| format!(" [{}]", task_workload(b, a.id)))
+                    .unwrap_or_default();
                 println!(
-                    "{}: {} (provider: {}, model: {}, tools: {}){}",
-                    a.id, a.system_prompt, provider_name, a.model, tool_names, status
+                    "{}: {} (provider: {}, model: {}, tools: {}){}{}",
+                    a.id, a.system_prompt, provider_name, a.model, tool_names, status, workload
                 );
+                for name in agent_model::unknown_tools(&a) {
+                    eprintln!(
+                        "Warning: agent {} references unknown tool `{name}`; it will fail at execution.",
+                        a.id
+                    );
+                }
+            }
+            if limit.is_some() || *offset > 0 {
+                println!("{}", super::page_footer(*offset, shown, total, "agents"));
             }
         }
         AgentCommands::Running => {
@@ -97,15 +391,27 @@ pub async fn handle(action: &AgentCommands) -> anyhow::Result<()> {
         }
         AgentCommands::Remove { id } => {
             agent_model::delete_agent(*id)?;
-            println!("Agent {id} deleted.");
+            info!("Agent {id} deleted.");
         }
         AgentCommands::Update {
             id,
             prompt,
+            prompt_file,
             tools,
             model,
             provider,
+            cron,
+            once,
+            retry_on_failure,
         } => {
+            if cron.as_deref().is_some_and(|c| !is_valid_cron(c)) {
+                anyhow::bail!("Invalid cron expression");
+            }
+            let repeat = if *once {
+                Some(false)
+            } else {
+                cron.as_ref().map(|_| true)
+            };
             let function_declarations = if let Some(specs) = tools {
                 Some(parse_tool_specs(specs)?)
             } else {
@@ -123,17 +429,21 @@ pub async fn handle(action: &AgentCommands) -> anyhow::Result<()> {
             };
             agent_model::update_agent(
                 *id,
-                prompt.clone(),
-                function_declarations,
-                model.clone(),
-                provider_update,
+                agent_model::AgentUpdate {
+                    prompt: read_prompt(prompt.as_ref(), prompt_file.as_ref())?,
+                    tools: function_declarations,
+                    model: model.clone(),
+                    provider: provider_update,
+                    schedule: cron.clone(),
+                    repeat,
+                    retry_on_failure: *retry_on_failure,
+                },
             )?;
-            println!("Agent {id} updated.");
+            info!("Agent {id} updated.");
         }
         AgentCommands::Schedule { action } => match action {
             ScheduleCommands::Set { id, cron, once } => {
-                if tokio_cron_scheduler::Job::new_async(cron, |_id, _| Box::pin(async {})).is_err()
-                {
+                if !is_valid_cron(cron) {
                     println!("Invalid cron expression");
                 } else {
                     let mut agents = agent_model::load_agents()?;
@@ -141,21 +451,21 @@ pub async fn handle(action: &AgentCommands) -> anyhow::Result<()> {
                         a.schedule = Some(cron.clone());
                         a.repeat = !*once;
                         agent_model::save_agents(&agents)?;
-                        println!("Agent {id} scheduled.");
+                        info!("Agent {id} scheduled.");
                     } else {
                         println!("Agent {id} not found.");
                     }
                 }
             }
-            ScheduleCommands::List => {
+            ScheduleCommands::List { all } => {
                 let agents = agent_model::load_agents()?;
-                for a in agents.into_iter().filter(|a| a.schedule.is_some()) {
-                    println!(
-                        "{}: {} (repeat: {})",
-                        a.id,
-                        a.schedule.as_deref().unwrap_or(""),
-                        a.repeat
-                    );
+                for a in agents.into_iter().filter(|a| *all || a.schedule.is_some()) {
+                    match a.schedule.as_deref() {
+                        Some(schedule) => {
+                            println!("{}: {} (repeat: {})", a.id, schedule, a.repeat);
+                        }
+                        None => println!("{}: no schedule", a.id),
+                    }
                 }
             }
             ScheduleCommands::Remove { id } => {
@@ -164,7 +474,7 @@ pub async fn handle(action: &AgentCommands) -> anyhow::Result<()> {
                     a.schedule = None;
                     a.repeat = false;
                     agent_model::save_agents(&agents)?;
-                    println!("Schedule removed for agent {id}.");
+                    info!("Schedule removed for agent {id}.");
                 } else {
                     println!("Agent {id} not found.");
                 }
@@ -0,0 +1,50 @@
+//! Built-in agent templates for `agent add --from-template`.
+
+/// A ready-made agent definition selectable by name.
+pub struct AgentTemplate {
+    pub name: &'static str,
+    pub prompt: &'static str,
+    pub tools: &'static [&'static str],
+    pub model: &'static str,
+}
+
+/// Templates shipped with Taskter, in the order they are listed.
+pub const TEMPLATES: &[AgentTemplate] = &[
+    AgentTemplate {
+        name: "coder",
+        prompt: "You are a careful software engineer. Inspect the project files, \
+                 run commands and scripts to make and verify changes, and summarize \
+                 what you changed.",
+        tools: &["run_bash", "run_python", "project_files"],
+        model: "gemini-2.5-pro",
+    },
+    AgentTemplate {
+        name: "researcher",
+        prompt: "You are a research assistant. Search the web for the information \
+                 the task asks for and answer with a concise summary of what you found.",
+        tools: &["web_search", "get_description"],
+        model: "gemini-2.5-pro",
+    },
+    AgentTemplate {
+        name: "emailer",
+        prompt: "You are an assistant that writes and sends clear, polite emails \
+                 as described by the task.",
+        tools: &["send_email"],
+        model: "gemini-2.5-pro",
+    },
+];
+
+/// Looks up a template by name.
+///
+/// # Errors
+///
+/// Returns an error listing the available templates when `name` is unknown.
+pub fn find(name: &str) -> anyhow::Result<&'static AgentTemplate> {
+    TEMPLATES.iter().find(|t| t.name == name).ok_or_else(|| {
+        let names: Vec<&str> = TEMPLATES.iter().map(|t| t.name).collect();
+        anyhow::anyhow!(
+            "Unknown agent template `{name}`. Available templates: {}",
+            names.join(", ")
+        )
+    })
+}
@@ -2,8 +2,10 @@ use std::fs;
 
 use crate::config;
 
+/// Stores the project description using the configured `storage.line_endings`.
 pub fn set(description: &str) -> anyhow::Result<()> {
-    fs::write(config::description_path()?, description)?;
-    println!("Project description updated successfully.");
+    let contents = config::line_endings()?.apply(description);
+    fs::write(config::description_path()?, contents)?;
+    info!("Project description updated successfully.");
     Ok(())
 }
@@ -1,31 +1,117 @@
-use std::{fs, path::Path};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
 
 use crate::config;
+use crate::store::Board;
+
+/// What `init` did with a single project file.
+enum FileAction {
+    Created,
+    Skipped,
+    Replaced { backup: PathBuf },
+}
+
+/// Returns a backup path next to `path` that does not exist yet.
+fn backup_path(path: &Path) -> PathBuf {
+    let base = format!("{}.bak", path.display());
+    let mut candidate = PathBuf::from(&base);
+    let mut n = 1;
+    while candidate.exists() {
+        candidate = PathBuf::from(format!("{base}.{n}"));
+        n += 1;
+    }
+    candidate
+}
 
-fn ensure_file(path: &Path, contents: &str) -> anyhow::Result<bool> {
+/// Writes `contents` to `path` when it is missing or empty. Existing non-empty
+/// files are left alone unless `force` is set, in which case they are copied
+/// to a backup before being overwritten.
+fn ensure_file(path: &Path, contents: &str, force: bool) -> anyhow::Result<FileAction> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
-    if !path.exists() {
-        fs::write(path, contents)?;
-        return Ok(true);
+    if path.exists() {
+        let current = fs::read(path)?;
+        if current == contents.as_bytes() {
+            return Ok(FileAction::Skipped);
+        }
+        if !current.iter().all(u8::is_ascii_whitespace) {
+            if !force {
+                return Ok(FileAction::Skipped);
+            }
+            let backup = backup_path(path);
+            fs::copy(path, &backup)?;
+            fs::write(path, contents)?;
+            return Ok(FileAction::Replaced { backup });
+        }
     }
-    Ok(false)
+    fs::write(path, contents)?;
+    Ok(FileAction::Created)
 }
 
-pub fn run() -> anyhow::Result<()> {
+/// Initializes the board, creating any missing project files.
+///
+/// With `force`, existing files are reset to their defaults after being
+/// backed up alongside the original. A new board records `name` and the
+/// creation time.
+pub fn run(force: bool, name: Option<&str>) -> anyhow::Result<()> {
     let data_dir = config::dir()?;
     fs::create_dir_all(&data_dir)?;
+    let board = Board {
+        tasks: Vec::new(),
+        name: name.map(str::to_string),
+        created_at: Some(Utc::now()),
+    };
+    let board = serde_json::to_string_pretty(&board)?;
+    let files = [
+        (config::description_path()?, "# Project Description"),
+        (config::okrs_path()?, "[]"),
+        (config::log_path()?, ""),
+        (config::board_path()?, board.as_str()),
+        (config::agents_path()?, "[]"),
+    ];
     let mut created = false;
-    created = ensure_file(&config::description_path()?, "# Project Description")? || created;
-    created = ensure_file(&config::okrs_path()?, "[]")? || created;
-    created = ensure_file(&config::log_path()?, "")? || created;
-    created = ensure_file(&config::board_path()?, r#"{ "tasks": [] }"#)? || created;
-    created = ensure_file(&config::agents_path()?, "[]")? || created;
+    for (path, contents) in &files {
+        match ensure_file(path, contents, force)? {
+            FileAction::Created => {
+                created = true;
+                info!("Created {}", path.display());
+            }
+            FileAction::Skipped => info!("Skipped {} (already exists)", path.display()),
+            FileAction::Replaced { backup } => {
+                created = true;
+                info!(
+                    "Replaced {} (backup at {})",
+                    path.display(),
+                    backup.display()
+                );
+            }
+        }
+    }
     if created {
-        println!("Taskter board initialized.");
+        info!("Taskter board initialized.");
     } else {
-        println!("Taskter board already initialized.");
+        info!("Taskter board already initialized.");
     }
     Ok(())
 }
+
+/// Fails with guidance when the data directory is missing, or creates it when
+/// `auto_init` is set.
+pub fn ensure_initialized(auto_init: bool) -> anyhow::Result<()> {
+    let data_dir = config::dir()?;
+    if data_dir.is_dir() {
+        return Ok(());
+    }
+    if auto_init {
+        return run(false, None);
+    }
+    anyhow::bail!(
+        "No Taskter board found at {}. Run `taskter init` first or pass --auto-init.",
+        data_dir.display()
+    )
+}
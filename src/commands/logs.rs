@@ -1,26 +1,30 @@
 use std::fs;
-use std::io::Write;
-
-use chrono::Local;
 
 use crate::cli::LogCommands;
 use crate::config;
+use crate::logging::{self, LogEvent};
 
 pub fn handle(action: &LogCommands) -> anyhow::Result<()> {
     match action {
         LogCommands::Add { message } => {
-            let mut file = fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(config::log_path()?)?;
-            let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-            writeln!(file, "[{timestamp}] {message}")?;
-            println!("Log added successfully.");
+            logging::append(&LogEvent::new("note", message.as_str()))?;
+            info!("Log added successfully.");
         }
         LogCommands::List => {
             let logs = fs::read_to_string(config::log_path()?)?;
             println!("{logs}");
         }
+        LogCommands::Prune { older_than } => {
+            let Some(cutoff) = chrono::Local::now().checked_sub_signed(*older_than) else {
+                anyhow::bail!("--older-than reaches before the earliest supported date");
+            };
+            let cutoff = cutoff.naive_local();
+            let report = logging::prune(cutoff)?;
+            info!(
+                "Pruned {} log entries and {} files ({} bytes freed).",
+                report.lines, report.files, report.bytes
+            );
+        }
     }
     Ok(())
 }
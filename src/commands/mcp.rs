@@ -1,10 +1,20 @@
 use anyhow::Result;
 
 use crate::cli::McpCommands;
-use crate::mcp;
+use crate::{config, mcp};
 
 pub async fn handle(action: &McpCommands) -> Result<()> {
     match action {
-        McpCommands::Serve => mcp::serve_stdio().await,
+        McpCommands::Serve {
+            trace,
+            disable_methods,
+            strict,
+        } => {
+            let mut disabled = config::mcp_disabled_methods()?;
+            disabled.extend(disable_methods.iter().cloned());
+            let capabilities =
+                mcp::Capabilities::without(disabled).strict(*strict || config::mcp_strict()?);
+            mcp::serve_stdio(trace.as_deref(), &capabilities).await
+        }
     }
 }
@@ -1,13 +1,76 @@
 //! CLI subcommand implementations.
 
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables informational output for the current process.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Returns `true` when informational output should be suppressed.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Prints an informational message to stdout unless `--quiet` is active.
+///
+/// Data output such as list results should keep using `println!` directly.
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if !$crate::commands::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Returns the requested page of `items` together with the total count.
+fn paginate<T>(items: Vec<T>, offset: usize, limit: Option<usize>) -> (Vec<T>, usize) {
+    let total = items.len();
+    let page = items
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    (page, total)
+}
+
+/// Formats the "Showing X-Y of Z" footer printed by paginated list commands.
+fn page_footer(offset: usize, shown: usize, total: usize, noun: &str) -> String {
+    if shown == 0 {
+        format!("Showing 0 of {total} {noun}.")
+    } else {
+        format!(
+            "Showing {}-{} of {total} {noun}.",
+            offset + 1,
+            offset + shown
+        )
+    }
+}
+
+/// Escapes backslashes, tabs and line breaks in a `--porcelain` field so
+/// every record stays on one tab-separated line.
+fn porcelain_escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
 pub mod agent;
+pub mod agent_templates;
 pub mod board;
 pub mod description;
 pub mod init;
 pub mod logs;
 pub mod mcp;
 pub mod okrs;
+pub mod providers;
+pub mod repl;
 pub mod scheduler;
+pub mod schema;
 pub mod show;
 pub mod task;
 pub mod tools;
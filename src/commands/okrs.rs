@@ -1,5 +1,81 @@
-use crate::cli::OkrCommands;
-use crate::store;
+use std::io::IsTerminal;
+
+use crate::cli::{OkrCommands, OkrFormat};
+use crate::store::{self, Trend};
+
+/// Trims key-result names, drops blank entries and collapses duplicates while
+/// preserving the original order.
+fn dedupe_key_results(raw: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for kr in raw {
+        let name = kr.trim();
+        if name.is_empty() {
+            continue;
+        }
+        if names.iter().any(|existing| existing == name) {
+            eprintln!("Warning: duplicate key result `{name}` ignored.");
+            continue;
+        }
+        names.push(name.to_string());
+    }
+    names
+}
+
+fn format_progress(progress: Option<f32>) -> String {
+    progress.map_or_else(|| "-".to_string(), |p| format!("{:.0}%", p * 100.0))
+}
+
+/// Renders OKRs as Markdown: one heading per objective followed by a
+/// checklist of its key results, ticked once they reach 100%.
+fn okrs_markdown(okrs: &[store::Okr]) -> String {
+    let sections: Vec<String> = okrs
+        .iter()
+        .map(|okr| {
+            let mut section = format!("## {}\n\n", okr.objective);
+            for kr in &okr.key_results {
+                let mark = if kr.progress >= 1.0 { 'x' } else { ' ' };
+                section.push_str(&format!(
+                    "- [{mark}] {} ({})\n",
+                    kr.name,
+                    format_progress(Some(kr.progress))
+                ));
+            }
+            section
+        })
+        .collect();
+    sections.join("\n")
+}
+
+fn load_okr_set(name: &str) -> anyhow::Result<Vec<store::Okr>> {
+    if name == "current" {
+        store::load_okrs()
+    } else {
+        store::load_okr_snapshot(name)
+    }
+}
+
+/// Formats one line of `okrs diff`: an arrow for the direction of change,
+/// green for increases and red for decreases when `color` is set.
+fn format_delta(delta: &store::ProgressDelta, color: bool) -> String {
+    let (arrow, ansi) = match delta.trend() {
+        Trend::Up => ('↑', "\x1b[32m"),
+        Trend::Down => ('↓', "\x1b[31m"),
+        Trend::Unchanged => ('=', ""),
+    };
+    let line = format!(
+        "{arrow} {} / {}: {} -> {} ({:+.0}%)",
+        delta.objective,
+        delta.key_result,
+        format_progress(delta.before),
+        format_progress(delta.after),
+        delta.change() * 100.0
+    );
+    if color && !ansi.is_empty() {
+        format!("{ansi}{line}\x1b[0m")
+    } else {
+        line
+    }
+}
 
 pub fn handle(action: &OkrCommands) -> anyhow::Result<()> {
     match action {
@@ -7,24 +83,82 @@ pub fn handle(action: &OkrCommands) -> anyhow::Result<()> {
             objective,
             key_results,
         } => {
+            let objective = objective.trim();
+            if objective.is_empty() {
+                anyhow::bail!("Objective cannot be empty");
+            }
+            let names = dedupe_key_results(key_results);
+            if names.is_empty() {
+                anyhow::bail!("At least one key result is required");
+            }
             let mut okrs = store::load_okrs()?;
             let new_okr = store::Okr {
-                objective: objective.clone(),
-                key_results: key_results
-                    .iter()
-                    .map(|kr| store::KeyResult {
-                        name: kr.clone(),
+                objective: objective.to_string(),
+                key_results: names
+                    .into_iter()
+                    .map(|name| store::KeyResult {
+                        name,
                         progress: 0.0,
                     })
                     .collect(),
             };
             okrs.push(new_okr);
             store::save_okrs(&okrs)?;
-            println!("OKR added successfully.");
+            info!("OKR added successfully.");
         }
-        OkrCommands::List => {
+        OkrCommands::List { format } => {
             let okrs = store::load_okrs()?;
-            println!("{}", serde_json::to_string_pretty(&okrs)?);
+            match format {
+                OkrFormat::Json => println!("{}", serde_json::to_string_pretty(&okrs)?),
+                OkrFormat::Markdown => print!("{}", okrs_markdown(&okrs)),
+            }
+        }
+        OkrCommands::Snapshot => {
+            let name = store::snapshot_okrs()?;
+            info!("OKR snapshot {name} saved.");
+        }
+        OkrCommands::History { from, to } => match (from, to) {
+            (Some(from), Some(to)) => {
+                let before = store::load_okr_snapshot(from)?;
+                let after = load_okr_set(to)?;
+                let deltas = store::diff_okrs(&before, &after);
+                if deltas.is_empty() {
+                    println!("No progress changes between {from} and {to}.");
+                }
+                for delta in deltas {
+                    println!(
+                        "{} / {}: {} -> {} ({:+.0}%)",
+                        delta.objective,
+                        delta.key_result,
+                        format_progress(delta.before),
+                        format_progress(delta.after),
+                        delta.change() * 100.0
+                    );
+                }
+            }
+            _ => {
+                let snapshots = store::list_okr_snapshots()?;
+                if snapshots.is_empty() {
+                    println!("No OKR snapshots found.");
+                }
+                for name in snapshots {
+                    println!("{name}");
+                }
+            }
+        },
+        OkrCommands::Diff { a, b, no_color } => {
+            let before = store::load_okr_snapshot(a)?;
+            let after = load_okr_set(b)?;
+            let color = !no_color
+                && std::env::var_os("NO_COLOR").is_none()
+                && std::io::stdout().is_terminal();
+            let deltas = store::compare_okrs(&before, &after);
+            if deltas.is_empty() {
+                println!("No key results in {a} or {b}.");
+            }
+            for delta in &deltas {
+                println!("{}", format_delta(delta, color));
+            }
         }
     }
     Ok(())
@@ -0,0 +1,65 @@
+use crate::cli::ProviderCommands;
+use crate::config;
+use crate::providers::{gemini, ollama::OllamaProvider};
+
+pub fn handle(action: &ProviderCommands) -> anyhow::Result<()> {
+    match action {
+        ProviderCommands::List => {
+            println!("Default provider: {}", config::default_provider()?);
+
+            let openai = config::openai()?;
+            println!();
+            println!("openai");
+            print_key(openai.api_key.as_deref());
+            println!("  base url: {}", openai.base_url);
+            println!("  responses endpoint: {}", openai.responses_endpoint);
+            println!("  chat endpoint: {}", openai.chat_endpoint);
+            println!(
+                "  request style: {}",
+                openai.request_style.as_deref().unwrap_or("auto (by model)")
+            );
+            if let Some(format) = &openai.response_format {
+                println!("  response format: {format}");
+            }
+            if let Some(organization) = &openai.organization {
+                println!("  organization: {organization}");
+            }
+            if let Some(project) = &openai.project {
+                println!("  project: {project}");
+            }
+
+            let gemini = config::gemini()?;
+            println!();
+            println!("gemini");
+            print_key(gemini.api_key.as_deref());
+            println!("  endpoint: {}/<model>:generateContent", gemini::MODELS_URL);
+
+            let ollama = config::ollama()?;
+            println!();
+            println!("ollama");
+            print_key(ollama.api_key.as_deref());
+            println!("  base url: {}", ollama.base_url);
+            println!("  chat endpoint: {}", OllamaProvider::endpoint_url());
+        }
+    }
+    Ok(())
+}
+
+fn print_key(key: Option<&str>) {
+    match key {
+        Some(key) => println!("  api key: {}", mask_key(key)),
+        None => println!("  api key: not set"),
+    }
+}
+
+/// Hides all but the first three and last four characters, e.g. `sk-…abcd`.
+/// Short keys are hidden entirely.
+fn mask_key(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() < 12 {
+        return "set (hidden)".to_string();
+    }
+    let head: String = chars[..3].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{head}…{tail}")
+}
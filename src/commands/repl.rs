@@ -0,0 +1,58 @@
+use std::io::{self, IsTerminal, Write};
+
+use crate::agent::{self, Conversation, ExecutionResult};
+
+/// Line that ends the chat.
+const QUIT: &str = ":quit";
+
+/// Chats with agent `agent_id` over stdin and stdout until `:quit` or the end
+/// of input. Each message is sent with the earlier turns, and tool calls run
+/// as they are requested.
+pub async fn run(agent_id: usize) -> anyhow::Result<()> {
+    let Some(found) = agent::load_agents()?.into_iter().find(|a| a.id == agent_id) else {
+        anyhow::bail!("Agent with id {agent_id} not found.");
+    };
+    let mut conversation = Conversation::new(found)?;
+
+    // Confirmation prompts read stdin too, so each line is read without
+    // holding the stdin lock across a turn.
+    let confirm = io::stdin().is_terminal();
+    if confirm {
+        agent::set_confirmation(Some(Box::new(|name, args| {
+            agent::ask_confirmation(&mut io::stdin().lock(), &mut io::stderr(), name, args)
+                .unwrap_or(false)
+        })));
+    }
+    info!("Chatting with agent {agent_id}. Type {QUIT} to exit.");
+
+    let outcome = chat(&mut conversation, agent_id).await;
+    if confirm {
+        agent::set_confirmation(None);
+    }
+    outcome
+}
+
+async fn chat(conversation: &mut Conversation, agent_id: usize) -> anyhow::Result<()> {
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            println!();
+            return Ok(());
+        }
+        let message = line.trim();
+        if message == QUIT {
+            return Ok(());
+        }
+        if message.is_empty() {
+            continue;
+        }
+        match conversation.send(message).await? {
+            ExecutionResult::Success { comment, .. } => println!("{comment}"),
+            ExecutionResult::Failure { comment, .. } => {
+                eprintln!("Agent {agent_id} failed: {comment}");
+            }
+        }
+    }
+}
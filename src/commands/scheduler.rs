@@ -3,8 +3,29 @@ use crate::scheduler;
 
 pub async fn handle(action: &SchedulerCommands) -> anyhow::Result<()> {
     match action {
-        SchedulerCommands::Run => {
-            scheduler::run().await?;
+        SchedulerCommands::Run {
+            metrics,
+            run_for,
+            concurrency,
+        } => {
+            let run_for = match run_for {
+                Some(duration) => Some(
+                    duration
+                        .to_std()
+                        .map_err(|_| anyhow::anyhow!("--for must not be negative"))?,
+                ),
+                None => None,
+            };
+            scheduler::run_with(scheduler::RunOptions {
+                metrics_addr: *metrics,
+                run_for,
+                max_concurrent_tasks: concurrency.and_then(|n| usize::try_from(n).ok()),
+            })
+            .await?;
+        }
+        SchedulerCommands::Stop => {
+            scheduler::request_stop()?;
+            info!("Stop requested; the running scheduler will exit shortly.");
         }
     }
     Ok(())
@@ -0,0 +1,48 @@
+use std::fs;
+use std::path::Path;
+
+use schemars::{schema_for, Schema};
+use serde_json::Value;
+
+use crate::agent::{Agent, FunctionDeclaration};
+use crate::store::{Board, Okr, Task};
+
+/// Returns the JSON Schema of every persisted type, keyed by CLI name.
+pub fn schemas() -> Vec<(&'static str, Schema)> {
+    vec![
+        ("board", schema_for!(Board)),
+        ("task", schema_for!(Task)),
+        ("agent", schema_for!(Agent)),
+        ("okr", schema_for!(Okr)),
+        ("function-declaration", schema_for!(FunctionDeclaration)),
+    ]
+}
+
+pub fn handle(name: Option<&str>, out_dir: Option<&Path>) -> anyhow::Result<()> {
+    let selected: Vec<_> = schemas()
+        .into_iter()
+        .filter(|(schema_name, _)| name.is_none_or(|n| n == *schema_name))
+        .collect();
+
+    if let Some(dir) = out_dir {
+        fs::create_dir_all(dir)?;
+        for (schema_name, schema) in &selected {
+            let path = dir.join(format!("{schema_name}.schema.json"));
+            fs::write(&path, serde_json::to_string_pretty(schema)?)?;
+            info!("Wrote {}", path.display());
+        }
+        return Ok(());
+    }
+
+    let output = match (name, selected.as_slice()) {
+        (Some(_), [(_, schema)]) => schema.as_value().clone(),
+        _ => Value::Object(
+            selected
+                .into_iter()
+                .map(|(schema_name, schema)| (schema_name.to_string(), schema.to_value()))
+                .collect(),
+        ),
+    };
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
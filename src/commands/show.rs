@@ -1,14 +1,168 @@
 use std::fs;
 
-use crate::cli::ShowCommands;
-use crate::config;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde_json::json;
+
+use crate::cli::{ShowCommands, ShowFormat};
+use crate::store::{Board, TaskStatus};
+use crate::{config, store};
 
 pub fn handle(what: &ShowCommands) -> anyhow::Result<()> {
     match what {
-        ShowCommands::Description => {
+        ShowCommands::Description { format } => {
             let description = fs::read_to_string(config::description_path()?)?;
-            println!("{description}");
+            match format {
+                ShowFormat::Text => println!("{description}"),
+                ShowFormat::Json => print_json(&json!({ "description": description }))?,
+            }
+        }
+        ShowCommands::Velocity { days, format } => {
+            let board = store::load_board()?;
+            // A window reaching past the earliest representable time covers
+            // every completed task.
+            let since = TimeDelta::try_days(i64::from(*days))
+                .and_then(|window| Utc::now().checked_sub_signed(window))
+                .unwrap_or(DateTime::<Utc>::MIN_UTC);
+            let velocity = board.velocity(since);
+            match format {
+                ShowFormat::Text => {
+                    println!(
+                        "Completed in the last {days} days: {} points",
+                        velocity.completed
+                    );
+                    println!("Remaining: {} points", velocity.remaining);
+                }
+                ShowFormat::Json => print_json(&json!({
+                    "days": days,
+                    "completed": velocity.completed,
+                    "remaining": velocity.remaining,
+                }))?,
+            }
+        }
+        ShowCommands::Board { width } => {
+            let board = store::load_board()?;
+            let width = width
+                .or_else(|| {
+                    crossterm::terminal::size()
+                        .ok()
+                        .map(|(cols, _)| usize::from(cols))
+                })
+                .unwrap_or(80);
+            print!("{}", render_board(&board, width));
+        }
+        ShowCommands::Stats { format } => {
+            let board = store::load_board()?;
+            let counts: Vec<(TaskStatus, usize)> =
+                [TaskStatus::ToDo, TaskStatus::InProgress, TaskStatus::Done]
+                    .into_iter()
+                    .map(|status| {
+                        let count = board.tasks.iter().filter(|t| t.status == status).count();
+                        (status, count)
+                    })
+                    .collect();
+            match format {
+                ShowFormat::Text => {
+                    if let Some(name) = &board.name {
+                        println!("Board: {name}");
+                    }
+                    if let Some(created_at) = board.created_at {
+                        println!("Created: {}", created_at.format("%Y-%m-%d %H:%M UTC"));
+                    }
+                    for (status, count) in &counts {
+                        println!("{status:?}: {count}");
+                    }
+                    println!("Total: {}", board.tasks.len());
+                }
+                ShowFormat::Json => {
+                    let by_status: serde_json::Map<String, serde_json::Value> = counts
+                        .iter()
+                        .map(|(status, count)| (format!("{status:?}"), json!(count)))
+                        .collect();
+                    print_json(&json!({
+                        "name": board.name,
+                        "created_at": board.created_at,
+                        "counts": by_status,
+                        "total": board.tasks.len(),
+                    }))?;
+                }
+            }
         }
     }
     Ok(())
 }
+
+/// Lays the board out as an ASCII table with one column per status, mirroring
+/// the TUI's columns.
+fn render_board(board: &Board, width: usize) -> String {
+    let statuses = [TaskStatus::ToDo, TaskStatus::InProgress, TaskStatus::Done];
+    let columns: Vec<(String, Vec<String>)> = statuses
+        .iter()
+        .map(|status| {
+            let cells: Vec<String> = board
+                .tasks
+                .iter()
+                .filter(|t| t.status == *status)
+                .map(|t| format!("{}: {}", t.id, t.title))
+                .collect();
+            (format!("{status:?} [{}]", cells.len()), cells)
+        })
+        .collect();
+
+    // Each column is `| ` + text + ` `, plus the closing `|`.
+    let cell = (width.saturating_sub(1) / columns.len())
+        .saturating_sub(3)
+        .max(8);
+    let rule = format!(
+        "+{}\n",
+        columns
+            .iter()
+            .map(|_| format!("{}+", "-".repeat(cell + 2)))
+            .collect::<String>()
+    );
+    let row = |texts: Vec<&str>| {
+        let mut line = String::new();
+        for text in texts {
+            line.push_str(&format!("| {} ", fit(text, cell)));
+        }
+        line.push_str("|\n");
+        line
+    };
+
+    let mut out = rule.clone();
+    out.push_str(&row(columns
+        .iter()
+        .map(|(title, _)| title.as_str())
+        .collect()));
+    out.push_str(&rule);
+    let depth = columns
+        .iter()
+        .map(|(_, cells)| cells.len())
+        .max()
+        .unwrap_or(0);
+    for i in 0..depth {
+        out.push_str(&row(columns
+            .iter()
+            .map(|(_, cells)| cells.get(i).map_or("", String::as_str))
+            .collect()));
+    }
+    if depth > 0 {
+        out.push_str(&rule);
+    }
+    out
+}
+
+/// Pads `text` to `width` characters, cutting it with `...` when too long.
+fn fit(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return format!("{text}{}", " ".repeat(width - len));
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(3)).collect();
+    cut.push_str("...");
+    cut
+}
+
+fn print_json(value: &serde_json::Value) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -1,7 +1,11 @@
 //! Task subcommand handlers.
 
-use crate::cli::TaskCommands;
-use crate::{agent, store};
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+
+use super::porcelain_escape;
+use crate::cli::{TaskCommands, TaskGrouping};
+use crate::{agent, logging, store};
 
 fn print_task(task: &store::Task) {
     match &task.description {
@@ -14,100 +18,316 @@ fn print_task(task: &store::Task) {
     }
 }
 
+/// Column order of `task list --porcelain`. Scripts rely on it, so new
+/// columns may only be appended at the end:
+///
+/// 1. `id`
+/// 2. `status` (`ToDo`, `InProgress` or `Done`)
+/// 3. `agent_id`, empty when unassigned
+/// 4. `estimate` in story points, empty when unset
+/// 5. `title`
+/// 6. `description`, empty when unset
+///
+/// Backslashes, tabs and line breaks inside fields are escaped as `\\`,
+/// `\t`, `\n` and `\r`, so every task stays on one line.
+pub const PORCELAIN_COLUMNS: [&str; 6] = [
+    "id",
+    "status",
+    "agent_id",
+    "estimate",
+    "title",
+    "description",
+];
+
+/// Formats `task` as a `--porcelain` line in [`PORCELAIN_COLUMNS`] order.
+fn porcelain_line(task: &store::Task) -> String {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    [
+        task.id.to_string(),
+        format!("{:?}", task.status),
+        optional(task.agent_id.map(|id| id.to_string())),
+        optional(task.estimate.map(|points| points.to_string())),
+        porcelain_escape(&task.title),
+        optional(task.description.as_deref().map(porcelain_escape)),
+    ]
+    .join("\t")
+}
+
+/// Splits `tasks` into titled sections, keeping their order within each
+/// section and skipping empty ones. Agent sections are sorted by id and
+/// followed by "Unassigned".
+fn group_tasks(tasks: Vec<store::Task>, group_by: TaskGrouping) -> Vec<(String, Vec<store::Task>)> {
+    let mut sections: Vec<(String, Vec<store::Task>)> = match group_by {
+        TaskGrouping::Status => [
+            store::TaskStatus::ToDo,
+            store::TaskStatus::InProgress,
+            store::TaskStatus::Done,
+        ]
+        .into_iter()
+        .map(|status| (format!("{status:?}"), Vec::new()))
+        .collect(),
+        TaskGrouping::Agent => {
+            let mut ids: Vec<usize> = tasks.iter().filter_map(|t| t.agent_id).collect();
+            ids.sort_unstable();
+            ids.dedup();
+            ids.into_iter()
+                .map(|id| (format!("Agent {id}"), Vec::new()))
+                .chain(std::iter::once(("Unassigned".to_string(), Vec::new())))
+                .collect()
+        }
+    };
+    for task in tasks {
+        let header = match group_by {
+            TaskGrouping::Status => format!("{:?}", task.status),
+            TaskGrouping::Agent => task
+                .agent_id
+                .map_or_else(|| "Unassigned".to_string(), |id| format!("Agent {id}")),
+        };
+        if let Some((_, section)) = sections.iter_mut().find(|(h, _)| *h == header) {
+            section.push(task);
+        }
+    }
+    sections.retain(|(_, tasks)| !tasks.is_empty());
+    sections
+}
+
+fn describe_steps(tool_calls: usize) -> String {
+    match tool_calls {
+        0 => "no tool calls".to_string(),
+        1 => "1 tool call".to_string(),
+        n => format!("{n} tool calls"),
+    }
+}
+
 pub async fn handle(action: &TaskCommands) -> anyhow::Result<()> {
     match action {
-        TaskCommands::Add { title, description } => {
-            let mut board = store::load_board()?;
-            let new_task = store::Task {
-                id: board.next_task_id(),
-                title: title.clone(),
-                description: description.clone(),
-                status: store::TaskStatus::ToDo,
-                agent_id: None,
-                comment: None,
-            };
-            board.tasks.push(new_task);
-            store::save_board(&board)?;
-            println!("Task added successfully.");
+        TaskCommands::Add {
+            title,
+            description,
+            estimate,
+        } => {
+            store::update_board(|board| {
+                let new_task = store::Task {
+                    id: board.next_task_id(),
+                    title: title.clone(),
+                    description: description.clone(),
+                    status: store::TaskStatus::ToDo,
+                    agent_id: None,
+                    comment: None,
+                    comment_author: None,
+                    estimate: *estimate,
+                    completed_at: None,
+                };
+                board.tasks.push(new_task);
+            })?;
+            info!("Task added successfully.");
         }
-        TaskCommands::List => {
-            let board = store::load_board()?;
-            let mut todo = Vec::new();
-            let mut in_progress = Vec::new();
-            let mut done = Vec::new();
-
-            for task in board.tasks {
-                match task.status {
-                    store::TaskStatus::ToDo => todo.push(task),
-                    store::TaskStatus::InProgress => in_progress.push(task),
-                    store::TaskStatus::Done => done.push(task),
+        TaskCommands::Update {
+            id,
+            title,
+            description,
+            clear_description,
+            estimate,
+            clear_estimate,
+        } => {
+            let found = store::update_board(|board| {
+                let Some(task) = board.task_mut(*id) else {
+                    return false;
+                };
+                if let Some(t) = title {
+                    task.title = t.clone();
+                }
+                if *clear_description {
+                    task.description = None;
+                } else if let Some(d) = description {
+                    task.description = Some(d.clone());
                 }
+                if *clear_estimate {
+                    task.estimate = None;
+                } else if estimate.is_some() {
+                    task.estimate = *estimate;
+                }
+                true
+            })?;
+            if found {
+                info!("Task {id} updated.");
+            } else {
+                println!("Task with id {id} not found.");
             }
+        }
+        TaskCommands::List {
+            limit,
+            offset,
+            group_by,
+            porcelain,
+        } => {
+            let board = store::load_board()?;
+            let (page, total) = super::paginate(board.tasks, *offset, *limit);
+            let shown = page.len();
 
-            if !todo.is_empty() {
-                println!("ToDo:");
-                for task in &todo {
-                    print_task(task);
+            if *porcelain {
+                for task in &page {
+                    println!("{}", porcelain_line(task));
                 }
-                println!();
+                return Ok(());
             }
 
-            if !in_progress.is_empty() {
-                println!("InProgress:");
-                for task in &in_progress {
+            for (i, (header, tasks)) in group_tasks(page, *group_by).iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                println!("{header}:");
+                for task in tasks {
                     print_task(task);
                 }
-                println!();
             }
 
-            if !done.is_empty() {
-                println!("Done:");
-                for task in &done {
-                    print_task(task);
-                }
+            if limit.is_some() || *offset > 0 {
+                println!();
+                println!("{}", super::page_footer(*offset, shown, total, "tasks"));
             }
         }
         TaskCommands::Complete { id } => {
-            let mut board = store::load_board()?;
-            if let Some(task) = board.tasks.iter_mut().find(|t| t.id == *id) {
-                task.status = store::TaskStatus::Done;
-                store::save_board(&board)?;
-                println!("Task {id} marked as done.");
+            if store::update_board(|board| board.move_task(*id, store::TaskStatus::Done))? {
+                info!("Task {id} marked as done.");
             } else {
                 println!("Task with id {id} not found.");
             }
         }
-        TaskCommands::Comment { task_id, comment } => {
-            let mut board = store::load_board()?;
-            if let Some(task) = board.tasks.iter_mut().find(|t| t.id == *task_id) {
-                task.comment = Some(comment.clone());
-                store::save_board(&board)?;
-                println!("Comment added to task {task_id}.");
+        TaskCommands::Move {
+            status_from,
+            status_to,
+            ids,
+            all,
+        } => {
+            let moved = store::update_board(|board| {
+                let ids: Vec<usize> = if *all {
+                    board
+                        .tasks
+                        .iter()
+                        .filter(|t| t.status == *status_from)
+                        .map(|t| t.id)
+                        .collect()
+                } else {
+                    ids.clone()
+                };
+                let mut moved = 0;
+                for id in ids {
+                    match board
+                        .tasks
+                        .iter()
+                        .find(|t| t.id == id)
+                        .map(|t| t.status.clone())
+                    {
+                        None => println!("Task with id {id} not found."),
+                        Some(status) if status != *status_from => {
+                            eprintln!(
+                                "Warning: task {id} is {status:?}, not {status_from:?}; skipped."
+                            );
+                        }
+                        Some(_) => {
+                            board.move_task(id, status_to.clone());
+                            moved += 1;
+                        }
+                    }
+                }
+                moved
+            })?;
+            info!("Moved {moved} task(s) from {status_from:?} to {status_to:?}.");
+        }
+        TaskCommands::Comment {
+            task_id,
+            comment,
+            from_agent,
+        } => {
+            let author = from_agent.map_or(store::CommentAuthor::User, store::CommentAuthor::Agent);
+            let found = store::update_board(|board| match board.task_mut(*task_id) {
+                Some(task) => {
+                    task.set_comment(comment.clone(), author);
+                    true
+                }
+                None => false,
+            })?;
+            if found {
+                info!("Comment added to task {task_id}.");
             } else {
                 println!("Task with id {task_id} not found.");
             }
         }
-        TaskCommands::Execute { task_id } => {
-            let mut board = store::load_board()?;
+        TaskCommands::Execute {
+            task_id,
+            follow,
+            timeout,
+            yes,
+        } => {
+            let board = store::load_board()?;
             let agents = agent::load_agents()?;
+            if *follow {
+                logging::set_follower(Some(Box::new(|line| eprintln!("{line}"))));
+            }
+            let confirm = !*yes && io::stdin().is_terminal();
+            if confirm {
+                agent::set_confirmation(Some(Box::new(|name, args| {
+                    agent::ask_confirmation(&mut io::stdin().lock(), &mut io::stderr(), name, args)
+                        .unwrap_or(false)
+                })));
+            }
 
-            if let Some(task) = board.tasks.iter_mut().find(|t| t.id == *task_id) {
+            if let Some(task) = board.tasks.iter().find(|t| t.id == *task_id).cloned() {
                 if let Some(agent_id) = task.agent_id {
                     if let Some(a) = agents.iter().find(|a| a.id == agent_id) {
-                        match agent::execute_task(a, Some(task)).await {
-                            Ok(result) => match result {
-                                agent::ExecutionResult::Success { comment } => {
-                                    task.status = store::TaskStatus::Done;
-                                    task.comment = Some(comment);
-                                    println!("Task {task_id} executed successfully.");
-                                }
-                                agent::ExecutionResult::Failure { comment } => {
-                                    task.status = store::TaskStatus::ToDo;
-                                    task.comment = Some(comment);
-                                    task.agent_id = None;
+                        let execution = agent::execute_task(a, Some(&task));
+                        let outcome = match timeout {
+                            Some(secs) => {
+                                tokio::time::timeout(Duration::from_secs(*secs), execution)
+                                    .await
+                                    .unwrap_or_else(|_| {
+                                        Ok(agent::ExecutionResult::Failure {
+                                            comment: "execution timed out".to_string(),
+                                            stats: agent::RunStats::new(
+                                                0,
+                                                agent::FinalAction::Interrupted,
+                                            ),
+                                        })
+                                    })
+                            }
+                            None => execution.await,
+                        };
+                        match outcome {
+                            Ok(result) => {
+                                // The run can take minutes, so the result is applied
+                                // to a fresh copy of the board under the board lock.
+                                let succeeded =
+                                    matches!(result, agent::ExecutionResult::Success { .. });
+                                let stats = result.stats();
+                                store::update_board(|board| {
+                                    let comment = match result {
+                                        agent::ExecutionResult::Success { comment, .. } => {
+                                            board.move_task(*task_id, store::TaskStatus::Done);
+                                            comment
+                                        }
+                                        agent::ExecutionResult::Failure { comment, .. } => {
+                                            board.return_task(*task_id);
+                                            comment
+                                        }
+                                    };
+                                    if let Some(task) = board.task_mut(*task_id) {
+                                        task.set_comment(
+                                            comment,
+                                            store::CommentAuthor::Agent(agent_id),
+                                        );
+                                    }
+                                })?;
+                                if succeeded {
+                                    info!("Task {task_id} executed successfully.");
+                                    info!(
+                                        "Agent finished after {}.",
+                                        describe_steps(stats.tool_calls)
+                                    );
+                                } else {
                                     println!("Task {task_id} failed to execute.");
                                 }
-                            },
+                            }
                             Err(e) => {
                                 println!("Error executing task {task_id}: {e}");
                             }
@@ -122,24 +342,38 @@ pub async fn handle(action: &TaskCommands) -> anyhow::Result<()> {
                 println!("Task with id {task_id} not found.");
             }
 
-            store::save_board(&board)?;
+            if *follow {
+                logging::set_follower(None);
+            }
+            if confirm {
+                agent::set_confirmation(None);
+            }
         }
-        TaskCommands::Assign { task_id, agent_id } => {
-            let mut board = store::load_board()?;
-            if let Some(task) = board.tasks.iter_mut().find(|t| t.id == *task_id) {
-                task.agent_id = Some(*agent_id);
-                store::save_board(&board)?;
-                println!("Agent {agent_id} assigned to task {task_id}.");
-            } else {
-                println!("Task with id {task_id} not found.");
+        TaskCommands::Assign { task_ids, agent_id } => {
+            if !agent::load_agents()?.iter().any(|a| a.id == *agent_id) {
+                anyhow::bail!("Agent with id {agent_id} not found.");
             }
+            store::update_board(|board| {
+                for task_id in task_ids {
+                    if let Some(task) = board.task_mut(*task_id) {
+                        task.agent_id = Some(*agent_id);
+                        info!("Agent {agent_id} assigned to task {task_id}.");
+                    } else {
+                        println!("Task with id {task_id} not found.");
+                    }
+                }
+            })?;
         }
         TaskCommands::Unassign { task_id } => {
-            let mut board = store::load_board()?;
-            if let Some(task) = board.tasks.iter_mut().find(|t| t.id == *task_id) {
-                task.agent_id = None;
-                store::save_board(&board)?;
-                println!("Agent unassigned from task {task_id}.");
+            let found = store::update_board(|board| match board.task_mut(*task_id) {
+                Some(task) => {
+                    task.agent_id = None;
+                    true
+                }
+                None => false,
+            })?;
+            if found {
+                info!("Agent unassigned from task {task_id}.");
             } else {
                 println!("Task with id {task_id} not found.");
             }
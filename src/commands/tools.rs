@@ -1,6 +1,27 @@
+use std::io::Read;
+
+use anyhow::Context;
+use serde_json::Value;
+
 use crate::cli::ToolCommands;
 use crate::tools;
 
+/// Parses tool arguments from `--args`, stdin, or defaults to `{}`.
+fn read_args(args: Option<&str>, from_stdin: bool) -> anyhow::Result<Value> {
+    let raw = if from_stdin {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        input
+    } else {
+        args.unwrap_or("{}").to_string()
+    };
+    let value: Value = serde_json::from_str(&raw).context("Invalid JSON tool arguments")?;
+    if !value.is_object() {
+        anyhow::bail!("Tool arguments must be a JSON object");
+    }
+    Ok(value)
+}
+
 pub fn handle(action: &ToolCommands) -> anyhow::Result<()> {
     match action {
         ToolCommands::List => {
@@ -8,6 +29,29 @@ pub fn handle(action: &ToolCommands) -> anyhow::Result<()> {
                 println!("{t}");
             }
         }
+        ToolCommands::Stats => {
+            let metrics = tools::metrics::load()?;
+            if metrics.is_empty() {
+                println!("No tool metrics recorded yet.");
+            }
+            for (name, stats) in metrics {
+                println!(
+                    "{name}: {} calls, {} failures, {} ms total ({} ms avg)",
+                    stats.calls,
+                    stats.failures,
+                    stats.total_ms,
+                    stats.average_ms()
+                );
+            }
+        }
+        ToolCommands::Call {
+            name,
+            args,
+            args_stdin,
+        } => {
+            let args = read_args(args.as_deref(), *args_stdin)?;
+            println!("{}", tools::execute_tool(name, &args)?);
+        }
     }
     Ok(())
 }
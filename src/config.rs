@@ -27,72 +27,98 @@ pub const DESCRIPTION_FILE: &str = ".taskter/description.md";
 pub const EMAIL_CONFIG_FILE: &str = ".taskter/email_config.json";
 /// Default relative path for the running agents file.
 pub const RUNNING_AGENTS_FILE: &str = ".taskter/running_agents.json";
+/// Default relative path for the tool invocation metrics file.
+pub const TOOL_METRICS_FILE: &str = ".taskter/tool_metrics.json";
 /// Default relative path for the API responses debug log.
 pub const RESPONSES_LOG_FILE: &str = ".taskter/api_responses.log";
 
 /// Command-line overrides for configuration values. Higher precedence than env/file/defaults.
+///
+/// Every flag is global, so it may appear before or after the subcommand.
 #[derive(Debug, Default, Clone, Args)]
 pub struct ConfigOverrides {
     /// Explicit path to the configuration file.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub config_file: Option<PathBuf>,
+    /// Directory of `*.toml` fragments merged in sorted order beneath the config file.
+    #[arg(long, global = true)]
+    pub config_dir: Option<PathBuf>,
+    /// Merge the `[profiles.<name>]` config section over the base settings.
+    /// Falls back to `TASKTER_PROFILE`.
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+    /// Load environment variables from this dotenv file instead of `./.env`.
+    /// Falls back to `TASKTER_ENV_FILE`.
+    #[arg(long, global = true)]
+    pub env_file: Option<PathBuf>,
 
     /// Override the data directory used for persistence.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub data_dir: Option<PathBuf>,
     /// Override the board JSON file path.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub board_file: Option<PathBuf>,
     /// Override the OKRs JSON file path.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub okrs_file: Option<PathBuf>,
+    /// Override the OKR snapshot history directory.
+    #[arg(long, global = true)]
+    pub okr_history_dir: Option<PathBuf>,
     /// Override the textual logs file path.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub log_file: Option<PathBuf>,
     /// Override the agents registry file path.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub agents_file: Option<PathBuf>,
     /// Override the project description file path.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub description_file: Option<PathBuf>,
     /// Override the email configuration file path.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub email_config_file: Option<PathBuf>,
     /// Override the running agents file path.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub running_agents_file: Option<PathBuf>,
+    /// Override the tool invocation metrics file path.
+    #[arg(long, global = true)]
+    pub tool_metrics_file: Option<PathBuf>,
     /// Override the API responses debug log path.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub responses_log_file: Option<PathBuf>,
 
+    /// Fail instead of simulating a run when no API key is set or the provider
+    /// request fails (sets `providers.allow_simulation = false`).
+    #[arg(long, global = true)]
+    pub no_fallback: bool,
+
     /// Override the OpenAI API key.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub openai_api_key: Option<String>,
     /// Override the OpenAI base URL used for default endpoints.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub openai_base_url: Option<String>,
     /// Override the OpenAI responses endpoint.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub openai_responses_endpoint: Option<String>,
     /// Override the OpenAI chat completions endpoint.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub openai_chat_endpoint: Option<String>,
     /// Override the OpenAI request style (responses/chat).
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub openai_request_style: Option<String>,
     /// Override the OpenAI response format (JSON or type name).
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub openai_response_format: Option<String>,
 
     /// Override the Gemini API key.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub gemini_api_key: Option<String>,
 
     /// Override the Ollama API key.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub ollama_api_key: Option<String>,
     /// Override the Ollama base URL.
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub ollama_base_url: Option<String>,
 }
 
@@ -179,11 +205,27 @@ pub fn okrs_path() -> Result<PathBuf> {
     with_config(|cfg| cfg.paths.okrs.clone())
 }
 
+/// Directory holding timestamped OKR snapshots.
+pub fn okr_history_dir() -> Result<PathBuf> {
+    with_config(|cfg| cfg.paths.okr_history.clone())
+}
+
 /// Path to the execution logs file.
 pub fn log_path() -> Result<PathBuf> {
     with_config(|cfg| cfg.paths.log.clone())
 }
 
+/// Path that scheduler activity is logged to; the execution log unless
+/// `scheduler.log_file` is set.
+pub fn scheduler_log_path() -> Result<PathBuf> {
+    with_config(|cfg| cfg.scheduler.log.clone())
+}
+
+/// Most task executions the scheduler runs at once, or `None` for no limit.
+pub fn scheduler_max_concurrent_tasks() -> Result<Option<usize>> {
+    with_config(|cfg| cfg.scheduler.max_concurrent_tasks)
+}
+
 /// Path to the agents registry JSON file.
 pub fn agents_path() -> Result<PathBuf> {
     with_config(|cfg| cfg.paths.agents.clone())
@@ -204,6 +246,11 @@ pub fn running_agents_path() -> Result<PathBuf> {
     with_config(|cfg| cfg.paths.running_agents.clone())
 }
 
+/// Path to the per-tool invocation metrics file.
+pub fn tool_metrics_path() -> Result<PathBuf> {
+    with_config(|cfg| cfg.paths.tool_metrics.clone())
+}
+
 /// Path to the debug API responses log.
 pub fn responses_log_path() -> Result<PathBuf> {
     with_config(|cfg| cfg.paths.responses_log.clone())
@@ -224,6 +271,96 @@ pub fn ollama() -> Result<OllamaResolved> {
     with_config(|cfg| cfg.providers.ollama.clone())
 }
 
+/// Resolved settings for the file search tool.
+pub fn file_search() -> Result<FileSearchResolved> {
+    with_config(|cfg| cfg.tools.file_search.clone())
+}
+
+/// Resolved settings for the `web_search` tool.
+pub fn web_search() -> Result<SearchResolved> {
+    with_config(|cfg| cfg.tools.search.clone())
+}
+
+/// Names of built-in tools disabled for this project.
+pub fn disabled_tools() -> Result<Vec<String>> {
+    with_config(|cfg| cfg.tools.disabled.clone())
+}
+
+/// JSON-RPC methods the MCP server refuses to answer.
+pub fn mcp_disabled_methods() -> Result<Vec<String>> {
+    with_config(|cfg| cfg.mcp.disabled_methods.clone())
+}
+
+/// Whether `taskter mcp serve` rejects requests sent before `initialize`.
+pub fn mcp_strict() -> Result<bool> {
+    with_config(|cfg| cfg.mcp.strict)
+}
+
+/// Names of tools that prompt before running during an interactive
+/// `task execute`.
+pub fn confirm_tools() -> Result<Vec<String>> {
+    with_config(|cfg| cfg.tools.require_confirmation.clone())
+}
+
+/// How many times the `taskter_*` tools retry a failed `taskter` subprocess.
+pub fn subprocess_retries() -> Result<u32> {
+    with_config(|cfg| cfg.tools.subprocess_retries)
+}
+
+/// Proxy settings for tool HTTP clients.
+pub fn tool_http() -> Result<ToolHttpResolved> {
+    with_config(|cfg| cfg.tools.http.clone())
+}
+
+/// Provider used when an agent names none and its model matches no known
+/// prefix (`providers.default`, defaults to `gemini`).
+pub fn default_provider() -> Result<String> {
+    with_config(|cfg| cfg.providers.default.clone())
+}
+
+/// Whether agent runs fall back to a local simulation when no API key is set
+/// or the provider request fails.
+pub fn allow_simulation() -> Result<bool> {
+    with_config(|cfg| cfg.providers.allow_simulation)
+}
+
+/// Whether the offline simulation runs an agent's single side-effect-free
+/// tool for real (`providers.simulate_tools`).
+pub fn simulate_tools() -> Result<bool> {
+    with_config(|cfg| cfg.providers.simulate_tools)
+}
+
+/// Whether a task's existing comment is added to the agent's prompt
+/// (`providers.include_task_comment`).
+pub fn include_task_comment() -> Result<bool> {
+    with_config(|cfg| cfg.providers.include_task_comment)
+}
+
+/// Whether model provider requests may go through a proxy.
+pub fn providers_use_proxy() -> Result<bool> {
+    with_config(|cfg| cfg.providers.use_proxy)
+}
+
+/// Resolved terminal UI settings.
+pub fn tui() -> Result<TuiResolved> {
+    with_config(|cfg| cfg.tui.clone())
+}
+
+/// Format used when writing the operations log.
+pub fn log_format() -> Result<LogFormat> {
+    with_config(|cfg| cfg.logging.format)
+}
+
+/// Line ending used when writing textual project files.
+pub fn line_endings() -> Result<LineEnding> {
+    with_config(|cfg| cfg.storage.line_endings)
+}
+
+/// URL notified after each agent execution, if configured.
+pub fn webhook_url() -> Result<Option<String>> {
+    with_config(|cfg| cfg.integrations.webhook_url.clone())
+}
+
 /// Return the API key configured for the given provider identifier.
 pub fn provider_api_key(provider: &str) -> Result<Option<String>> {
     with_config(|cfg| cfg.providers.api_key_for(provider))
@@ -234,6 +371,13 @@ pub fn provider_api_key(provider: &str) -> Result<Option<String>> {
 struct ResolvedConfig {
     paths: ResolvedPaths,
     providers: ResolvedProviders,
+    tools: ResolvedTools,
+    tui: TuiResolved,
+    logging: LoggingResolved,
+    storage: StorageResolved,
+    integrations: IntegrationsResolved,
+    scheduler: SchedulerResolved,
+    mcp: McpResolved,
 }
 
 #[derive(Debug, Clone)]
@@ -241,11 +385,13 @@ struct ResolvedPaths {
     data_dir: PathBuf,
     board: PathBuf,
     okrs: PathBuf,
+    okr_history: PathBuf,
     log: PathBuf,
     agents: PathBuf,
     description: PathBuf,
     email_config: PathBuf,
     running_agents: PathBuf,
+    tool_metrics: PathBuf,
     responses_log: PathBuf,
 }
 
@@ -254,6 +400,11 @@ struct ResolvedProviders {
     openai: OpenAiResolved,
     gemini: GeminiResolved,
     ollama: OllamaResolved,
+    use_proxy: bool,
+    default: String,
+    allow_simulation: bool,
+    simulate_tools: bool,
+    include_task_comment: bool,
 }
 
 impl ResolvedProviders {
@@ -267,6 +418,106 @@ impl ResolvedProviders {
     }
 }
 
+#[derive(Debug, Clone)]
+struct ResolvedTools {
+    file_search: FileSearchResolved,
+    search: SearchResolved,
+    disabled: Vec<String>,
+    require_confirmation: Vec<String>,
+    http: ToolHttpResolved,
+    subprocess_retries: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileSearchResolved {
+    pub concurrency: usize,
+    pub max_file_size: u64,
+    pub ignore_dirs: Vec<String>,
+}
+
+/// Settings for the `web_search` tool.
+#[derive(Debug, Clone)]
+pub struct SearchResolved {
+    /// Search API queried with `q` and `format=json` parameters.
+    pub endpoint: String,
+    /// `User-Agent` header sent with each request, if any.
+    pub user_agent: Option<String>,
+}
+
+/// Proxy settings for HTTP clients created by built-in tools.
+#[derive(Debug, Clone)]
+pub struct ToolHttpResolved {
+    /// Explicit proxy URL applied to all tool requests.
+    pub proxy: Option<String>,
+    /// Whether `HTTP(S)_PROXY` environment variables are honored when no
+    /// explicit proxy is set.
+    pub env_proxy: bool,
+}
+
+#[derive(Debug, Clone)]
+struct LoggingResolved {
+    format: LogFormat,
+}
+
+/// Line ending applied to textual files such as `description.md`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    Crlf,
+}
+
+impl LineEnding {
+    /// Rewrites every line break in `text` to this line ending.
+    pub fn apply(self, text: &str) -> String {
+        let normalized = text.replace("\r\n", "\n");
+        match self {
+            LineEnding::Lf => normalized,
+            LineEnding::Crlf => normalized.replace('\n', "\r\n"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StorageResolved {
+    line_endings: LineEnding,
+}
+
+#[derive(Debug, Clone, Default)]
+struct IntegrationsResolved {
+    webhook_url: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct SchedulerResolved {
+    log: PathBuf,
+    max_concurrent_tasks: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct McpResolved {
+    disabled_methods: Vec<String>,
+    strict: bool,
+}
+
+/// Output format of `.taskter/logs.log`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human readable `[timestamp] message` lines.
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TuiResolved {
+    pub render_markdown: bool,
+    pub mouse: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct OpenAiResolved {
     pub api_key: Option<String>,
@@ -275,6 +526,8 @@ pub struct OpenAiResolved {
     pub chat_endpoint: String,
     pub request_style: Option<String>,
     pub response_format: Option<String>,
+    pub organization: Option<String>,
+    pub project: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -293,6 +546,13 @@ pub struct OllamaResolved {
 struct RawConfig {
     paths: PathsSection,
     providers: ProvidersSection,
+    tools: ToolsSection,
+    tui: TuiSection,
+    logging: LoggingSection,
+    storage: StorageSection,
+    integrations: IntegrationsSection,
+    scheduler: SchedulerSection,
+    mcp: McpSection,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -301,11 +561,13 @@ struct PathsSection {
     data_dir: PathBuf,
     board_file: Option<PathBuf>,
     okrs_file: Option<PathBuf>,
+    okr_history_dir: Option<PathBuf>,
     log_file: Option<PathBuf>,
     agents_file: Option<PathBuf>,
     description_file: Option<PathBuf>,
     email_config_file: Option<PathBuf>,
     running_agents_file: Option<PathBuf>,
+    tool_metrics_file: Option<PathBuf>,
     responses_log_file: Option<PathBuf>,
 }
 
@@ -315,11 +577,13 @@ impl Default for PathsSection {
             data_dir: PathBuf::from(DIR),
             board_file: None,
             okrs_file: None,
+            okr_history_dir: None,
             log_file: None,
             agents_file: None,
             description_file: None,
             email_config_file: None,
             running_agents_file: None,
+            tool_metrics_file: None,
             responses_log_file: None,
         }
     }
@@ -331,47 +595,131 @@ struct ProvidersSection {
     openai: OpenAiSection,
     gemini: GeminiSection,
     ollama: OllamaSection,
+    use_proxy: bool,
+    default: Option<String>,
+    allow_simulation: Option<bool>,
+    simulate_tools: bool,
+    include_task_comment: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(default)]
 struct OpenAiSection {
     api_key: Option<String>,
+    api_key_file: Option<PathBuf>,
     base_url: Option<String>,
     responses_endpoint: Option<String>,
     chat_endpoint: Option<String>,
     request_style: Option<String>,
     response_format: Option<String>,
+    organization: Option<String>,
+    project: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(default)]
 struct GeminiSection {
     api_key: Option<String>,
+    api_key_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(default)]
 struct OllamaSection {
     api_key: Option<String>,
+    api_key_file: Option<PathBuf>,
     base_url: Option<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct ToolsSection {
+    file_search: FileSearchSection,
+    search: SearchSection,
+    disabled: Vec<String>,
+    require_confirmation: Option<Vec<String>>,
+    http_proxy: Option<String>,
+    env_proxy: Option<bool>,
+    subprocess_retries: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct FileSearchSection {
+    concurrency: Option<usize>,
+    max_file_size: Option<u64>,
+    ignore_dirs: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct SearchSection {
+    endpoint: Option<String>,
+    user_agent: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct TuiSection {
+    render_markdown: bool,
+    mouse: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct LoggingSection {
+    format: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct StorageSection {
+    line_endings: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct IntegrationsSection {
+    webhook_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct SchedulerSection {
+    log_file: Option<PathBuf>,
+    max_concurrent_tasks: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+struct McpSection {
+    disabled_methods: Vec<String>,
+    strict: bool,
+}
+
 fn load_config(overrides: &ConfigOverrides) -> Result<ResolvedConfig> {
     let disable_host_config = host_config_disabled();
-    if !disable_host_config {
-        dotenvy::dotenv().ok();
-    }
+    load_env_file(overrides, disable_host_config)?;
 
     let mut builder = config_rs::Config::builder();
 
+    if let Some(dir) = overrides.config_dir.as_ref() {
+        for fragment in config_fragments(dir)? {
+            builder = builder.add_source(
+                config_rs::File::from(fragment.as_path())
+                    .format(FileFormat::Toml)
+                    .required(true),
+            );
+        }
+    }
+
     if let Some(path) = overrides.config_file.as_ref() {
         builder = builder.add_source(
             config_rs::File::from(path.as_path())
                 .format(FileFormat::Toml)
                 .required(true),
         );
-    } else if !disable_host_config {
+    } else if !disable_host_config || config_home_override().is_some() {
         if let Some(project_dirs) = default_config_path() {
             builder = builder.add_source(
                 config_rs::File::from(project_dirs)
@@ -381,6 +729,17 @@ fn load_config(overrides: &ConfigOverrides) -> Result<ResolvedConfig> {
         }
     }
 
+    let files = builder
+        .build()
+        .context("failed to build Taskter configuration sources")?;
+    let mut builder = config_rs::Config::builder().add_source(files.clone());
+    let profile = overrides
+        .profile
+        .clone()
+        .or_else(|| clean_string(std::env::var("TASKTER_PROFILE").ok()));
+    if let Some(name) = profile {
+        builder = builder.add_source(profile_source(&files, &name)?);
+    }
     builder = builder.add_source(config_rs::Environment::with_prefix("TASKTER").separator("__"));
 
     let raw: RawConfig = builder
@@ -396,10 +755,89 @@ fn load_config(overrides: &ConfigOverrides) -> Result<ResolvedConfig> {
     resolve(merged)
 }
 
-fn default_config_path() -> Option<PathBuf> {
+/// Extracts `[profiles.<name>]` from the file sources so it can be layered
+/// between the files and the environment.
+fn profile_source(files: &config_rs::Config, name: &str) -> Result<config_rs::Config> {
+    let mut profiles = files.get_table("profiles").unwrap_or_default();
+    let Some(profile) = profiles.remove(name) else {
+        let mut known: Vec<_> = profiles.into_keys().collect();
+        known.sort();
+        if known.is_empty() {
+            anyhow::bail!("Unknown config profile '{name}'; no [profiles] are defined");
+        }
+        anyhow::bail!(
+            "Unknown config profile '{name}'; available profiles: {}",
+            known.join(", ")
+        );
+    };
+    let table: serde_json::Value = profile
+        .try_deserialize()
+        .with_context(|| format!("failed to read config profile '{name}'"))?;
+    config_rs::Config::try_from(&table)
+        .with_context(|| format!("failed to apply config profile '{name}'"))
+}
+
+fn config_fragments(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read config directory {}", dir.display()))?;
+    let mut fragments = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "toml") {
+            fragments.push(path);
+        }
+    }
+    fragments.sort();
+    Ok(fragments)
+}
+
+/// Loads dotenv variables ahead of config resolution.
+///
+/// An explicit `--env-file` is always read, like `--config-file`. Otherwise
+/// `TASKTER_ENV_FILE` or `./.env` is used unless host config is disabled.
+fn load_env_file(overrides: &ConfigOverrides, disable_host_config: bool) -> Result<()> {
+    if let Some(path) = &overrides.env_file {
+        dotenvy::from_path(path)
+            .with_context(|| format!("failed to load env file {}", path.display()))?;
+        return Ok(());
+    }
+    if disable_host_config {
+        return Ok(());
+    }
+    match std::env::var_os("TASKTER_ENV_FILE").filter(|value| !value.is_empty()) {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            dotenvy::from_path(&path)
+                .with_context(|| format!("failed to load env file {}", path.display()))?;
+        }
+        None => {
+            dotenvy::dotenv().ok();
+        }
+    }
+    Ok(())
+}
+
+fn config_home_override() -> Option<PathBuf> {
+    std::env::var_os("TASKTER_CONFIG_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Returns the config file path used when `--config-file` is not given.
+///
+/// `TASKTER_CONFIG_HOME` takes precedence over the platform directory from
+/// [`ProjectDirs`] and is honored even when host config is disabled.
+pub fn default_config_path() -> Option<PathBuf> {
+    if let Some(home) = config_home_override() {
+        return Some(home.join("config.toml"));
+    }
     ProjectDirs::from("", "", "taskter").map(|dirs| dirs.config_dir().join("config.toml"))
 }
 
+fn env_path(key: &str) -> Option<PathBuf> {
+    clean_string(std::env::var(key).ok()).map(PathBuf::from)
+}
+
 fn apply_legacy_environment(raw: &mut RawConfig) {
     if host_config_disabled() {
         return;
@@ -446,6 +884,20 @@ fn apply_legacy_environment(raw: &mut RawConfig) {
             }
         }
     }
+    if raw.providers.openai.organization.is_none() {
+        if let Ok(val) = std::env::var("OPENAI_ORG_ID") {
+            if !val.trim().is_empty() {
+                raw.providers.openai.organization = Some(val);
+            }
+        }
+    }
+    if raw.providers.openai.project.is_none() {
+        if let Ok(val) = std::env::var("OPENAI_PROJECT_ID") {
+            if !val.trim().is_empty() {
+                raw.providers.openai.project = Some(val);
+            }
+        }
+    }
     if raw.providers.gemini.api_key.is_none() {
         if let Ok(val) = std::env::var("GEMINI_API_KEY") {
             if !val.trim().is_empty() {
@@ -460,6 +912,15 @@ fn apply_legacy_environment(raw: &mut RawConfig) {
             }
         }
     }
+    if raw.providers.openai.api_key_file.is_none() {
+        raw.providers.openai.api_key_file = env_path("OPENAI_API_KEY_FILE");
+    }
+    if raw.providers.gemini.api_key_file.is_none() {
+        raw.providers.gemini.api_key_file = env_path("GEMINI_API_KEY_FILE");
+    }
+    if raw.providers.ollama.api_key_file.is_none() {
+        raw.providers.ollama.api_key_file = env_path("OLLAMA_API_KEY_FILE");
+    }
     if raw.providers.ollama.base_url.is_none() {
         if let Ok(val) = std::env::var("OLLAMA_BASE_URL") {
             if !val.trim().is_empty() {
@@ -470,6 +931,9 @@ fn apply_legacy_environment(raw: &mut RawConfig) {
 }
 
 fn apply_cli_overrides(raw: &mut RawConfig, overrides: &ConfigOverrides) {
+    if overrides.no_fallback {
+        raw.providers.allow_simulation = Some(false);
+    }
     if let Some(dir) = overrides.data_dir.as_ref() {
         raw.paths.data_dir = dir.clone();
     }
@@ -479,6 +943,9 @@ fn apply_cli_overrides(raw: &mut RawConfig, overrides: &ConfigOverrides) {
     if let Some(path) = overrides.okrs_file.as_ref() {
         raw.paths.okrs_file = Some(path.clone());
     }
+    if let Some(path) = overrides.okr_history_dir.as_ref() {
+        raw.paths.okr_history_dir = Some(path.clone());
+    }
     if let Some(path) = overrides.log_file.as_ref() {
         raw.paths.log_file = Some(path.clone());
     }
@@ -494,6 +961,9 @@ fn apply_cli_overrides(raw: &mut RawConfig, overrides: &ConfigOverrides) {
     if let Some(path) = overrides.running_agents_file.as_ref() {
         raw.paths.running_agents_file = Some(path.clone());
     }
+    if let Some(path) = overrides.tool_metrics_file.as_ref() {
+        raw.paths.tool_metrics_file = Some(path.clone());
+    }
     if let Some(path) = overrides.responses_log_file.as_ref() {
         raw.paths.responses_log_file = Some(path.clone());
     }
@@ -532,7 +1002,47 @@ fn apply_cli_overrides(raw: &mut RawConfig, overrides: &ConfigOverrides) {
 fn resolve(raw: RawConfig) -> Result<ResolvedConfig> {
     let paths = resolve_paths(raw.paths);
     let providers = resolve_providers(raw.providers)?;
-    Ok(ResolvedConfig { paths, providers })
+    let tools = resolve_tools(raw.tools);
+    let tui = TuiResolved {
+        render_markdown: raw.tui.render_markdown,
+        mouse: raw.tui.mouse,
+    };
+    let logging = resolve_logging(raw.logging)?;
+    let storage = resolve_storage(raw.storage)?;
+    let integrations = IntegrationsResolved {
+        webhook_url: clean_string(raw.integrations.webhook_url),
+    };
+    let scheduler = SchedulerResolved {
+        log: raw
+            .scheduler
+            .log_file
+            .filter(|path| !path.as_os_str().is_empty())
+            .unwrap_or_else(|| paths.log.clone()),
+        max_concurrent_tasks: raw
+            .scheduler
+            .max_concurrent_tasks
+            .filter(|limit| *limit > 0),
+    };
+    let mcp = McpResolved {
+        disabled_methods: raw
+            .mcp
+            .disabled_methods
+            .into_iter()
+            .filter_map(|method| clean_string(Some(method)))
+            .collect(),
+        strict: raw.mcp.strict,
+    };
+    Ok(ResolvedConfig {
+        paths,
+        providers,
+        tools,
+        tui,
+        logging,
+        storage,
+        integrations,
+        scheduler,
+        mcp,
+    })
 }
 
 fn resolve_paths(paths: PathsSection) -> ResolvedPaths {
@@ -544,22 +1054,26 @@ fn resolve_paths(paths: PathsSection) -> ResolvedPaths {
 
     let board = resolve_path(&data_dir, paths.board_file, "board.json");
     let okrs = resolve_path(&data_dir, paths.okrs_file, "okrs.json");
+    let okr_history = resolve_path(&data_dir, paths.okr_history_dir, "okr_history");
     let log = resolve_path(&data_dir, paths.log_file, "logs.log");
     let agents = resolve_path(&data_dir, paths.agents_file, "agents.json");
     let description = resolve_path(&data_dir, paths.description_file, "description.md");
     let email_config = resolve_path(&data_dir, paths.email_config_file, "email_config.json");
     let running_agents = resolve_path(&data_dir, paths.running_agents_file, "running_agents.json");
+    let tool_metrics = resolve_path(&data_dir, paths.tool_metrics_file, "tool_metrics.json");
     let responses_log = resolve_path(&data_dir, paths.responses_log_file, "api_responses.log");
 
     ResolvedPaths {
         data_dir,
         board,
         okrs,
+        okr_history,
         log,
         agents,
         description,
         email_config,
         running_agents,
+        tool_metrics,
         responses_log,
     }
 }
@@ -574,13 +1088,23 @@ fn resolve_path(data_dir: &Path, explicit: Option<PathBuf>, default_name: &str)
 
 fn resolve_providers(providers: ProvidersSection) -> Result<ResolvedProviders> {
     let openai = resolve_openai(providers.openai)?;
-    let gemini = resolve_gemini(providers.gemini);
-    let ollama = resolve_ollama(providers.ollama);
+    let gemini = resolve_gemini(providers.gemini)?;
+    let ollama = resolve_ollama(providers.ollama)?;
+    let default = match clean_string(providers.default) {
+        Some(name) => crate::providers::normalize_provider_id(&name)
+            .with_context(|| format!("invalid providers.default `{name}`"))?,
+        None => "gemini".to_string(),
+    };
 
     Ok(ResolvedProviders {
         openai,
         gemini,
         ollama,
+        use_proxy: providers.use_proxy,
+        default,
+        allow_simulation: providers.allow_simulation.unwrap_or(true),
+        simulate_tools: providers.simulate_tools,
+        include_task_comment: providers.include_task_comment,
     })
 }
 
@@ -603,28 +1127,144 @@ fn resolve_openai(section: OpenAiSection) -> Result<OpenAiResolved> {
     }
 
     Ok(OpenAiResolved {
-        api_key: clean_string(section.api_key),
+        api_key: resolve_api_key(section.api_key, section.api_key_file.as_deref())?,
         base_url: normalized_base,
         responses_endpoint,
         chat_endpoint,
         request_style: clean_string(section.request_style),
         response_format,
+        organization: clean_string(section.organization),
+        project: clean_string(section.project),
     })
 }
 
-fn resolve_gemini(section: GeminiSection) -> GeminiResolved {
-    GeminiResolved {
-        api_key: clean_string(section.api_key),
-    }
+fn resolve_gemini(section: GeminiSection) -> Result<GeminiResolved> {
+    Ok(GeminiResolved {
+        api_key: resolve_api_key(section.api_key, section.api_key_file.as_deref())?,
+    })
 }
 
-fn resolve_ollama(section: OllamaSection) -> OllamaResolved {
+fn resolve_ollama(section: OllamaSection) -> Result<OllamaResolved> {
     let base_url = clean_string(section.base_url)
         .filter(|s| !s.is_empty())
         .unwrap_or_else(|| "http://localhost:11434".to_string());
-    OllamaResolved {
-        api_key: clean_string(section.api_key),
+    Ok(OllamaResolved {
+        api_key: resolve_api_key(section.api_key, section.api_key_file.as_deref())?,
         base_url: base_url.trim_end_matches('/').to_string(),
+    })
+}
+
+/// Prefers an inline key and otherwise reads it from `api_key_file`.
+fn resolve_api_key(inline: Option<String>, file: Option<&Path>) -> Result<Option<String>> {
+    if let Some(key) = clean_string(inline) {
+        return Ok(Some(key));
+    }
+    match file {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read API key file {}", path.display()))?;
+            Ok(clean_string(Some(contents)))
+        }
+        None => Ok(None),
+    }
+}
+
+fn resolve_logging(section: LoggingSection) -> Result<LoggingResolved> {
+    let format = match clean_string(section.format)
+        .map(|f| f.to_lowercase())
+        .as_deref()
+    {
+        None | Some("text") => LogFormat::Text,
+        Some("json") => LogFormat::Json,
+        Some(other) => {
+            anyhow::bail!("Unsupported logging.format `{other}` (expected text or json)")
+        }
+    };
+    Ok(LoggingResolved { format })
+}
+
+fn resolve_storage(section: StorageSection) -> Result<StorageResolved> {
+    let line_endings = match clean_string(section.line_endings)
+        .map(|l| l.to_lowercase())
+        .as_deref()
+    {
+        None | Some("lf") => LineEnding::Lf,
+        Some("crlf") => LineEnding::Crlf,
+        Some("native") if cfg!(windows) => LineEnding::Crlf,
+        Some("native") => LineEnding::Lf,
+        Some(other) => {
+            anyhow::bail!(
+                "Unsupported storage.line_endings `{other}` (expected lf, crlf or native)"
+            )
+        }
+    };
+    Ok(StorageResolved { line_endings })
+}
+
+const DEFAULT_SUBPROCESS_RETRIES: u32 = 2;
+const DEFAULT_SEARCH_ENDPOINT: &str = "https://api.duckduckgo.com";
+const DEFAULT_CONFIRM_TOOLS: &[&str] = &["run_bash", "run_python", "send_email", "project_files"];
+
+fn resolve_tools(tools: ToolsSection) -> ResolvedTools {
+    ResolvedTools {
+        file_search: resolve_file_search(tools.file_search),
+        search: SearchResolved {
+            endpoint: clean_string(tools.search.endpoint)
+                .unwrap_or_else(|| DEFAULT_SEARCH_ENDPOINT.to_string()),
+            user_agent: clean_string(tools.search.user_agent),
+        },
+        disabled: tools
+            .disabled
+            .into_iter()
+            .filter_map(|name| clean_string(Some(name)))
+            .collect(),
+        require_confirmation: tools.require_confirmation.map_or_else(
+            || {
+                DEFAULT_CONFIRM_TOOLS
+                    .iter()
+                    .map(|name| (*name).to_string())
+                    .collect()
+            },
+            |names| {
+                names
+                    .into_iter()
+                    .filter_map(|name| clean_string(Some(name)))
+                    .collect()
+            },
+        ),
+        http: ToolHttpResolved {
+            proxy: clean_string(tools.http_proxy),
+            env_proxy: tools.env_proxy.unwrap_or(true),
+        },
+        subprocess_retries: tools
+            .subprocess_retries
+            .unwrap_or(DEFAULT_SUBPROCESS_RETRIES),
+    }
+}
+
+fn resolve_file_search(section: FileSearchSection) -> FileSearchResolved {
+    let concurrency = section.concurrency.filter(|n| *n > 0).unwrap_or_else(|| {
+        std::thread::available_parallelism()
+            .map(std::num::NonZeroUsize::get)
+            .unwrap_or(1)
+    });
+    let ignore_dirs = section
+        .ignore_dirs
+        .map(|dirs| {
+            dirs.into_iter()
+                .filter_map(|d| clean_string(Some(d)))
+                .collect()
+        })
+        .unwrap_or_else(|| {
+            [".git", "target", "node_modules"]
+                .iter()
+                .map(|d| (*d).to_string())
+                .collect()
+        });
+    FileSearchResolved {
+        concurrency,
+        max_file_size: section.max_file_size.unwrap_or(1024 * 1024),
+        ignore_dirs,
     }
 }
 
@@ -28,6 +28,7 @@ pub mod agent;
 pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod logging;
 pub mod mcp;
 pub mod providers;
 pub mod scheduler;
@@ -0,0 +1,230 @@
+//! Operation log writer shared by agents and the `logs` command.
+//!
+//! Entries are appended to `.taskter/logs.log` either as plain
+//! `[timestamp] message` lines or, when `logging.format = "json"`, as one JSON
+//! object per line.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde_json::{json, Value};
+
+use crate::config::{self, LogFormat};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Callback receiving each appended entry as a `[timestamp] message` line.
+pub type Follower = Box<dyn Fn(&str) + Send + Sync>;
+
+static FOLLOWER: RwLock<Option<Follower>> = RwLock::new(None);
+
+/// Installs (or with `None`, removes) a callback that is invoked for every
+/// entry written by [`append`]. Used by `task execute --follow` to stream
+/// agent progress while it runs.
+pub fn set_follower(follower: Option<Follower>) {
+    *FOLLOWER.write().expect("log follower lock poisoned") = follower;
+}
+
+/// A single entry in the operations log.
+#[derive(Debug, Clone)]
+pub struct LogEvent<'a> {
+    pub agent_id: Option<usize>,
+    pub event: &'a str,
+    pub tool: Option<&'a str>,
+    pub message: String,
+}
+
+impl<'a> LogEvent<'a> {
+    /// Creates an event of the given type with no agent or tool attached.
+    pub fn new(event: &'a str, message: impl Into<String>) -> Self {
+        Self {
+            agent_id: None,
+            event,
+            tool: None,
+            message: message.into(),
+        }
+    }
+
+    /// Attaches the id of the agent that emitted the event.
+    #[must_use]
+    pub fn agent(mut self, id: usize) -> Self {
+        self.agent_id = Some(id);
+        self
+    }
+
+    /// Attaches the name of the tool the event refers to.
+    #[must_use]
+    pub fn tool(mut self, name: &'a str) -> Self {
+        self.tool = Some(name);
+        self
+    }
+
+    fn to_json(&self, timestamp: &str) -> Value {
+        json!({
+            "timestamp": timestamp,
+            "agent_id": self.agent_id,
+            "event": self.event,
+            "tool": self.tool,
+            "message": self.message,
+        })
+    }
+}
+
+/// Appends an event to the operations log using the configured format.
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be loaded or the log file
+/// cannot be written.
+pub fn append(event: &LogEvent) -> anyhow::Result<()> {
+    append_to(&config::log_path()?, event)
+}
+
+/// Appends an event to the log file at `path`, as [`append`] does for the
+/// operations log.
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be loaded or the file cannot
+/// be written.
+pub fn append_to(path: &Path, event: &LogEvent) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let timestamp = Local::now().format(TIMESTAMP_FORMAT).to_string();
+    let line = format!("[{timestamp}] {}", event.message);
+    match config::log_format()? {
+        LogFormat::Text => writeln!(file, "{line}")?,
+        LogFormat::Json => writeln!(file, "{}", event.to_json(&timestamp))?,
+    }
+    if let Some(follow) = FOLLOWER
+        .read()
+        .expect("log follower lock poisoned")
+        .as_ref()
+    {
+        follow(&line);
+    }
+    Ok(())
+}
+
+/// Renders raw log contents for display, turning JSON lines into the same
+/// `[timestamp] message` shape used by the text format.
+pub fn format_for_display(contents: &str) -> String {
+    contents
+        .lines()
+        .map(|line| match serde_json::from_str::<Value>(line) {
+            Ok(Value::Object(obj)) => {
+                let field = |key: &str| obj.get(key).and_then(Value::as_str).unwrap_or_default();
+                let mut prefix = format!("[{}]", field("timestamp"));
+                if let Some(id) = obj.get("agent_id").and_then(Value::as_u64) {
+                    prefix.push_str(&format!(" agent {id}"));
+                }
+                prefix.push_str(&format!(" {}", field("event")));
+                if let Some(tool) = obj.get("tool").and_then(Value::as_str) {
+                    prefix.push_str(&format!(" ({tool})"));
+                }
+                format!("{prefix}: {}", field("message"))
+            }
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Parses an age such as `30d`, `12h`, `45m`, `90s` or `2w`.
+///
+/// # Errors
+///
+/// Returns a message suitable for command-line errors when the value has no
+/// known unit, its number is invalid or the age is too large to represent.
+pub fn parse_age(value: &str) -> Result<chrono::Duration, String> {
+    let value = value.trim();
+    let split = value.len() - value.chars().last().map_or(0, char::len_utf8);
+    let (number, unit) = value.split_at(split);
+    let amount: i64 = number
+        .parse()
+        .map_err(|_| format!("invalid age `{value}` (expected e.g. 30d, 12h, 45m)"))?;
+    let age = match unit {
+        "s" => chrono::TimeDelta::try_seconds(amount),
+        "m" => chrono::TimeDelta::try_minutes(amount),
+        "h" => chrono::TimeDelta::try_hours(amount),
+        "d" => chrono::TimeDelta::try_days(amount),
+        "w" => chrono::TimeDelta::try_weeks(amount),
+        _ => {
+            return Err(format!(
+                "invalid age `{value}` (use one of the units s, m, h, d, w)"
+            ))
+        }
+    };
+    age.ok_or_else(|| format!("age `{value}` is too large"))
+}
+
+/// What [`prune`] removed.
+#[derive(Debug, Default, PartialEq)]
+pub struct PruneReport {
+    /// Entries dropped from the operations log.
+    pub lines: usize,
+    /// Files deleted entirely.
+    pub files: usize,
+    /// Bytes freed across all files.
+    pub bytes: u64,
+}
+
+/// Reads the timestamp of a text or JSON log line.
+fn entry_timestamp(line: &str) -> Option<NaiveDateTime> {
+    let raw = match serde_json::from_str::<Value>(line) {
+        Ok(Value::Object(obj)) => obj.get("timestamp")?.as_str()?.to_string(),
+        _ => line.strip_prefix('[')?.split_once(']')?.0.to_string(),
+    };
+    NaiveDateTime::parse_from_str(&raw, TIMESTAMP_FORMAT).ok()
+}
+
+/// Drops operations-log entries written before `cutoff` and deletes the
+/// provider responses log when it was last written before `cutoff`.
+///
+/// Lines without a timestamp, such as continuations of multi-line messages,
+/// share the fate of the entry above them.
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be loaded or a file cannot be
+/// read, rewritten or removed.
+pub fn prune(cutoff: NaiveDateTime) -> anyhow::Result<PruneReport> {
+    let mut report = PruneReport::default();
+
+    let log_path = config::log_path()?;
+    if log_path.exists() {
+        let contents = fs::read_to_string(&log_path)?;
+        let mut kept = String::new();
+        let mut keep = true;
+        for line in contents.lines() {
+            if let Some(timestamp) = entry_timestamp(line) {
+                keep = timestamp >= cutoff;
+                if !keep {
+                    report.lines += 1;
+                }
+            }
+            if keep {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+        if kept.len() < contents.len() {
+            report.bytes += (contents.len() - kept.len()) as u64;
+            fs::write(&log_path, kept)?;
+        }
+    }
+
+    let responses_path = config::responses_log_path()?;
+    if let Ok(metadata) = fs::metadata(&responses_path) {
+        let modified = DateTime::<Local>::from(metadata.modified()?).naive_local();
+        if modified < cutoff {
+            fs::remove_file(&responses_path)?;
+            report.files += 1;
+            report.bytes += metadata.len();
+        }
+    }
+
+    Ok(report)
+}
@@ -1,3 +1,5 @@
+use std::process::ExitCode;
+
 use clap::Parser;
 
 use taskter::cli::{Cli, Commands};
@@ -18,8 +20,26 @@ use taskter::config;
 // create a dedicated multi-threaded runtime only for that specific operation or
 // revisit this decision.
 #[tokio::main(flavor = "current_thread")]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> ExitCode {
     let cli = Cli::parse();
+    match run(&cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // Scripts that asked for JSON output get a JSON error object on
+            // stderr; everyone else sees the usual `Error: ...` report.
+            if cli.command.json_output() {
+                let report = serde_json::json!({"error": format!("{err:#}"), "code": 1});
+                eprintln!("{report}");
+            } else {
+                eprintln!("Error: {err:?}");
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: &Cli) -> anyhow::Result<()> {
+    commands::set_quiet(cli.quiet);
 
     if let Err(err) = config::init(&cli.config) {
         if matches!(cli.command, Commands::Mcp { .. }) {
@@ -29,15 +49,24 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    if cli.command.mutates_project() {
+        commands::init::ensure_initialized(cli.auto_init)?;
+    }
+
     match &cli.command {
-        Commands::Init => commands::init::run()?,
+        Commands::Init { force, name } => commands::init::run(*force, name.as_deref())?,
         Commands::Task { action } => commands::task::handle(action).await?,
         Commands::Agent { action } => commands::agent::handle(action).await?,
         Commands::Show { what } => commands::show::handle(what)?,
         Commands::Okrs { action } => commands::okrs::handle(action)?,
         Commands::Logs { action } => commands::logs::handle(action)?,
         Commands::Tools { action } => commands::tools::handle(action)?,
+        Commands::Providers { action } => commands::providers::handle(action)?,
         Commands::Scheduler { action } => commands::scheduler::handle(action).await?,
+        Commands::Repl { agent_id } => commands::repl::run(*agent_id).await?,
+        Commands::Schema { name, out_dir } => {
+            commands::schema::handle(name.as_deref(), out_dir.as_deref())?;
+        }
         Commands::Board => commands::board::run()?,
         Commands::Description { description } => commands::description::set(description)?,
         Commands::Mcp { action } => commands::mcp::handle(action).await?,
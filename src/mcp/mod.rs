@@ -8,6 +8,7 @@
 use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashSet;
 use std::io::Write;
 use tokio::io::{
     self, AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
@@ -19,6 +20,53 @@ use crate::tools;
 const JSONRPC: &str = "2.0";
 const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
 
+/// Methods accepted before `initialize` has completed in strict mode.
+const LIFECYCLE_METHODS: &[&str] = &[
+    "initialize",
+    "notifications/initialized",
+    "initialized",
+    "ping",
+    "shutdown",
+];
+
+/// JSON-RPC error code for requests sent before `initialize`.
+const SERVER_NOT_INITIALIZED: i64 = -32002;
+
+/// The set of JSON-RPC methods a server session answers. Every method is
+/// enabled by default; disabled ones are rejected with `-32601`.
+#[derive(Debug, Clone, Default)]
+pub struct Capabilities {
+    disabled: HashSet<String>,
+    strict: bool,
+}
+
+impl Capabilities {
+    /// Capabilities with the given methods (e.g. `tools/call`) turned off.
+    pub fn without<I, S>(methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            disabled: methods.into_iter().map(Into::into).collect(),
+            strict: false,
+        }
+    }
+
+    /// Rejects non-lifecycle methods with `-32002` until the client has sent
+    /// `initialize`.
+    #[must_use]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Returns `true` unless `method` has been disabled.
+    pub fn allows(&self, method: &str) -> bool {
+        !self.disabled.contains(method)
+    }
+}
+
 #[derive(Debug)]
 struct RpcRequest {
     jsonrpc: String,
@@ -145,6 +193,19 @@ fn line_delimited_response_enabled() -> bool {
     }
 }
 
+fn ready_line_enabled() -> bool {
+    match std::env::var("TASKTER_MCP_READY_LINE") {
+        Ok(value) => {
+            let trimmed = value.trim();
+            !(trimmed.is_empty()
+                || trimmed.eq_ignore_ascii_case("0")
+                || trimmed.eq_ignore_ascii_case("false")
+                || trimmed.eq_ignore_ascii_case("off"))
+        }
+        Err(_) => false,
+    }
+}
+
 struct TraceLogger {
     sink: Option<Box<dyn Write + Send>>,
 }
@@ -154,12 +215,14 @@ impl TraceLogger {
         Self { sink: None }
     }
 
-    fn new() -> Self {
-        if !trace_enabled() {
-            return Self::disabled();
-        }
-
-        let path = trace_path();
+    /// Opens the trace sink. An explicit `path` (from `--trace`) enables
+    /// tracing and takes precedence over `TASKTER_MCP_TRACE_FILE`.
+    fn new(path: Option<&std::path::Path>) -> Self {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None if trace_enabled() => trace_path(),
+            None => return Self::disabled(),
+        };
         let sink = std::fs::OpenOptions::new()
             .create(true)
             .append(true)
@@ -224,18 +287,17 @@ fn handle_tools_list(req: &RpcRequest) -> RpcResponse {
 }
 
 async fn handle_tools_call(req: &RpcRequest) -> RpcResponse {
-    let name = req
-        .params
-        .get("name")
-        .and_then(Value::as_str)
-        .map(str::to_string);
-    let args = req
-        .params
-        .get("arguments")
-        .cloned()
-        .unwrap_or_else(|| json!({}));
-    let Some(tool_name) = name else {
-        return rpc_err(req.response_id(), -32602, "Missing tool name");
+    let tool_name = match req.params.get("name") {
+        Some(Value::String(name)) => name.clone(),
+        None | Some(Value::Null) => {
+            return invalid_params(req, "missing tool `name`");
+        }
+        Some(_) => return invalid_params(req, "`name` must be a string"),
+    };
+    let args = match req.params.get("arguments") {
+        None | Some(Value::Null) => json!({}),
+        Some(args @ Value::Object(_)) => args.clone(),
+        Some(_) => return invalid_params(req, "`arguments` must be an object"),
     };
 
     let tool_name_clone = tool_name.clone();
@@ -267,7 +329,7 @@ async fn handle_tools_call(req: &RpcRequest) -> RpcResponse {
         json!({
             "content": [{
                 "type": "text",
-                "text": output,
+                "text": output.to_string(),
             }]
         }),
     )
@@ -277,9 +339,45 @@ fn handle_shutdown(req: &RpcRequest) -> RpcResponse {
     rpc_ok(req.response_id(), json!({}))
 }
 
-async fn dispatch(req: &RpcRequest) -> (RpcResponse, bool) {
+/// Builds a `-32602` error whose message explains which field is wrong.
+fn invalid_params(req: &RpcRequest, detail: &str) -> RpcResponse {
+    rpc_err(
+        req.response_id(),
+        -32602,
+        format!("Invalid params: {detail}"),
+    )
+}
+
+/// MCP uses named parameters only: `params` must be an object when present,
+/// and `tools/call` cannot omit it.
+fn check_params(req: &RpcRequest) -> Option<RpcResponse> {
+    match &req.params {
+        Value::Object(_) => None,
+        Value::Null if req.method != "tools/call" => None,
+        Value::Null => Some(invalid_params(
+            req,
+            &format!("`params` is required for `{}`", req.method),
+        )),
+        _ => Some(invalid_params(
+            req,
+            &format!("`params` must be an object for `{}`", req.method),
+        )),
+    }
+}
+
+async fn dispatch(req: &RpcRequest, capabilities: &Capabilities) -> (RpcResponse, bool) {
+    if !capabilities.allows(&req.method) {
+        return (rpc_err(req.response_id(), -32601, "method disabled"), false);
+    }
+    if let Some(response) = check_params(req) {
+        return (response, false);
+    }
     match req.method.as_str() {
         "initialize" => (handle_initialize(req), false),
+        // Sent by the client once it has processed the `initialize` result.
+        "notifications/initialized" | "initialized" => {
+            (rpc_ok(req.response_id(), json!({})), false)
+        }
         "ping" => (handle_ping(req), false),
         "tools/list" => (handle_tools_list(req), false),
         "tools/call" => (handle_tools_call(req).await, false),
@@ -326,7 +424,13 @@ fn parse_request(line: &str) -> Result<RpcRequest> {
     })
 }
 
-async fn handle_line(line: &str) -> (Option<RpcResponse>, bool) {
+/// Handles one request. `initialized` tracks whether the session has seen a
+/// successful `initialize`, which strict mode requires before other methods.
+async fn handle_line(
+    line: &str,
+    capabilities: &Capabilities,
+    initialized: &mut bool,
+) -> (Option<RpcResponse>, bool) {
     let parsed = match parse_request(line) {
         Ok(req) => req,
         Err(err) => {
@@ -353,7 +457,30 @@ async fn handle_line(line: &str) -> (Option<RpcResponse>, bool) {
         );
     }
 
-    let (response, should_shutdown) = dispatch(&parsed).await;
+    if capabilities.strict && !*initialized && !LIFECYCLE_METHODS.contains(&parsed.method.as_str())
+    {
+        let response = rpc_err(
+            parsed.response_id(),
+            SERVER_NOT_INITIALIZED,
+            format!(
+                "Server not initialized; send `initialize` before `{}`",
+                parsed.method
+            ),
+        );
+        return (
+            if is_notification(parsed.has_id) {
+                None
+            } else {
+                Some(response)
+            },
+            false,
+        );
+    }
+
+    let (response, should_shutdown) = dispatch(&parsed, capabilities).await;
+    if parsed.method == "initialize" && response.error.is_none() {
+        *initialized = true;
+    }
     (
         if is_notification(parsed.has_id) {
             None
@@ -451,12 +578,17 @@ async fn read_message<R: AsyncBufRead + Unpin>(
     Ok(Some((headers, body)))
 }
 
-async fn serve_stream<R, W>(mut reader: R, mut writer: W) -> Result<()>
+async fn serve_stream<R, W>(
+    mut reader: R,
+    mut writer: W,
+    trace_file: Option<&std::path::Path>,
+    capabilities: &Capabilities,
+) -> Result<()>
 where
     R: AsyncBufRead + Unpin,
     W: AsyncWrite + Unpin,
 {
-    let mut trace = TraceLogger::new();
+    let mut trace = TraceLogger::new(trace_file);
     if trace.enabled() {
         let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("<unknown>"));
         trace.log(format!(
@@ -466,6 +598,7 @@ where
         ));
     }
 
+    let mut initialized = false;
     loop {
         let (headers, body) = match read_message(&mut reader).await {
             Ok(Some(value)) => value,
@@ -480,7 +613,8 @@ where
         let body_str = std::str::from_utf8(&body).context("MCP body not valid UTF-8")?;
 
         let response_as_line = line_delimited_response_enabled();
-        let (response, should_shutdown) = handle_line(body_str).await;
+        let (response, should_shutdown) =
+            handle_line(body_str, capabilities, &mut initialized).await;
         if trace.enabled() {
             if headers.is_empty() {
                 trace.log("MCP <- headers: (none, line-delimited request)");
@@ -534,10 +668,27 @@ where
 }
 
 /// Serve MCP over stdio using MCP's `Content-Length` framing.
-pub async fn serve_stdio() -> Result<()> {
+///
+/// When `TASKTER_MCP_READY_LINE` is set, a single readiness line is written to
+/// stderr before the first request is read. Returns `Ok` after a `shutdown`
+/// request or end of input and an error on transport failures, so the process
+/// exit code tells supervisors how the session ended.
+///
+/// `trace_file` enables tracing to that path, overriding the
+/// `TASKTER_MCP_TRACE*` environment variables. Methods that `capabilities`
+/// disables are answered with a `-32601 "method disabled"` error, and in
+/// strict mode requests other than lifecycle methods fail with `-32002` until
+/// `initialize` has succeeded.
+pub async fn serve_stdio(
+    trace_file: Option<&std::path::Path>,
+    capabilities: &Capabilities,
+) -> Result<()> {
+    if ready_line_enabled() {
+        eprintln!("taskter MCP ready, protocol {MCP_PROTOCOL_VERSION}");
+    }
     let reader = BufReader::new(io::stdin());
     let writer = io::stdout();
-    serve_stream(reader, writer).await
+    serve_stream(reader, writer, trace_file, capabilities).await
 }
 
 #[cfg(test)]
@@ -572,6 +723,16 @@ mod tests {
         EnvVarGuard { name, previous }
     }
 
+    /// Restores the default configuration when dropped, even if the test
+    /// panics first.
+    struct ConfigResetGuard;
+
+    impl Drop for ConfigResetGuard {
+        fn drop(&mut self) {
+            let _ = crate::config::init(&crate::config::ConfigOverrides::default());
+        }
+    }
+
     fn parse_content_length_response(response: &str) -> (usize, &str) {
         let (headers, body) = response
             .split_once("\r\n\r\n")
@@ -594,7 +755,7 @@ mod tests {
             method: "tools/list".into(),
             params: json!({}),
         };
-        let (resp, _) = dispatch(&req).await;
+        let (resp, _) = dispatch(&req, &Capabilities::default()).await;
         let tools = resp
             .result
             .as_ref()
@@ -605,6 +766,169 @@ mod tests {
         assert!(!tools.is_empty(), "expected at least one tool");
     }
 
+    #[tokio::test]
+    async fn tools_call_rejects_malformed_params() {
+        let call = |params: Value| RpcRequest {
+            jsonrpc: JSONRPC.to_string(),
+            id: json!(7),
+            has_id: true,
+            method: "tools/call".into(),
+            params,
+        };
+        for (params, detail) in [
+            (
+                json!(["run_bash"]),
+                "`params` must be an object for `tools/call`",
+            ),
+            (json!({"name": 42}), "`name` must be a string"),
+            (json!({"arguments": {}}), "missing tool `name`"),
+            (
+                json!({"name": "run_bash", "arguments": "ls"}),
+                "`arguments` must be an object",
+            ),
+            (Value::Null, "`params` is required for `tools/call`"),
+        ] {
+            let (resp, shutdown) = dispatch(&call(params), &Capabilities::default()).await;
+            assert!(!shutdown);
+            assert_eq!(resp.id, Some(json!(7)));
+            let error = resp.error.expect("error response");
+            assert_eq!(error.code, -32602);
+            assert_eq!(error.message, format!("Invalid params: {detail}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn ping_accepts_missing_params_but_not_arrays() {
+        let ping = |params: Value| RpcRequest {
+            jsonrpc: JSONRPC.to_string(),
+            id: json!(1),
+            has_id: true,
+            method: "ping".into(),
+            params,
+        };
+        let (resp, _) = dispatch(&ping(Value::Null), &Capabilities::default()).await;
+        assert!(resp.error.is_none());
+        let (resp, _) = dispatch(&ping(json!([1])), &Capabilities::default()).await;
+        assert_eq!(resp.error.expect("error response").code, -32602);
+    }
+
+    #[tokio::test]
+    async fn disabled_methods_return_method_disabled() {
+        let request = |method: &str| RpcRequest {
+            jsonrpc: JSONRPC.to_string(),
+            id: json!(3),
+            has_id: true,
+            method: method.into(),
+            params: json!({"name": "run_bash", "arguments": {"command": "ls"}}),
+        };
+        let capabilities = Capabilities::without(["tools/call"]);
+
+        let (resp, shutdown) = dispatch(&request("tools/call"), &capabilities).await;
+        assert!(!shutdown);
+        let error = resp.error.expect("error response");
+        assert_eq!(error.code, -32601);
+        assert_eq!(error.message, "method disabled");
+
+        let (resp, _) = dispatch(&request("tools/list"), &capabilities).await;
+        assert!(resp.error.is_none());
+        assert!(resp.result.expect("tools/list result")["tools"].is_array());
+    }
+
+    #[tokio::test]
+    async fn initialized_notification_is_accepted_and_strict_mode_requires_initialize() {
+        let strict = Capabilities::default().strict(true);
+        let mut initialized = false;
+
+        let (resp, _) = handle_line(
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+            &strict,
+            &mut initialized,
+        )
+        .await;
+        let error = resp.and_then(|r| r.error).expect("tools/list rejected");
+        assert_eq!(error.code, SERVER_NOT_INITIALIZED);
+
+        let (resp, _) = handle_line(
+            r#"{"jsonrpc":"2.0","id":2,"method":"initialize","params":{}}"#,
+            &strict,
+            &mut initialized,
+        )
+        .await;
+        assert!(resp.expect("initialize response").error.is_none());
+        assert!(initialized);
+
+        for method in ["notifications/initialized", "initialized"] {
+            let line = format!(r#"{{"jsonrpc":"2.0","method":"{method}"}}"#);
+            let (resp, shutdown) = handle_line(&line, &strict, &mut initialized).await;
+            assert!(resp.is_none(), "{method} produced a response");
+            assert!(!shutdown);
+        }
+        let (resp, _) = dispatch(
+            &RpcRequest {
+                jsonrpc: JSONRPC.to_string(),
+                id: json!(3),
+                has_id: true,
+                method: "notifications/initialized".into(),
+                params: Value::Null,
+            },
+            &strict,
+        )
+        .await;
+        assert!(resp.error.is_none());
+
+        let (resp, _) = handle_line(
+            r#"{"jsonrpc":"2.0","id":4,"method":"tools/list"}"#,
+            &strict,
+            &mut initialized,
+        )
+        .await;
+        assert!(resp.expect("tools/list response").error.is_none());
+
+        let mut lenient = false;
+        let (resp, _) = handle_line(
+            r#"{"jsonrpc":"2.0","id":5,"method":"tools/list"}"#,
+            &Capabilities::default(),
+            &mut lenient,
+        )
+        .await;
+        assert!(resp.expect("tools/list response").error.is_none());
+    }
+
+    #[tokio::test]
+    async fn tools_list_omits_disabled_tools() {
+        let _guard = ENV_MUTEX.lock().await;
+        let dir = tempfile::tempdir().expect("tempdir");
+        let config_file = dir.path().join("config.toml");
+        std::fs::write(&config_file, "[tools]\ndisabled = [\"run_bash\"]\n").expect("write config");
+        crate::config::init(&crate::config::ConfigOverrides {
+            config_file: Some(config_file),
+            ..Default::default()
+        })
+        .expect("init config");
+        let _config_guard = ConfigResetGuard;
+
+        let req = RpcRequest {
+            jsonrpc: JSONRPC.to_string(),
+            id: json!(1),
+            has_id: true,
+            method: "tools/list".into(),
+            params: json!({}),
+        };
+        let (resp, _) = dispatch(&req, &Capabilities::default()).await;
+        let names: Vec<String> = resp
+            .result
+            .as_ref()
+            .and_then(|v| v.get("tools"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|t| t.get("name").and_then(Value::as_str).map(str::to_string))
+            .collect();
+
+        assert!(!names.is_empty());
+        assert!(!names.iter().any(|n| n == "run_bash"));
+    }
+
     #[tokio::test]
     async fn content_length_round_trip() {
         let _guard = ENV_MUTEX.lock().await;
@@ -614,8 +938,9 @@ mod tests {
         let server_reader = BufReader::new(server_read);
         let (mut client_reader, mut client_writer) = tokio::io::split(client);
 
-        let server_task =
-            tokio::spawn(async move { serve_stream(server_reader, server_write).await });
+        let server_task = tokio::spawn(async move {
+            serve_stream(server_reader, server_write, None, &Capabilities::default()).await
+        });
 
         let request_body = r#"{"jsonrpc":"2.0","id":1,"method":"ping","params":{}}"#;
         let request = format!(
@@ -651,8 +976,9 @@ mod tests {
         let server_reader = BufReader::new(server_read);
         let (mut client_reader, mut client_writer) = tokio::io::split(client);
 
-        let server_task =
-            tokio::spawn(async move { serve_stream(server_reader, server_write).await });
+        let server_task = tokio::spawn(async move {
+            serve_stream(server_reader, server_write, None, &Capabilities::default()).await
+        });
 
         let request_body = r#"{"jsonrpc":"2.0","id":1,"method":"ping","params":{}}"#;
         let request = format!("{request_body}\n");
@@ -687,8 +1013,9 @@ mod tests {
         let server_reader = BufReader::new(server_read);
         let (mut client_reader, mut client_writer) = tokio::io::split(client);
 
-        let server_task =
-            tokio::spawn(async move { serve_stream(server_reader, server_write).await });
+        let server_task = tokio::spawn(async move {
+            serve_stream(server_reader, server_write, None, &Capabilities::default()).await
+        });
 
         let request_body = r#"{"jsonrpc":"2.0","id":1,"method":"ping","params":{}}"#;
         let request = format!(
@@ -717,4 +1044,46 @@ mod tests {
 
         server_task.await.unwrap().unwrap();
     }
+
+    #[tokio::test]
+    async fn trace_path_receives_round_trip() {
+        let _guard = ENV_MUTEX.lock().await;
+        let _env_guard = set_env_var("TASKTER_MCP_TRACE", None);
+        let dir = tempfile::tempdir().expect("tempdir");
+        let trace_file = dir.path().join("trace.log");
+
+        let (client, server) = duplex(4096);
+        let (server_read, server_write) = tokio::io::split(server);
+        let server_reader = BufReader::new(server_read);
+        let (mut client_reader, mut client_writer) = tokio::io::split(client);
+
+        let server_trace = trace_file.clone();
+        let server_task = tokio::spawn(async move {
+            serve_stream(
+                server_reader,
+                server_write,
+                Some(&server_trace),
+                &Capabilities::default(),
+            )
+            .await
+        });
+
+        let request_body = r#"{"jsonrpc":"2.0","id":1,"method":"ping","params":{}}"#;
+        let request = format!(
+            "Content-Length: {}\r\n\r\n{}",
+            request_body.len(),
+            request_body
+        );
+        client_writer.write_all(request.as_bytes()).await.unwrap();
+        client_writer.shutdown().await.unwrap();
+
+        let mut response_raw = Vec::new();
+        client_reader.read_to_end(&mut response_raw).await.unwrap();
+        server_task.await.unwrap().unwrap();
+
+        let trace = std::fs::read_to_string(&trace_file).expect("trace file written");
+        assert!(trace.contains("MCP server started"));
+        assert!(trace.contains(&format!("MCP <- body: {request_body}")));
+        assert!(trace.contains(r#"MCP -> body: {"jsonrpc":"2.0","id":1,"result":{}}"#));
+    }
 }
@@ -3,6 +3,10 @@ use serde_json::{json, Value};
 
 use super::{ModelAction, ModelProvider};
 use crate::agent::Agent;
+use crate::tools::ToolOutput;
+
+/// Base URL of the Gemini `generateContent` API; the model name is appended.
+pub const MODELS_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
 
 pub struct GeminiProvider;
 
@@ -28,28 +32,40 @@ impl ModelProvider for GeminiProvider {
         history: &mut Vec<Value>,
         tool_name: &str,
         args: &Value,
-        tool_response: &str,
-        _call_id: Option<&str>,
+        tool_response: &ToolOutput,
+        call_id: Option<&str>,
     ) {
+        // Gemini does not always return call ids, so synthesize one from the
+        // number of calls already in the history to keep each
+        // functionCall/functionResponse pair correlated.
+        let id = call_id.map_or_else(
+            || format!("call_{}", count_function_calls(history)),
+            str::to_string,
+        );
         history.push(json!({
             "role": "model",
-            "parts": [{"functionCall": {"name": tool_name, "args": args}}]
+            "parts": [{"functionCall": {"id": id, "name": tool_name, "args": args}}]
         }));
         history.push(json!({
             "role": "tool",
-            "parts": [{"functionResponse": {"name": tool_name, "response": {"content": tool_response}}}]
+            "parts": [{"functionResponse": {"id": id, "name": tool_name, "response": function_response(tool_response)}}]
         }));
     }
 
+    fn append_text_reply(&self, _agent: &Agent, history: &mut Vec<Value>, reply: &str) {
+        history.push(json!({"role": "model", "parts": [{"text": reply}]}));
+    }
+
+    fn append_user_turn(&self, _agent: &Agent, history: &mut Vec<Value>, message: &str) {
+        history.push(json!({"role": "user", "parts": [{"text": message}]}));
+    }
+
     fn tools_payload(&self, agent: &Agent) -> Value {
         json!({"functionDeclarations": agent.tools})
     }
 
     fn endpoint(&self, agent: &Agent) -> String {
-        format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent",
-            agent.model
-        )
+        format!("{MODELS_URL}/{}:generateContent", agent.model)
     }
 
     fn request_body(&self, _agent: &Agent, history: &[Value], tools: &Value) -> Value {
@@ -60,6 +76,7 @@ impl ModelProvider for GeminiProvider {
     }
 
     fn parse_response(&self, response_json: &Value) -> Result<ModelAction> {
+        super::check_error_body(response_json)?;
         let candidate = &response_json["candidates"][0];
         let part = &candidate["content"]["parts"][0];
 
@@ -75,7 +92,10 @@ impl ModelProvider for GeminiProvider {
             return Ok(ModelAction::ToolCall {
                 name: tool_name.to_string(),
                 args,
-                call_id: None,
+                call_id: function_call
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
             });
         }
 
@@ -95,3 +115,22 @@ impl ModelProvider for GeminiProvider {
         ]
     }
 }
+
+/// `functionResponse.response` must be an object: JSON objects are passed
+/// through and anything else is wrapped in `{"content": ...}`.
+fn function_response(tool_response: &ToolOutput) -> Value {
+    match tool_response {
+        ToolOutput::Json(value @ Value::Object(_)) => value.clone(),
+        ToolOutput::Json(value) => json!({"content": value}),
+        ToolOutput::Text(text) => json!({"content": text}),
+    }
+}
+
+fn count_function_calls(history: &[Value]) -> usize {
+    history
+        .iter()
+        .filter_map(|entry| entry["parts"].as_array())
+        .flatten()
+        .filter(|part| part.get("functionCall").is_some())
+        .count()
+}
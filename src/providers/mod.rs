@@ -5,6 +5,7 @@ use std::fs::OpenOptions;
 use std::io::Write as _;
 
 use crate::agent::Agent;
+use crate::tools::ToolOutput;
 
 #[derive(Debug)]
 pub enum ModelAction {
@@ -18,6 +19,26 @@ pub enum ModelAction {
     },
 }
 
+/// Fails with the provider's message when a response body carries a
+/// top-level `error`, as some gateways do alongside an HTTP 200 status.
+///
+/// Both `{"error": {"message": "..."}}` and `{"error": "..."}` are recognised.
+///
+/// # Errors
+///
+/// Returns the provider error message when one is present.
+pub fn check_error_body(response_json: &Value) -> Result<()> {
+    let Some(error) = response_json.get("error").filter(|e| !e.is_null()) else {
+        return Ok(());
+    };
+    let message = error
+        .get("message")
+        .and_then(Value::as_str)
+        .or_else(|| error.as_str())
+        .map_or_else(|| error.to_string(), str::to_string);
+    anyhow::bail!("Provider returned an error: {message}")
+}
+
 pub trait ModelProvider {
     fn name(&self) -> &'static str;
     fn api_key_env(&self) -> &'static str;
@@ -31,9 +52,13 @@ pub trait ModelProvider {
         history: &mut Vec<Value>,
         tool_name: &str,
         args: &Value,
-        tool_response: &str,
+        tool_response: &ToolOutput,
         call_id: Option<&str>,
     );
+    /// Records the model's final text answer so a later turn can see it.
+    fn append_text_reply(&self, agent: &Agent, history: &mut Vec<Value>, reply: &str);
+    /// Adds a follow-up user message to an existing history.
+    fn append_user_turn(&self, agent: &Agent, history: &mut Vec<Value>, message: &str);
     fn tools_payload(&self, agent: &Agent) -> Value;
     fn endpoint(&self, agent: &Agent) -> String;
     fn request_body(&self, agent: &Agent, history: &[Value], tools: &Value) -> Value;
@@ -139,6 +164,63 @@ fn is_ollama_model(model_lc: &str) -> bool {
         || model_lc.starts_with("ollama-")
 }
 
+fn is_gemini_model(model_lc: &str) -> bool {
+    model_lc.starts_with("gemini") || model_lc.starts_with("models/gemini")
+}
+
+/// Well-known model names offered as suggestions for unrecognized models.
+const KNOWN_MODELS: &[&str] = &[
+    "gpt-4.1",
+    "gpt-4.1-mini",
+    "gpt-4o",
+    "gpt-5",
+    "o1",
+    "o3",
+    "o4-mini",
+    "gemini-2.5-pro",
+    "gemini-2.5-flash",
+];
+
+/// OpenAI prefixes missing the dash. They still route to OpenAI but are
+/// usually typos for `gpt-4*` / `gpt-5*` model names.
+fn is_dashless_gpt(model_lc: &str) -> bool {
+    model_lc.starts_with("gpt4") || model_lc.starts_with("gpt5")
+}
+
+/// Returns a warning when no provider was given explicitly and `model`
+/// matches no known provider prefix, so it would silently fall back to the
+/// default provider, or is a dash-less `gpt4` / `gpt5` spelling.
+///
+/// The model is still accepted; this only helps catch typos.
+pub fn model_warning(model: &str, provider: Option<&str>) -> Option<String> {
+    if provider.is_some() {
+        return None;
+    }
+    let model_lc = model.trim().to_lowercase();
+    let recognized =
+        is_openai_model(&model_lc) || is_ollama_model(&model_lc) || is_gemini_model(&model_lc);
+    if recognized && !is_dashless_gpt(&model_lc) {
+        return None;
+    }
+    let mut warning = if recognized {
+        format!("Warning: model `{model}` is not a known OpenAI model name.")
+    } else {
+        format!(
+            "Warning: model `{model}` does not match a known provider and will be sent to the default provider `{}`.",
+            default_provider()
+        )
+    };
+    let suggestion = KNOWN_MODELS
+        .iter()
+        .map(|known| (crate::tools::edit_distance(&model_lc, known), *known))
+        .filter(|(distance, known)| *distance <= (known.len() / 3).max(2))
+        .min_by_key(|(distance, _)| *distance);
+    if let Some((_, known)) = suggestion {
+        warning.push_str(&format!(" Did you mean `{known}`?"));
+    }
+    Some(warning)
+}
+
 fn provider_from_field(agent: &Agent) -> Option<String> {
     agent
         .provider
@@ -153,10 +235,14 @@ fn fallback_provider(agent: &Agent) -> String {
     } else if is_openai_model(&model_lc) {
         "openai".to_string()
     } else {
-        "gemini".to_string()
+        default_provider()
     }
 }
 
+fn default_provider() -> String {
+    crate::config::default_provider().unwrap_or_else(|_| "gemini".to_string())
+}
+
 pub fn resolve_provider_name(agent: &Agent) -> String {
     provider_from_field(agent).unwrap_or_else(|| fallback_provider(agent))
 }
@@ -3,6 +3,7 @@ use serde_json::{json, Value};
 
 use super::{ModelAction, ModelProvider};
 use crate::agent::Agent;
+use crate::tools::ToolOutput;
 
 pub struct OllamaProvider;
 
@@ -22,7 +23,8 @@ impl OllamaProvider {
             .unwrap_or_else(|_| "http://localhost:11434".to_string())
     }
 
-    fn endpoint_url() -> String {
+    /// The `/api/chat` URL under the configured base URL.
+    pub fn endpoint_url() -> String {
         let base = Self::base_url();
         let trimmed = base.trim_end_matches('/');
         format!("{trimmed}/api/chat")
@@ -55,7 +57,7 @@ impl ModelProvider for OllamaProvider {
         history: &mut Vec<Value>,
         tool_name: &str,
         args: &Value,
-        tool_response: &str,
+        tool_response: &ToolOutput,
         call_id: Option<&str>,
     ) {
         let id = call_id.unwrap_or("tool_call_1");
@@ -80,10 +82,18 @@ impl ModelProvider for OllamaProvider {
             "role": "tool",
             "tool_call_id": id,
             "name": tool_name,
-            "content": tool_response
+            "content": tool_response.to_string()
         }));
     }
 
+    fn append_text_reply(&self, _agent: &Agent, history: &mut Vec<Value>, reply: &str) {
+        history.push(json!({"role": "assistant", "content": reply}));
+    }
+
+    fn append_user_turn(&self, _agent: &Agent, history: &mut Vec<Value>, message: &str) {
+        history.push(json!({"role": "user", "content": message}));
+    }
+
     fn tools_payload(&self, agent: &Agent) -> Value {
         json!(agent
             .tools
@@ -118,6 +128,7 @@ impl ModelProvider for OllamaProvider {
     }
 
     fn parse_response(&self, response_json: &Value) -> Result<ModelAction> {
+        super::check_error_body(response_json)?;
         if let Some(message) = response_json.get("message") {
             if let Some(tool_calls) = message.get("tool_calls").and_then(|t| t.as_array()) {
                 if let Some(tc) = tool_calls.first() {
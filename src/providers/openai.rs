@@ -3,6 +3,7 @@ use serde_json::{json, Value};
 
 use super::{ModelAction, ModelProvider};
 use crate::agent::Agent;
+use crate::tools::ToolOutput;
 
 pub struct OpenAIProvider;
 
@@ -77,7 +78,7 @@ impl ModelProvider for OpenAIProvider {
         history: &mut Vec<Value>,
         tool_name: &str,
         args: &Value,
-        tool_response: &str,
+        tool_response: &ToolOutput,
         call_id: Option<&str>,
     ) {
         let id = call_id.unwrap_or("tool_call_1");
@@ -98,7 +99,7 @@ impl ModelProvider for OpenAIProvider {
                 history.push(json!({
                     "type": "function_call_output",
                     "call_id": id,
-                    "output": tool_response
+                    "output": tool_response.to_string()
                 }));
             }
             RequestStyle::ChatCompletions => {
@@ -123,12 +124,36 @@ impl ModelProvider for OpenAIProvider {
                     "role": "tool",
                     "tool_call_id": id,
                     "name": tool_name,
-                    "content": tool_response
+                    "content": tool_response.to_string()
                 }));
             }
         }
     }
 
+    fn append_text_reply(&self, agent: &Agent, history: &mut Vec<Value>, reply: &str) {
+        match Self::request_style(agent) {
+            RequestStyle::Responses => history.push(json!({
+                "role": "assistant",
+                "content": [ {"type": "output_text", "text": reply } ]
+            })),
+            RequestStyle::ChatCompletions => {
+                history.push(json!({"role": "assistant", "content": reply}));
+            }
+        }
+    }
+
+    fn append_user_turn(&self, agent: &Agent, history: &mut Vec<Value>, message: &str) {
+        match Self::request_style(agent) {
+            RequestStyle::Responses => history.push(json!({
+                "role": "user",
+                "content": [ {"type": "input_text", "text": message } ]
+            })),
+            RequestStyle::ChatCompletions => {
+                history.push(json!({"role": "user", "content": message}));
+            }
+        }
+    }
+
     fn tools_payload(&self, agent: &Agent) -> Value {
         // Map FunctionDeclaration to the OpenAI tools schema
         match Self::request_style(agent) {
@@ -203,6 +228,7 @@ impl ModelProvider for OpenAIProvider {
     }
 
     fn parse_response(&self, v: &Value) -> Result<ModelAction> {
+        super::check_error_body(v)?;
         // Responses parsing
         if let Some(output_items) = v.get("output").and_then(|o| o.as_array()) {
             for out in output_items {
@@ -326,11 +352,20 @@ impl ModelProvider for OpenAIProvider {
     }
 
     fn headers(&self, api_key: &str) -> Vec<(String, String)> {
-        vec![
+        let mut headers = vec![
             ("Authorization".to_string(), format!("Bearer {api_key}")),
             ("Content-Type".to_string(), "application/json".to_string()),
             // Model is provided in the body; keep headers minimal.
-        ]
+        ];
+        if let Ok(cfg) = crate::config::openai() {
+            if let Some(org) = cfg.organization {
+                headers.push(("OpenAI-Organization".to_string(), org));
+            }
+            if let Some(project) = cfg.project {
+                headers.push(("OpenAI-Project".to_string(), project));
+            }
+        }
+        headers
     }
 }
 
@@ -1,81 +1,324 @@
 //! Cron-based scheduler that runs agents on a timetable.
 
-use crate::{agent, store};
+use crate::logging::{self, LogEvent};
+use crate::{agent, config, store};
 use agent::ExecutionResult;
 use chrono_tz::America::New_York;
 use futures::future::join_all;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
-use store::TaskStatus;
+use store::{CommentAuthor, TaskStatus};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
 use tokio_cron_scheduler::{Job, JobScheduler};
 
-/// Starts the background scheduler and runs due agents.
+/// How often a running scheduler checks for the stop sentinel.
+const STOP_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// File that `scheduler stop` creates to ask a running scheduler to exit.
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be loaded.
+pub fn stop_sentinel_path() -> anyhow::Result<PathBuf> {
+    Ok(config::dir()?.join("scheduler.stop"))
+}
+
+/// Asks a running scheduler to shut down by creating the stop sentinel.
+///
+/// # Errors
+///
+/// Returns an error if the sentinel cannot be written.
+pub fn request_stop() -> anyhow::Result<()> {
+    fs::write(stop_sentinel_path()?, "")?;
+    Ok(())
+}
+
+/// Resolves once `sentinel` exists, checking every `poll`, and removes it so
+/// the next scheduler start is not stopped right away.
+pub async fn wait_for_stop(sentinel: &Path, poll: Duration) {
+    loop {
+        if sentinel.exists() {
+            let _ = fs::remove_file(sentinel);
+            return;
+        }
+        tokio::time::sleep(poll).await;
+    }
+}
+
+/// Counters updated by scheduled jobs and exposed by `scheduler run --metrics`.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    jobs_fired: AtomicU64,
+    executions_succeeded: AtomicU64,
+    executions_failed: AtomicU64,
+}
+
+impl Metrics {
+    /// Renders the counters, plus the number of running agents, in the
+    /// Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let running = agent::load_running_agents().map_or(0, |ids| ids.len());
+        let mut out = String::new();
+        for (name, kind, help, value) in [
+            (
+                "taskter_scheduler_jobs_fired_total",
+                "counter",
+                "Scheduled jobs that have fired.",
+                self.jobs_fired.load(Ordering::Relaxed),
+            ),
+            (
+                "taskter_scheduler_executions_succeeded_total",
+                "counter",
+                "Agent executions started by the scheduler that succeeded.",
+                self.executions_succeeded.load(Ordering::Relaxed),
+            ),
+            (
+                "taskter_scheduler_executions_failed_total",
+                "counter",
+                "Agent executions started by the scheduler that failed.",
+                self.executions_failed.load(Ordering::Relaxed),
+            ),
+            (
+                "taskter_agents_running",
+                "gauge",
+                "Agents currently executing a task.",
+                running as u64,
+            ),
+        ] {
+            out.push_str(&format!(
+                "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"
+            ));
+        }
+        out
+    }
+}
+
+/// Answers `GET /metrics` on `listener` with [`Metrics::render`] and every
+/// other request with 404.
+async fn serve_metrics(listener: TcpListener, metrics: Arc<Metrics>) {
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let metrics = Arc::clone(&metrics);
+        tokio::spawn(async move {
+            let mut request = [0u8; 1024];
+            let Ok(len) = stream.read(&mut request).await else {
+                return;
+            };
+            let request = String::from_utf8_lossy(&request[..len]);
+            let response = if request.starts_with("GET /metrics ") {
+                let body = metrics.render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string()
+            };
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+/// Starts the background scheduler and runs due agents until `scheduler stop`
+/// is requested or the process receives Ctrl-C (or, with
+/// [`RunOptions::run_for`], the time is up).
 ///
 /// # Errors
 ///
 /// Returns an error if the scheduler cannot be created, a job cannot be added,
-/// or if the scheduler fails to start.
+/// or if the scheduler fails to start or shut down.
 pub async fn run() -> anyhow::Result<()> {
+    run_with(RunOptions::default()).await
+}
+
+/// Optional behaviour of [`run_with`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunOptions {
+    /// Serve Prometheus metrics at `http://<addr>/metrics`.
+    pub metrics_addr: Option<SocketAddr>,
+    /// Shut down cleanly once this much time has passed.
+    pub run_for: Option<Duration>,
+    /// Most task executions to run at once across all agents. Falls back to
+    /// `scheduler.max_concurrent_tasks`, and to no limit when that is unset.
+    pub max_concurrent_tasks: Option<usize>,
+}
+
+/// Like [`run`], with the extras selected in `options`.
+///
+/// # Errors
+///
+/// Returns an error if the metrics address cannot be bound, or for any of the
+/// reasons [`run`] fails.
+pub async fn run_with(options: RunOptions) -> anyhow::Result<()> {
+    let RunOptions {
+        metrics_addr,
+        run_for,
+        max_concurrent_tasks,
+    } = options;
     let agents = agent::load_agents()?;
-    let sched = JobScheduler::new().await?;
+    let limit = match max_concurrent_tasks.filter(|limit| *limit > 0) {
+        Some(limit) => Some(limit),
+        None => config::scheduler_max_concurrent_tasks()?,
+    };
+    // Shared by every job, so the limit holds across agents firing together.
+    let permits = Arc::new(Semaphore::new(
+        limit.map_or(Semaphore::MAX_PERMITS, |limit| {
+            limit.min(Semaphore::MAX_PERMITS)
+        }),
+    ));
+    let mut sched = JobScheduler::new().await?;
+    let sentinel = stop_sentinel_path()?;
+    // A sentinel left behind while no scheduler was running is stale.
+    let _ = fs::remove_file(&sentinel);
+    let metrics = Arc::new(Metrics::default());
+    let server = match metrics_addr {
+        Some(addr) => {
+            let listener = TcpListener::bind(addr).await?;
+            Some(tokio::spawn(serve_metrics(listener, Arc::clone(&metrics))))
+        }
+        None => None,
+    };
 
     for ag in agents {
         if let Some(expr) = &ag.schedule {
             let job_agent = ag.clone();
             let cron_expr = expr.clone();
+            let job_metrics = Arc::clone(&metrics);
+            let job_permits = Arc::clone(&permits);
             let job = Job::new_async_tz(cron_expr, New_York, move |_id, l| {
                 let a = job_agent.clone();
+                let metrics = Arc::clone(&job_metrics);
+                let permits = Arc::clone(&job_permits);
                 Box::pin(async move {
-                    if let Ok(mut board) = store::load_board() {
-                        let tasks: Vec<usize> = board
-                            .tasks
-                            .iter()
-                            .filter(|t| t.agent_id == Some(a.id) && t.status != TaskStatus::Done)
-                            .map(|t| t.id)
-                            .collect();
-
-                        if tasks.is_empty() {
-                            let _ = agent::execute_task(&a, None).await;
-                        } else {
-                            let task_data: Vec<(usize, store::Task)> = tasks
+                    match store::load_board() {
+                        Err(err) => log_activity(
+                            "job_skipped",
+                            a.id,
+                            format!("Scheduler skipped agent {}: {err}", a.id),
+                        ),
+                        Ok(board) => {
+                            let tasks: Vec<usize> = board
+                                .tasks
                                 .iter()
-                                .filter_map(|id| {
-                                    board
-                                        .tasks
-                                        .iter()
-                                        .find(|t| t.id == *id)
-                                        .cloned()
-                                        .map(|task| (*id, task))
+                                .filter(|t| {
+                                    t.agent_id == Some(a.id) && t.status != TaskStatus::Done
                                 })
+                                .map(|t| t.id)
                                 .collect();
+                            log_activity(
+                                "job_fired",
+                                a.id,
+                                format!(
+                                    "Scheduler fired agent {} with {} open task(s)",
+                                    a.id,
+                                    tasks.len()
+                                ),
+                            );
+                            metrics.jobs_fired.fetch_add(1, Ordering::Relaxed);
+                            let mut succeeded = 0;
+                            let mut failed = 0;
 
-                            let handles = task_data.into_iter().map(|(id, task)| {
-                                let agent_clone = a.clone();
-                                tokio::spawn(async move {
-                                    (id, agent::execute_task(&agent_clone, Some(&task)).await)
-                                })
-                            });
-
-                            for (task_id, exec) in join_all(handles).await.into_iter().flatten() {
-                                if let Ok(exec) = exec {
-                                    if let Some(task_mut) =
-                                        board.tasks.iter_mut().find(|t| t.id == task_id)
-                                    {
-                                        match exec {
-                                            ExecutionResult::Success { comment } => {
-                                                task_mut.status = TaskStatus::Done;
-                                                task_mut.comment = Some(comment);
+                            if tasks.is_empty() {
+                                let _permit = permits.acquire().await;
+                                match agent::execute_task(&a, None).await {
+                                    Ok(ExecutionResult::Success { .. }) => succeeded += 1,
+                                    _ => failed += 1,
+                                }
+                            } else {
+                                let task_data: Vec<(usize, store::Task)> = tasks
+                                    .iter()
+                                    .filter_map(|id| {
+                                        board
+                                            .tasks
+                                            .iter()
+                                            .find(|t| t.id == *id)
+                                            .cloned()
+                                            .map(|task| (*id, task))
+                                    })
+                                    .collect();
+
+                                let handles = task_data.into_iter().map(|(id, task)| {
+                                    let agent_clone = a.clone();
+                                    let permits = Arc::clone(&permits);
+                                    tokio::spawn(async move {
+                                        let _permit = permits.acquire_owned().await;
+                                        (id, agent::execute_task(&agent_clone, Some(&task)).await)
+                                    })
+                                });
+
+                                let mut outcomes = Vec::new();
+                                for (task_id, exec) in join_all(handles).await.into_iter().flatten()
+                                {
+                                    if let Ok(exec) = exec {
+                                        match &exec {
+                                            ExecutionResult::Success { .. } => succeeded += 1,
+                                            ExecutionResult::Failure { .. } => failed += 1,
+                                        }
+                                        outcomes.push((task_id, exec));
+                                    } else {
+                                        failed += 1;
+                                    }
+                                }
+                                // Agent runs can take minutes, so the results are
+                                // applied to a fresh copy of the board under the
+                                // board lock instead of the one read at the start.
+                                let agent_id = a.id;
+                                let saved = store::update_board_async(move |board| {
+                                    for (task_id, exec) in outcomes {
+                                        let comment = match exec {
+                                            ExecutionResult::Success { comment, .. } => {
+                                                board.move_task(task_id, TaskStatus::Done);
+                                                comment
                                             }
-                                            ExecutionResult::Failure { comment } => {
-                                                task_mut.status = TaskStatus::ToDo;
-                                                task_mut.comment = Some(comment);
-                                                task_mut.agent_id = None;
+                                            ExecutionResult::Failure { comment, .. } => {
+                                                board.return_task(task_id);
+                                                comment
                                             }
+                                        };
+                                        if let Some(task_mut) = board.task_mut(task_id) {
+                                            task_mut.set_comment(
+                                                comment,
+                                                CommentAuthor::Agent(agent_id),
+                                            );
                                         }
                                     }
+                                })
+                                .await;
+                                if let Err(err) = saved {
+                                    log_activity(
+                                        "board_save_failed",
+                                        a.id,
+                                        format!(
+                                            "Scheduler could not save results of agent {}: {err}",
+                                            a.id
+                                        ),
+                                    );
                                 }
                             }
+                            metrics
+                                .executions_succeeded
+                                .fetch_add(succeeded, Ordering::Relaxed);
+                            metrics
+                                .executions_failed
+                                .fetch_add(failed, Ordering::Relaxed);
+                            log_activity(
+                                "job_completed",
+                                a.id,
+                                format!(
+                                    "Scheduler finished agent {}: {succeeded} succeeded, {failed} failed",
+                                    a.id
+                                ),
+                            );
                         }
-                        let _ = store::save_board(&board);
                     }
                     if !a.repeat {
                         let _ = l.remove(&_id).await;
@@ -95,7 +338,34 @@ pub async fn run() -> anyhow::Result<()> {
 
     sched.start().await?;
 
-    loop {
-        tokio::time::sleep(Duration::from_secs(3600)).await;
+    let deadline = async {
+        match run_for {
+            Some(duration) => tokio::time::sleep(duration).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        () = wait_for_stop(&sentinel, STOP_POLL_INTERVAL) => {}
+        () = deadline => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    sched.shutdown().await?;
+    if let Some(server) = server {
+        server.abort();
+    }
+    if let Ok(path) = config::scheduler_log_path() {
+        let _ = logging::append_to(
+            &path,
+            &LogEvent::new("scheduler_stopped", "Scheduler stopped"),
+        );
+    }
+    Ok(())
+}
+
+/// Appends scheduler activity to `scheduler.log_file`, or the shared
+/// execution log when none is configured.
+fn log_activity(event: &str, agent_id: usize, message: String) {
+    if let Ok(path) = config::scheduler_log_path() {
+        let _ = logging::append_to(&path, &LogEvent::new(event, message).agent(agent_id));
     }
 }
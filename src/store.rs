@@ -1,20 +1,48 @@
 //! Data models for tasks, boards, and OKRs with helpers for persistence.
 
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use crate::config;
+use crate::logging::{self, LogEvent};
 
 /// Progress state of a [`Task`].
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, JsonSchema)]
 pub enum TaskStatus {
+    #[default]
     ToDo,
     InProgress,
     Done,
 }
 
+impl std::str::FromStr for TaskStatus {
+    type Err = String;
+
+    /// Accepts the column names `ToDo`, `InProgress` and `Done`, ignoring
+    /// case, dashes, underscores and spaces (so `in-progress` works too).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key: String = s
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .collect::<String>()
+            .to_lowercase();
+        match key.as_str() {
+            "todo" => Ok(TaskStatus::ToDo),
+            "inprogress" => Ok(TaskStatus::InProgress),
+            "done" => Ok(TaskStatus::Done),
+            _ => Err(format!(
+                "unknown status `{s}` (expected ToDo, InProgress or Done)"
+            )),
+        }
+    }
+}
+
 /// A single task stored in `.taskter/board.json`.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Task {
     pub id: usize,
     pub title: String,
@@ -22,12 +50,55 @@ pub struct Task {
     pub status: TaskStatus,
     pub agent_id: Option<usize>,
     pub comment: Option<String>,
+    /// Who wrote [`Task::comment`]; absent for comments from older boards.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment_author: Option<CommentAuthor>,
+    /// Size of the task in story points.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    /// When the task last moved to [`TaskStatus::Done`]; cleared if it is
+    /// reopened.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl Task {
+    /// Replaces the task comment and records its author.
+    pub fn set_comment(&mut self, comment: String, author: CommentAuthor) {
+        self.comment = Some(comment);
+        self.comment_author = Some(author);
+    }
+}
+
+/// Source of a task comment.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentAuthor {
+    /// Written by a person through the CLI or TUI.
+    User,
+    /// Produced by the agent with the given id.
+    Agent(usize),
+}
+
+impl std::fmt::Display for CommentAuthor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommentAuthor::User => write!(f, "user"),
+            CommentAuthor::Agent(id) => write!(f, "agent {id}"),
+        }
+    }
 }
 
 /// Collection of tasks comprising the Kanban board.
-#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Board {
     pub tasks: Vec<Task>,
+    /// Project name given to `taskter init --name`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// When `taskter init` created the board. Older boards have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
 }
 
 impl Board {
@@ -35,23 +106,98 @@ impl Board {
     pub fn next_task_id(&self) -> usize {
         self.tasks.iter().map(|t| t.id).max().unwrap_or(0) + 1
     }
+
+    /// Returns a mutable reference to the task with the given id.
+    pub fn task_mut(&mut self, id: usize) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|t| t.id == id)
+    }
+
+    /// Moves the task with the given id to `status`, stamping
+    /// [`Task::completed_at`] when it becomes done and clearing it when it
+    /// leaves Done.
+    ///
+    /// Returns `false` and leaves the board untouched when no task has that id.
+    pub fn move_task(&mut self, id: usize, status: TaskStatus) -> bool {
+        match self.task_mut(id) {
+            Some(task) => {
+                if status != TaskStatus::Done {
+                    task.completed_at = None;
+                } else if task.status != TaskStatus::Done {
+                    task.completed_at = Some(Utc::now());
+                }
+                task.status = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sends a task back to "To Do" and clears its agent, as happens after a
+    /// failed or cancelled agent run.
+    ///
+    /// Returns `false` when no task has the given id.
+    pub fn return_task(&mut self, id: usize) -> bool {
+        if !self.move_task(id, TaskStatus::ToDo) {
+            return false;
+        }
+        if let Some(task) = self.task_mut(id) {
+            task.agent_id = None;
+        }
+        true
+    }
+
+    /// Sums task estimates into points completed since `since` and points
+    /// still open. Tasks without an estimate, and done tasks without a
+    /// completion time, are left out.
+    pub fn velocity(&self, since: DateTime<Utc>) -> Velocity {
+        let mut velocity = Velocity::default();
+        for task in &self.tasks {
+            let Some(points) = task.estimate else {
+                continue;
+            };
+            match (&task.status, task.completed_at) {
+                (TaskStatus::Done, Some(at)) if at >= since => velocity.completed += points,
+                (TaskStatus::Done, _) => {}
+                _ => velocity.remaining += points,
+            }
+        }
+        velocity
+    }
+}
+
+/// Story points reported by [`Board::velocity`].
+#[derive(Debug, Default, PartialEq)]
+pub struct Velocity {
+    /// Points of tasks completed within the window.
+    pub completed: u32,
+    /// Points of tasks not yet done.
+    pub remaining: u32,
 }
 
 /// A measurable key result belonging to an [`Okr`].
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct KeyResult {
     pub name: String,
     pub progress: f32,
 }
 
 /// Objective with its associated key results stored in `.taskter/okrs.json`.
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Okr {
     pub objective: String,
     pub key_results: Vec<KeyResult>,
 }
 
-/// Reads the Kanban board from `.taskter/board.json`.
+/// Board path that makes read-only commands take the board from stdin, as in
+/// `cat board.json | taskter --board-file - task list`.
+pub const STDIN_BOARD: &str = "-";
+
+fn is_stdin_board(path: &std::path::Path) -> bool {
+    path == std::path::Path::new(STDIN_BOARD)
+}
+
+/// Reads the Kanban board from `.taskter/board.json`, or from stdin when the
+/// board path is [`STDIN_BOARD`].
 ///
 /// Returns an empty board if the file does not exist.
 ///
@@ -61,6 +207,11 @@ pub struct Okr {
 /// JSON.
 pub fn load_board() -> anyhow::Result<Board> {
     let path = config::board_path()?;
+    if is_stdin_board(&path) {
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut content)?;
+        return Ok(serde_json::from_str(&content)?);
+    }
     if !path.exists() {
         return Ok(Board::default());
     }
@@ -74,15 +225,122 @@ pub fn load_board() -> anyhow::Result<Board> {
 ///
 /// # Errors
 ///
-/// Returns an error if the board cannot be serialized or if the file cannot be
-/// written.
+/// Returns an error if the board cannot be serialized, if the file cannot be
+/// written, or if the board was read from stdin.
 pub fn save_board(board: &Board) -> anyhow::Result<()> {
     let path = config::board_path()?;
+    if is_stdin_board(&path) {
+        anyhow::bail!(
+            "The board was read from stdin and cannot be saved; pass a board file to modify it"
+        );
+    }
     let content = serde_json::to_string_pretty(board)?;
-    fs::write(path, content)?;
+    // Write a sibling file and rename it over the board so readers never see
+    // a half-written file.
+    let mut tmp = path.clone().into_os_string();
+    tmp.push(".tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, &path)?;
     Ok(())
 }
 
+/// How long [`lock_board`] waits for another process to release the board.
+pub const BOARD_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const BOARD_LOCK_RETRY: Duration = Duration::from_millis(50);
+
+/// Advisory lock on the board, held by a process while it reads, changes and
+/// writes `board.json`. It is an OS file lock on `board.lock` next to the
+/// board, so it is released when the guard is dropped or the process dies.
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct BoardLock {
+    _file: Option<fs::File>,
+}
+
+fn board_lock_path(board: &Path) -> PathBuf {
+    board.with_extension("lock")
+}
+
+/// Acquires the board lock, waiting up to [`BOARD_LOCK_TIMEOUT`].
+///
+/// # Errors
+///
+/// Returns an error if another process keeps the lock past the timeout or the
+/// lock file cannot be opened.
+pub fn lock_board() -> anyhow::Result<BoardLock> {
+    lock_board_within(BOARD_LOCK_TIMEOUT)
+}
+
+/// Acquires the board lock, retrying until `timeout` has passed.
+///
+/// # Errors
+///
+/// Returns an error if another process keeps the lock past `timeout` or the
+/// lock file cannot be opened.
+pub fn lock_board_within(timeout: Duration) -> anyhow::Result<BoardLock> {
+    let board = config::board_path()?;
+    if is_stdin_board(&board) {
+        // Nothing on disk to protect; saving such a board fails anyway.
+        return Ok(BoardLock { _file: None });
+    }
+    let path = board_lock_path(&board);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)?;
+    let deadline = Instant::now().checked_add(timeout);
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(BoardLock { _file: Some(file) }),
+            Err(fs::TryLockError::WouldBlock) => {
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    anyhow::bail!("board is locked by another process");
+                }
+                std::thread::sleep(BOARD_LOCK_RETRY);
+            }
+            Err(fs::TryLockError::Error(err)) => return Err(err.into()),
+        }
+    }
+}
+
+/// Loads the board, applies `change` and saves the result while holding the
+/// board lock, so concurrent writers cannot drop each other's updates. The
+/// board is only written when `change` modified it.
+///
+/// # Errors
+///
+/// Returns an error if the lock cannot be acquired or the board cannot be
+/// read or written.
+pub fn update_board<T>(change: impl FnOnce(&mut Board) -> T) -> anyhow::Result<T> {
+    let _lock = lock_board()?;
+    let mut board = load_board()?;
+    let original = board.clone();
+    let result = change(&mut board);
+    if board != original {
+        save_board(&board)?;
+    }
+    Ok(result)
+}
+
+/// Like [`update_board`], for async callers: waiting for the lock happens on
+/// the blocking thread pool instead of stalling the runtime.
+///
+/// # Errors
+///
+/// Returns the errors of [`update_board`], or an error if the blocking task
+/// panicked.
+pub async fn update_board_async<T, F>(change: F) -> anyhow::Result<T>
+where
+    F: FnOnce(&mut Board) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(move || update_board(change)).await?
+}
+
 /// Loads all OKRs from `.taskter/okrs.json`.
 ///
 /// Returns an empty list if the file is missing.
@@ -101,15 +359,354 @@ pub fn load_okrs() -> anyhow::Result<Vec<Okr>> {
     Ok(okrs)
 }
 
+/// Clamps a key result's progress into the `0.0..=1.0` range, recording an
+/// `okr_progress_clamped` entry in `.taskter/logs.log` when the value had to
+/// be adjusted.
+fn clamp_progress(kr: &mut KeyResult) {
+    let clamped = if kr.progress.is_nan() {
+        0.0
+    } else {
+        kr.progress.clamp(0.0, 1.0)
+    };
+    if clamped != kr.progress {
+        let _ = logging::append(&LogEvent::new(
+            "okr_progress_clamped",
+            format!(
+                "Progress {} for key result `{}` is outside 0.0-1.0; saved as {clamped}.",
+                kr.progress, kr.name
+            ),
+        ));
+        kr.progress = clamped;
+    }
+}
+
 /// Persists OKRs to `.taskter/okrs.json`.
 ///
+/// Key result progress is clamped to `0.0..=1.0` before writing.
+///
 /// # Errors
 ///
 /// Returns an error if the OKRs cannot be serialized or if the file cannot be
 /// written.
 pub fn save_okrs(okrs: &[Okr]) -> anyhow::Result<()> {
     let path = config::okrs_path()?;
-    let content = serde_json::to_string_pretty(okrs)?;
+    let mut okrs = okrs.to_vec();
+    okrs.iter_mut()
+        .flat_map(|okr| okr.key_results.iter_mut())
+        .for_each(clamp_progress);
+    let content = serde_json::to_string_pretty(&okrs)?;
     fs::write(path, content)?;
     Ok(())
 }
+
+/// Writes the current OKRs to a timestamped file in the OKR history directory
+/// and returns the snapshot name.
+///
+/// # Errors
+///
+/// Returns an error if the OKRs cannot be loaded or the snapshot cannot be
+/// written.
+pub fn snapshot_okrs() -> anyhow::Result<String> {
+    let dir = config::okr_history_dir()?;
+    fs::create_dir_all(&dir)?;
+    let stamp = chrono::Local::now().format("%Y%m%dT%H%M%S").to_string();
+    let mut name = stamp.clone();
+    let mut suffix = 1;
+    while dir.join(format!("{name}.json")).exists() {
+        suffix += 1;
+        name = format!("{stamp}-{suffix}");
+    }
+    let content = serde_json::to_string_pretty(&load_okrs()?)?;
+    fs::write(dir.join(format!("{name}.json")), content)?;
+    Ok(name)
+}
+
+/// Lists snapshot names in the OKR history directory, oldest first.
+///
+/// # Errors
+///
+/// Returns an error if the history directory cannot be read.
+pub fn list_okr_snapshots() -> anyhow::Result<Vec<String>> {
+    let dir = config::okr_history_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                names.push(stem.to_string());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Loads the OKRs stored in the named snapshot.
+///
+/// # Errors
+///
+/// Returns an error if the snapshot does not exist or cannot be parsed.
+pub fn load_okr_snapshot(name: &str) -> anyhow::Result<Vec<Okr>> {
+    let path: PathBuf = config::okr_history_dir()?.join(format!("{name}.json"));
+    let content = fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("Cannot read OKR snapshot `{name}`: {e}"))?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Progress change of a single key result between two OKR sets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressDelta {
+    pub objective: String,
+    pub key_result: String,
+    /// Progress in the older set, or `None` if the key result is new.
+    pub before: Option<f32>,
+    /// Progress in the newer set, or `None` if the key result was removed.
+    pub after: Option<f32>,
+}
+
+/// Direction of a [`ProgressDelta`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Up,
+    Down,
+    Unchanged,
+}
+
+impl ProgressDelta {
+    /// Change in progress, treating a missing side as `0.0`.
+    pub fn change(&self) -> f32 {
+        self.after.unwrap_or(0.0) - self.before.unwrap_or(0.0)
+    }
+
+    /// Whether progress went up, down or stayed the same.
+    pub fn trend(&self) -> Trend {
+        let change = self.change();
+        if change > 0.0 {
+            Trend::Up
+        } else if change < 0.0 {
+            Trend::Down
+        } else {
+            Trend::Unchanged
+        }
+    }
+}
+
+/// Compares key results by objective and name, reporting every key result
+/// whose progress differs or that exists on only one side.
+pub fn diff_okrs(before: &[Okr], after: &[Okr]) -> Vec<ProgressDelta> {
+    compare_okrs(before, after)
+        .into_iter()
+        .filter(|delta| delta.before != delta.after)
+        .collect()
+}
+
+/// Like [`diff_okrs`] but also reports key results whose progress did not
+/// change, so every key result on either side appears exactly once.
+pub fn compare_okrs(before: &[Okr], after: &[Okr]) -> Vec<ProgressDelta> {
+    let flatten = |okrs: &[Okr]| -> Vec<(String, String, f32)> {
+        okrs.iter()
+            .flat_map(|okr| {
+                okr.key_results
+                    .iter()
+                    .map(|kr| (okr.objective.clone(), kr.name.clone(), kr.progress))
+            })
+            .collect()
+    };
+    let old = flatten(before);
+    let new = flatten(after);
+    let find = |list: &[(String, String, f32)], objective: &str, name: &str| {
+        list.iter()
+            .find(|(o, n, _)| o == objective && n == name)
+            .map(|(_, _, p)| *p)
+    };
+
+    let mut deltas = Vec::new();
+    for (objective, name, progress) in &new {
+        deltas.push(ProgressDelta {
+            objective: objective.clone(),
+            key_result: name.clone(),
+            before: find(&old, objective, name),
+            after: Some(*progress),
+        });
+    }
+    for (objective, name, progress) in &old {
+        if find(&new, objective, name).is_none() {
+            deltas.push(ProgressDelta {
+                objective: objective.clone(),
+                key_result: name.clone(),
+                before: Some(*progress),
+                after: None,
+            });
+        }
+    }
+    deltas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board_with_task() -> Board {
+        Board {
+            tasks: vec![Task {
+                id: 1,
+                title: "Ship".into(),
+                description: None,
+                status: TaskStatus::ToDo,
+                agent_id: Some(2),
+                comment: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn board_without_metadata_loads_with_none() {
+        let board: Board = serde_json::from_str(r#"{ "tasks": [] }"#).unwrap();
+        assert_eq!(board.name, None);
+        assert_eq!(board.created_at, None);
+        assert!(!serde_json::to_string(&board)
+            .unwrap()
+            .contains("created_at"));
+    }
+
+    #[test]
+    fn move_task_sets_each_status() {
+        let mut board = board_with_task();
+        for status in [TaskStatus::InProgress, TaskStatus::Done, TaskStatus::ToDo] {
+            assert!(board.move_task(1, status.clone()));
+            assert_eq!(board.tasks[0].status, status);
+            assert_eq!(board.tasks[0].agent_id, Some(2));
+        }
+    }
+
+    #[test]
+    fn move_task_with_unknown_id_is_a_noop() {
+        let mut board = board_with_task();
+        assert!(!board.move_task(7, TaskStatus::Done));
+        assert!(!board.return_task(7));
+        assert_eq!(board, board_with_task());
+    }
+
+    #[test]
+    fn return_task_resets_status_and_agent() {
+        let mut board = board_with_task();
+        board.move_task(1, TaskStatus::InProgress);
+        assert!(board.return_task(1));
+        assert_eq!(board.tasks[0].status, TaskStatus::ToDo);
+        assert_eq!(board.tasks[0].agent_id, None);
+    }
+
+    #[test]
+    fn move_task_tracks_completion_time() {
+        let mut board = board_with_task();
+        board.move_task(1, TaskStatus::Done);
+        let completed_at = board.tasks[0].completed_at;
+        assert!(completed_at.is_some());
+        board.move_task(1, TaskStatus::Done);
+        assert_eq!(board.tasks[0].completed_at, completed_at);
+        board.move_task(1, TaskStatus::InProgress);
+        assert_eq!(board.tasks[0].completed_at, None);
+    }
+
+    #[test]
+    fn velocity_sums_estimates_by_status_and_window() {
+        let now = Utc::now();
+        let task = |id, status, estimate, days_ago: Option<i64>| Task {
+            id,
+            title: format!("Task {id}"),
+            description: None,
+            status,
+            agent_id: None,
+            comment: None,
+            estimate,
+            completed_at: days_ago.map(|d| now - chrono::Duration::days(d)),
+            ..Default::default()
+        };
+        let board = Board {
+            tasks: vec![
+                task(1, TaskStatus::Done, Some(3), Some(1)),
+                task(2, TaskStatus::Done, Some(5), Some(6)),
+                task(3, TaskStatus::Done, Some(8), Some(30)),
+                task(4, TaskStatus::Done, Some(2), None),
+                task(5, TaskStatus::Done, None, Some(1)),
+                task(6, TaskStatus::InProgress, Some(13), None),
+                task(7, TaskStatus::ToDo, Some(1), None),
+                task(8, TaskStatus::ToDo, None, None),
+            ],
+            ..Default::default()
+        };
+
+        let week = board.velocity(now - chrono::Duration::days(7));
+        assert_eq!(
+            week,
+            Velocity {
+                completed: 8,
+                remaining: 14
+            }
+        );
+        let all_time = board.velocity(DateTime::<Utc>::MIN_UTC);
+        assert_eq!(all_time.completed, 16);
+        assert_eq!(all_time.remaining, 14);
+    }
+
+    fn okr_with(progress: &[(&str, f32)]) -> Vec<Okr> {
+        vec![Okr {
+            objective: "Grow".into(),
+            key_results: progress
+                .iter()
+                .map(|(name, progress)| KeyResult {
+                    name: (*name).into(),
+                    progress: *progress,
+                })
+                .collect(),
+        }]
+    }
+
+    #[test]
+    fn compare_okrs_reports_increases_decreases_and_unchanged() {
+        let before = okr_with(&[("Users", 0.2), ("Churn", 0.6), ("Docs", 0.5)]);
+        let after = okr_with(&[("Users", 0.7), ("Churn", 0.4), ("Docs", 0.5)]);
+
+        let deltas = compare_okrs(&before, &after);
+        let trends: Vec<(&str, Trend)> = deltas
+            .iter()
+            .map(|d| (d.key_result.as_str(), d.trend()))
+            .collect();
+        assert_eq!(
+            trends,
+            vec![
+                ("Users", Trend::Up),
+                ("Churn", Trend::Down),
+                ("Docs", Trend::Unchanged),
+            ]
+        );
+        assert!((deltas[0].change() - 0.5).abs() < 1e-6);
+        assert!((deltas[1].change() + 0.2).abs() < 1e-6);
+
+        let changed: Vec<String> = diff_okrs(&before, &after)
+            .into_iter()
+            .map(|d| d.key_result)
+            .collect();
+        assert_eq!(changed, vec!["Users", "Churn"]);
+    }
+
+    #[test]
+    fn compare_okrs_treats_missing_key_results_as_zero() {
+        let before = okr_with(&[("Old", 0.3)]);
+        let after = okr_with(&[("New", 0.1)]);
+
+        let deltas = compare_okrs(&before, &after);
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].key_result, "New");
+        assert_eq!(deltas[0].before, None);
+        assert_eq!(deltas[0].trend(), Trend::Up);
+        assert_eq!(deltas[1].key_result, "Old");
+        assert_eq!(deltas[1].after, None);
+        assert_eq!(deltas[1].trend(), Trend::Down);
+    }
+}
@@ -1,12 +1,13 @@
 use anyhow::{anyhow, Result};
 use lettre::{transport::smtp::authentication::Credentials, Message, SmtpTransport, Transport};
+use once_cell::sync::Lazy;
 use serde::Deserialize;
 use serde_json::Value;
 use std::fs;
 
 use crate::agent::FunctionDeclaration;
 use crate::config;
-use crate::tools::Tool;
+use crate::tools::{invalid_arguments, Tool, ToolOutput};
 use std::collections::HashMap;
 
 #[derive(Deserialize)]
@@ -19,9 +20,12 @@ struct EmailConfig {
 
 const DECL_JSON: &str = include_str!("../../tools/send_email.json");
 
+static DECLARATION: Lazy<FunctionDeclaration> =
+    Lazy::new(|| serde_json::from_str(DECL_JSON).expect("invalid send_email.json"));
+
 /// Returns the function declaration for this tool.
 pub fn declaration() -> FunctionDeclaration {
-    serde_json::from_str(DECL_JSON).expect("invalid send_email.json")
+    DECLARATION.clone()
 }
 
 /// Sends an email using `.taskter/email_config.json` for credentials.
@@ -29,16 +33,18 @@ pub fn declaration() -> FunctionDeclaration {
 /// # Errors
 ///
 /// Returns an error if reading the configuration or sending the email fails.
-pub fn execute(args: &Value) -> Result<String> {
-    let to = args["to"].as_str().ok_or_else(|| anyhow!("to missing"))?;
+pub fn execute(args: &Value) -> Result<ToolOutput> {
+    let to = args["to"]
+        .as_str()
+        .ok_or_else(|| invalid_arguments("to missing"))?;
     let subject = args["subject"]
         .as_str()
-        .ok_or_else(|| anyhow!("subject missing"))?;
+        .ok_or_else(|| invalid_arguments("subject missing"))?;
     let body = args["body"]
         .as_str()
-        .ok_or_else(|| anyhow!("body missing"))?;
+        .ok_or_else(|| invalid_arguments("body missing"))?;
     send_email(to, subject, body)
-        .map(|_| format!("Email sent to {to} with subject '{subject}' and body '{body}'"))
+        .map(|_| format!("Email sent to {to} with subject '{subject}' and body '{body}'").into())
         .map_err(|e| anyhow!("Failed to send email: {e}"))
 }
 
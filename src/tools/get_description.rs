@@ -1,17 +1,21 @@
 use anyhow::Result;
+use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::fs;
 
 use crate::agent::FunctionDeclaration;
 use crate::config;
-use crate::tools::Tool;
+use crate::tools::{Tool, ToolOutput};
 use std::collections::HashMap;
 
 const DECL_JSON: &str = include_str!("../../tools/get_description.json");
 
+static DECLARATION: Lazy<FunctionDeclaration> =
+    Lazy::new(|| serde_json::from_str(DECL_JSON).expect("invalid get_description.json"));
+
 /// Returns the function declaration for this tool.
 pub fn declaration() -> FunctionDeclaration {
-    serde_json::from_str(DECL_JSON).expect("invalid get_description.json")
+    DECLARATION.clone()
 }
 
 /// Reads `.taskter/description.md` and returns its contents.
@@ -19,9 +23,9 @@ pub fn declaration() -> FunctionDeclaration {
 /// # Errors
 ///
 /// Returns an error if the description file cannot be read.
-pub fn execute(_args: &Value) -> Result<String> {
+pub fn execute(_args: &Value) -> Result<ToolOutput> {
     let content = fs::read_to_string(config::description_path()?)?;
-    Ok(content)
+    Ok(content.into())
 }
 
 /// Registers the tool in the provided map.
@@ -0,0 +1,96 @@
+//! Per-tool invocation counters persisted in `tool_metrics.json`.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config;
+
+/// Aggregated statistics for a single tool.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct ToolStats {
+    pub calls: u64,
+    pub failures: u64,
+    /// Cumulative execution time in milliseconds.
+    pub total_ms: u64,
+}
+
+impl ToolStats {
+    /// Mean execution time per call in milliseconds.
+    #[must_use]
+    pub fn average_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.calls).unwrap_or(0)
+    }
+}
+
+/// Loads the recorded metrics keyed by tool name.
+///
+/// # Errors
+///
+/// Returns an error if the metrics file exists but cannot be read or parsed.
+pub fn load() -> Result<BTreeMap<String, ToolStats>> {
+    let path = config::tool_metrics_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = fs::read_to_string(path)?;
+    if content.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Adds one invocation of `name` to the metrics file.
+///
+/// Nothing is recorded when the data directory does not exist, so tools used
+/// outside an initialized project leave no files behind. The file is locked
+/// while it is updated so concurrent agents do not lose counts.
+///
+/// # Errors
+///
+/// Returns an error if the metrics file cannot be locked, read, parsed or
+/// written. A corrupt file is left untouched.
+pub(crate) fn record(name: &str, success: bool, elapsed: Duration) -> Result<()> {
+    let path = config::tool_metrics_path()?;
+    let parent_exists = path
+        .parent()
+        .is_none_or(|dir| dir.as_os_str().is_empty() || dir.is_dir());
+    if !parent_exists {
+        return Ok(());
+    }
+    update(&path, |metrics| {
+        let stats = metrics.entry(name.to_string()).or_default();
+        stats.calls += 1;
+        if !success {
+            stats.failures += 1;
+        }
+        let elapsed_ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        stats.total_ms = stats.total_ms.saturating_add(elapsed_ms);
+    })
+}
+
+fn update(path: &Path, apply: impl FnOnce(&mut BTreeMap<String, ToolStats>)) -> Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    file.lock()?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    let mut metrics: BTreeMap<String, ToolStats> = if content.trim().is_empty() {
+        BTreeMap::new()
+    } else {
+        serde_json::from_str(&content)?
+    };
+    apply(&mut metrics);
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(serde_json::to_string_pretty(&metrics)?.as_bytes())?;
+    Ok(())
+}
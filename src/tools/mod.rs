@@ -4,11 +4,18 @@ use anyhow::Result;
 use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
 
 use crate::agent::FunctionDeclaration;
 
 pub mod email;
 pub mod get_description;
+pub mod metrics;
+mod process;
 pub mod project_files;
 pub mod run_bash;
 pub mod run_python;
@@ -17,53 +24,551 @@ pub mod taskter_okrs;
 pub mod taskter_task;
 pub mod taskter_tools;
 pub mod web_search;
+pub mod workdir;
 
 /// Runtime representation of a callable tool.
 pub struct Tool {
     pub declaration: FunctionDeclaration,
-    pub execute: fn(&Value) -> Result<String>,
+    pub execute: fn(&Value) -> Result<ToolOutput>,
 }
 
+type Registry = HashMap<&'static str, Tool>;
+
+/// Result of a tool call as handed back to the model.
+///
+/// Providers map each variant onto their own content format: `Json` is sent
+/// as a structured object where the API accepts one and as serialized text
+/// elsewhere.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolOutput {
+    Text(String),
+    Json(Value),
+}
+
+impl ToolOutput {
+    /// Wraps command output, keeping a JSON object or array structured so
+    /// providers can pass it on as such.
+    pub fn from_stdout(stdout: String) -> Self {
+        match serde_json::from_str(&stdout) {
+            Ok(value @ (Value::Object(_) | Value::Array(_))) => ToolOutput::Json(value),
+            _ => ToolOutput::Text(stdout),
+        }
+    }
+}
+
+impl fmt::Display for ToolOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolOutput::Text(text) => f.write_str(text),
+            ToolOutput::Json(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Error for tool arguments that cannot succeed as given, such as a missing
+/// field or a path outside the agent workdir. Agent runs that fail with it
+/// are not retried.
+#[derive(Debug)]
+pub struct InvalidArguments(pub String);
+
+impl fmt::Display for InvalidArguments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidArguments {}
+
+/// Builds an [`InvalidArguments`] error.
+pub(crate) fn invalid_arguments(message: impl Into<String>) -> anyhow::Error {
+    InvalidArguments(message.into()).into()
+}
+
+impl From<String> for ToolOutput {
+    fn from(text: String) -> Self {
+        ToolOutput::Text(text)
+    }
+}
+
+impl From<&str> for ToolOutput {
+    fn from(text: &str) -> Self {
+        ToolOutput::Text(text.to_string())
+    }
+}
+
+impl From<Value> for ToolOutput {
+    fn from(value: Value) -> Self {
+        ToolOutput::Json(value)
+    }
+}
+
+/// Registration functions of every bundled tool module.
+const REGISTRATIONS: &[fn(&mut Registry)] = &[
+    email::register,
+    get_description::register,
+    run_bash::register,
+    run_python::register,
+    project_files::register,
+    web_search::register,
+    taskter_task::register,
+    taskter_agent::register,
+    taskter_okrs::register,
+    taskter_tools::register,
+];
+
 /// Registry of all tools bundled with Taskter.
-pub static BUILTIN_TOOLS: Lazy<HashMap<&'static str, Tool>> = Lazy::new(|| {
-    let mut m = HashMap::new();
-    email::register(&mut m);
-    get_description::register(&mut m);
-    run_bash::register(&mut m);
-    run_python::register(&mut m);
-    project_files::register(&mut m);
-    web_search::register(&mut m);
-    taskter_task::register(&mut m);
-    taskter_agent::register(&mut m);
-    taskter_okrs::register(&mut m);
-    taskter_tools::register(&mut m);
-    m
+pub static BUILTIN_TOOLS: Lazy<Registry> = Lazy::new(|| {
+    let (registry, duplicates) = build_registry(REGISTRATIONS);
+    for name in duplicates {
+        eprintln!("Warning: built-in tool `{name}` is registered more than once; the last registration wins.");
+    }
+    registry
 });
 
-/// Returns the names of all built-in tools.
+/// Runs each `register` function into one registry and returns it together
+/// with the names that were registered more than once. A later registration
+/// replaces an earlier one.
+fn build_registry(registrations: &[fn(&mut Registry)]) -> (Registry, Vec<&'static str>) {
+    let mut registry = HashMap::new();
+    let mut duplicates = Vec::new();
+    for register in registrations {
+        let mut added = HashMap::new();
+        register(&mut added);
+        for (name, tool) in added {
+            if registry.insert(name, tool).is_some() {
+                duplicates.push(name);
+            }
+        }
+    }
+    (registry, duplicates)
+}
+
+/// Returns an HTTP client builder configured with the `tools.http_proxy` /
+/// `tools.env_proxy` settings. Tools that make network requests should start
+/// from this builder.
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be loaded or the proxy URL is
+/// invalid.
+pub fn http_client_builder() -> Result<reqwest::ClientBuilder> {
+    let settings = crate::config::tool_http()?;
+    let builder = reqwest::Client::builder();
+    Ok(match settings.proxy {
+        Some(url) => builder.proxy(reqwest::Proxy::all(url.as_str())?),
+        None if settings.env_proxy => builder,
+        None => builder.no_proxy(),
+    })
+}
+
+/// Delay before the first retry of a failed subprocess; doubled for each
+/// further attempt.
+const SUBPROCESS_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A subcommand accepted by the structured form of the `taskter_*` tools.
+/// Each field maps to the `--kebab-case` flag of the same name.
+pub(crate) struct StructuredAction {
+    pub name: &'static str,
+    pub required: &'static [&'static str],
+    pub optional: &'static [&'static str],
+}
+
+/// Converts `taskter_*` tool arguments into command-line arguments.
+///
+/// The positional form `{"args": ["add", "--title", "Demo"]}` is passed
+/// through. Otherwise `action` selects one of `actions` and every other field
+/// becomes a flag: strings and numbers are passed as its value, arrays as
+/// several values, `true` as a bare switch. `false`, `null`, empty strings and
+/// empty arrays are omitted, since strict-mode providers fill every field.
+///
+/// # Errors
+///
+/// Returns an error if neither form is present, the action is unknown, a
+/// required field is missing, a field is not accepted by the action, or a
+/// value has an unsupported type.
+pub(crate) fn subcommand_args(args: &Value, actions: &[StructuredAction]) -> Result<Vec<String>> {
+    if let Some(list) = args
+        .get("args")
+        .filter(|v| !v.is_null() && v.as_array().is_none_or(|a| !a.is_empty()))
+    {
+        let list = list
+            .as_array()
+            .ok_or_else(|| invalid_arguments("args must be an array"))?;
+        return list
+            .iter()
+            .map(|a| {
+                a.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| invalid_arguments("args must be strings"))
+            })
+            .collect();
+    }
+    let Some(name) = args.get("action").and_then(Value::as_str) else {
+        return Err(invalid_arguments(
+            "args missing: pass either `args` or `action` with named fields",
+        ));
+    };
+    let Some(action) = actions.iter().find(|a| a.name == name) else {
+        let known: Vec<_> = actions.iter().map(|a| a.name).collect();
+        return Err(invalid_arguments(format!(
+            "Unknown action `{name}`; expected one of: {}",
+            known.join(", ")
+        )));
+    };
+    let fields = args
+        .as_object()
+        .ok_or_else(|| invalid_arguments("arguments must be a JSON object"))?;
+    let is_empty = |value: &Value| match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    };
+    for field in action.required {
+        if fields.get(*field).is_none_or(is_empty) {
+            return Err(invalid_arguments(format!(
+                "Missing required field `{field}` for action `{name}`"
+            )));
+        }
+    }
+    let mut cli = vec![name.to_string()];
+    for (key, value) in fields {
+        if key == "action" || key == "args" || is_empty(value) {
+            continue;
+        }
+        if !action.required.contains(&key.as_str()) && !action.optional.contains(&key.as_str()) {
+            return Err(invalid_arguments(format!(
+                "Field `{key}` is not accepted by action `{name}`"
+            )));
+        }
+        let flag = format!("--{}", key.replace('_', "-"));
+        match value {
+            Value::Bool(_) => cli.push(flag),
+            Value::Array(items) => {
+                cli.push(flag);
+                for item in items {
+                    cli.push(scalar_arg(key, item)?);
+                }
+            }
+            other => {
+                cli.push(flag);
+                cli.push(scalar_arg(key, other)?);
+            }
+        }
+    }
+    Ok(cli)
+}
+
+fn scalar_arg(key: &str, value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        _ => Err(invalid_arguments(format!(
+            "Field `{key}` must be a string, number or array of them"
+        ))),
+    }
+}
+
+/// Runs `cmd` and returns its trimmed stdout, retrying transient failures.
+///
+/// Spawn and I/O errors and runs killed by a signal are retried up to
+/// `tools.subprocess_retries` times with exponential backoff. A missing binary
+/// and any non-zero exit fail immediately, since the command reported its own
+/// error and would report it again.
+///
+/// # Errors
+///
+/// Returns an error if the binary cannot be found, the command exits with a
+/// non-zero status, or it still fails after the last retry.
+pub(crate) fn run_subprocess(cmd: &mut Command) -> Result<String> {
+    let retries = crate::config::subprocess_retries().unwrap_or(0);
+    let mut attempt = 0;
+    loop {
+        let error = match cmd.output() {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+            }
+            Ok(output) => {
+                let error = anyhow::anyhow!(
+                    "Command failed: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                if output.status.code().is_some() {
+                    return Err(error);
+                }
+                error
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Err(anyhow::anyhow!(
+                    "Command not found: {}",
+                    cmd.get_program().to_string_lossy()
+                ));
+            }
+            Err(err) => err.into(),
+        };
+        if attempt >= retries {
+            return Err(error);
+        }
+        std::thread::sleep(SUBPROCESS_BACKOFF * 2u32.saturating_pow(attempt));
+        attempt += 1;
+    }
+}
+
+/// Returns `true` unless the tool is listed in `tools.disabled`.
+///
+/// # Errors
+///
+/// Returns an error if the configuration cannot be loaded.
+fn is_enabled(name: &str) -> Result<bool> {
+    crate::config::disabled_tools().map(|disabled| !disabled.iter().any(|d| d == name))
+}
+
+/// `project_files` actions that never modify the project.
+const READ_ONLY_FILE_ACTIONS: &[&str] = &["read", "search"];
+
+/// Returns `true` if `tools.require_confirmation` lists the tool. Read-only
+/// `project_files` actions are exempt, and `file_ops` counts as
+/// `project_files`.
+pub fn requires_confirmation(name: &str, args: &Value) -> bool {
+    let name = if name == "file_ops" {
+        "project_files"
+    } else {
+        name
+    };
+    if name == "project_files"
+        && args["action"]
+            .as_str()
+            .is_some_and(|action| READ_ONLY_FILE_ACTIONS.contains(&action))
+    {
+        return false;
+    }
+    crate::config::confirm_tools()
+        .map(|names| names.iter().any(|n| n == name))
+        .unwrap_or(false)
+}
+
+/// Returns the names of all enabled built-in tools. A tool whose state cannot
+/// be read from the configuration counts as disabled.
 #[must_use = "check the list to know which tools are available"]
 pub fn builtin_names() -> Vec<&'static str> {
-    let mut names: Vec<&'static str> = BUILTIN_TOOLS.keys().copied().collect();
+    let mut names: Vec<&'static str> = BUILTIN_TOOLS
+        .keys()
+        .copied()
+        .filter(|name| is_enabled(name).unwrap_or(false))
+        .collect();
     names.sort();
     names
 }
 
-/// Retrieves the declaration for a built-in tool by name.
+/// Retrieves the declaration for an enabled built-in tool by name, treating
+/// a tool whose state cannot be read from the configuration as disabled.
 pub fn builtin_declaration(name: &str) -> Option<FunctionDeclaration> {
+    if !is_enabled(name).unwrap_or(false) {
+        return None;
+    }
     BUILTIN_TOOLS.get(name).map(|t| t.declaration.clone())
 }
 
 /// Executes a named built-in tool.
 ///
-/// Individual tools may read or write files in `.taskter/`.
+/// Individual tools may read or write files in `.taskter/`. Each call is
+/// counted in the tool metrics file (see [`metrics`]).
+///
+/// # Errors
+///
+/// Returns an error if the tool name is unknown or disabled, if the
+/// configuration cannot be loaded, or if the tool execution fails.
+pub fn execute_tool(name: &str, args: &Value) -> Result<ToolOutput> {
+    execute_tool_in(name, args, None)
+}
+
+/// Executes a named built-in tool confined to `workdir`, a directory relative
+/// to the project. File paths are resolved inside it and shell tools run
+/// there (see [`workdir`]).
 ///
 /// # Errors
 ///
-/// Returns an error if the tool name is unknown or if the tool execution fails.
-pub fn execute_tool(name: &str, args: &Value) -> Result<String> {
+/// Returns the same errors as [`execute_tool`], and an error if a path escapes
+/// `workdir`.
+pub fn execute_tool_in(name: &str, args: &Value, workdir: Option<&Path>) -> Result<ToolOutput> {
+    if !is_enabled(name)? {
+        return Err(anyhow::anyhow!("Tool {name} is disabled by configuration"));
+    }
     if let Some(tool) = BUILTIN_TOOLS.get(name) {
-        (tool.execute)(args)
+        let started = Instant::now();
+        let result = workdir::scoped(workdir, || (tool.execute)(args));
+        let _ = metrics::record(name, result.is_ok(), started.elapsed());
+        result
     } else {
-        Err(anyhow::anyhow!("Unknown tool: {name}"))
+        Err(anyhow::anyhow!("{}", unknown_tool_message(name)))
+    }
+}
+
+/// Returns the enabled built-in tool whose name is closest to `name`, if any
+/// is within a small edit distance.
+pub fn suggest(name: &str) -> Option<&'static str> {
+    let max_distance = (name.chars().count() / 3).max(2);
+    builtin_names()
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Expands `all` or a glob such as `taskter_*` to the enabled built-in tool
+/// names it matches, sorted. `*` matches any run of characters and `?` a
+/// single one. Returns `None` when `pattern` is neither, i.e. a plain name.
+pub fn matching_builtins(pattern: &str) -> Option<Vec<&'static str>> {
+    if pattern == "all" {
+        return Some(builtin_names());
+    }
+    if !pattern.contains(['*', '?']) {
+        return None;
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    Some(
+        builtin_names()
+            .into_iter()
+            .filter(|name| glob_match(&pattern, &name.chars().collect::<Vec<_>>()))
+            .collect(),
+    )
+}
+
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| glob_match(rest, &name[skip..])),
+        Some((&p, rest)) => name
+            .split_first()
+            .is_some_and(|(&c, tail)| (p == '?' || p == c) && glob_match(rest, tail)),
+    }
+}
+
+/// Formats an "Unknown tool" error, adding a suggestion when one is close.
+pub fn unknown_tool_message(name: &str) -> String {
+    match suggest(name) {
+        Some(candidate) => format!("Unknown tool: {name} (did you mean `{candidate}`?)"),
+        None => format!("Unknown tool: {name}"),
+    }
+}
+
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn echo(_: &Value) -> Result<ToolOutput> {
+        Ok("first".into())
+    }
+
+    fn echo_again(_: &Value) -> Result<ToolOutput> {
+        Ok("second".into())
+    }
+
+    fn register_first(map: &mut Registry) {
+        map.insert(
+            "echo",
+            Tool {
+                declaration: run_bash::declaration(),
+                execute: echo,
+            },
+        );
+    }
+
+    fn register_second(map: &mut Registry) {
+        map.insert(
+            "echo",
+            Tool {
+                declaration: run_bash::declaration(),
+                execute: echo_again,
+            },
+        );
+    }
+
+    #[test]
+    fn duplicate_registration_is_reported_and_last_wins() {
+        let (registry, duplicates) = build_registry(&[register_first, register_second]);
+        assert_eq!(duplicates, vec!["echo"]);
+        let result = (registry["echo"].execute)(&Value::Null).unwrap();
+        assert_eq!(result, ToolOutput::from("second"));
+    }
+
+    #[test]
+    fn from_stdout_keeps_json_objects_and_arrays_structured() {
+        assert_eq!(
+            ToolOutput::from_stdout(r#"[{"id":1}]"#.into()),
+            ToolOutput::Json(serde_json::json!([{"id": 1}]))
+        );
+        assert_eq!(
+            ToolOutput::from_stdout("42".into()),
+            ToolOutput::Text("42".into())
+        );
+        assert_eq!(
+            ToolOutput::from_stdout("Task added.".into()),
+            ToolOutput::Text("Task added.".into())
+        );
+    }
+
+    #[test]
+    fn builtin_registry_has_no_duplicates() {
+        let (_, duplicates) = build_registry(REGISTRATIONS);
+        assert!(duplicates.is_empty(), "{duplicates:?}");
+    }
+
+    const ADD_ONLY: &[StructuredAction] = &[StructuredAction {
+        name: "add",
+        required: &["title"],
+        optional: &["description", "estimate"],
+    }];
+
+    #[test]
+    fn subcommand_args_maps_named_fields_to_flags() {
+        let args = serde_json::json!({
+            "action": "add",
+            "title": "Ship it",
+            "description": "",
+            "estimate": 3,
+        });
+        assert_eq!(
+            subcommand_args(&args, ADD_ONLY).unwrap(),
+            vec!["add", "--estimate", "3", "--title", "Ship it"]
+        );
+        let positional = serde_json::json!({"args": ["list"]});
+        assert_eq!(
+            subcommand_args(&positional, ADD_ONLY).unwrap(),
+            vec!["list"]
+        );
+    }
+
+    #[test]
+    fn subcommand_args_validates_structured_fields() {
+        let error = |args: Value| subcommand_args(&args, ADD_ONLY).unwrap_err().to_string();
+        assert_eq!(
+            error(serde_json::json!({"action": "add"})),
+            "Missing required field `title` for action `add`"
+        );
+        assert_eq!(
+            error(serde_json::json!({"action": "add", "title": "x", "agent_id": 1})),
+            "Field `agent_id` is not accepted by action `add`"
+        );
+        assert_eq!(
+            error(serde_json::json!({"action": "delete"})),
+            "Unknown action `delete`; expected one of: add"
+        );
+        assert!(error(serde_json::json!({})).starts_with("args missing"));
     }
 }
@@ -0,0 +1,124 @@
+//! Subprocess runner shared by the command tools.
+//!
+//! Commands run in their own process group so a timeout can kill everything
+//! they started, not just the direct child.
+
+use std::io::{self, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Exit status and captured output of a finished command.
+pub(crate) struct Finished {
+    pub status: ExitStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    /// Whether the command was killed because the timeout elapsed.
+    pub timed_out: bool,
+}
+
+/// Runs `cmd` with its output captured and stdin closed.
+///
+/// When `timeout` elapses the whole process group is killed. A timeout too
+/// large to express as a deadline is treated as no timeout.
+pub(crate) fn run(mut cmd: Command, timeout: Option<Duration>) -> io::Result<Finished> {
+    let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    let mut child = cmd.spawn()?;
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let mut timed_out = false;
+    let status = match deadline {
+        None => child.wait()?,
+        Some(deadline) => loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                timed_out = true;
+                kill_process_tree(&mut child);
+                break child.wait()?;
+            }
+            thread::sleep(POLL_INTERVAL);
+        },
+    };
+    // Background children keep the pipes open after the command exits, so
+    // the deadline also bounds reading their output.
+    let stdout = collect(&stdout, deadline, &mut child, &mut timed_out);
+    let stderr = collect(&stderr, deadline, &mut child, &mut timed_out);
+
+    Ok(Finished {
+        status,
+        stdout,
+        stderr,
+        timed_out,
+    })
+}
+
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        let _ = tx.send(buf);
+    });
+    rx
+}
+
+fn collect(
+    rx: &Receiver<Vec<u8>>,
+    deadline: Option<Instant>,
+    child: &mut Child,
+    timed_out: &mut bool,
+) -> Vec<u8> {
+    if let Some(deadline) = deadline.filter(|_| !*timed_out) {
+        match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(buf) => return buf,
+            Err(RecvTimeoutError::Disconnected) => return Vec::new(),
+            Err(RecvTimeoutError::Timeout) => {
+                *timed_out = true;
+                kill_process_tree(child);
+            }
+        }
+    }
+    rx.recv().unwrap_or_default()
+}
+
+/// Kills the command's process group, which also takes down its children.
+#[cfg(unix)]
+fn kill_process_tree(child: &mut Child) {
+    use nix::sys::signal::{killpg, Signal};
+    use nix::unistd::Pid;
+
+    match i32::try_from(child.id()) {
+        Ok(pid) => {
+            let _ = killpg(Pid::from_raw(pid), Signal::SIGKILL);
+        }
+        Err(_) => {
+            let _ = child.kill();
+        }
+    }
+}
+
+/// Kills the command and its descendants with `taskkill /T`.
+#[cfg(not(unix))]
+fn kill_process_tree(child: &mut Child) {
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .output();
+    let _ = child.kill();
+}
@@ -1,72 +1,196 @@
 use anyhow::{anyhow, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 use crate::agent::FunctionDeclaration;
-use crate::tools::Tool;
+use crate::config::{self, FileSearchResolved};
+use crate::tools::{invalid_arguments, workdir, Tool, ToolOutput};
 
 const DECL_JSON: &str = include_str!("../../tools/project_files.json");
 
+static DECLARATION: Lazy<FunctionDeclaration> =
+    Lazy::new(|| serde_json::from_str(DECL_JSON).expect("invalid project_files.json"));
+
 /// Returns the function declaration for this tool.
 pub fn declaration() -> FunctionDeclaration {
-    serde_json::from_str(DECL_JSON).expect("invalid project_files.json")
+    DECLARATION.clone()
 }
 
-/// Executes file operations in the project directory.
-pub fn execute(args: &Value) -> Result<String> {
+/// Executes file operations in the project directory, or in the agent
+/// workdir when one is active.
+pub fn execute(args: &Value) -> Result<ToolOutput> {
     let action = args["action"]
         .as_str()
-        .ok_or_else(|| anyhow!("action missing"))?;
+        .ok_or_else(|| invalid_arguments("action missing"))?;
     match action {
         "create" => {
             let path = args["path"]
                 .as_str()
-                .ok_or_else(|| anyhow!("path missing"))?;
+                .ok_or_else(|| invalid_arguments("path missing"))?;
             let content = args["content"].as_str().unwrap_or_default();
-            fs::write(path, content)?;
-            Ok(format!("Created {path}"))
+            fs::write(workdir::resolve(path)?, content)?;
+            Ok(format!("Created {path}").into())
         }
         "read" => {
             let path = args["path"]
                 .as_str()
-                .ok_or_else(|| anyhow!("path missing"))?;
-            let content = fs::read_to_string(path)?;
-            Ok(content)
+                .ok_or_else(|| invalid_arguments("path missing"))?;
+            let content = fs::read_to_string(workdir::resolve(path)?)?;
+            Ok(content.into())
         }
         "update" => {
             let path = args["path"]
                 .as_str()
-                .ok_or_else(|| anyhow!("path missing"))?;
+                .ok_or_else(|| invalid_arguments("path missing"))?;
             let content = args["content"]
                 .as_str()
-                .ok_or_else(|| anyhow!("content missing"))?;
-            fs::write(path, content)?;
-            Ok(format!("Updated {path}"))
+                .ok_or_else(|| invalid_arguments("content missing"))?;
+            fs::write(workdir::resolve(path)?, content)?;
+            Ok(format!("Updated {path}").into())
         }
         "search" => {
             let query = args["query"]
                 .as_str()
-                .ok_or_else(|| anyhow!("query missing"))?;
-            let mut matches = Vec::new();
-            for entry in walkdir::WalkDir::new(".") {
-                let entry = entry?;
-                if entry.file_type().is_file() {
-                    if let Ok(contents) = fs::read_to_string(entry.path()) {
-                        if contents.contains(query) {
-                            matches.push(entry.path().display().to_string());
-                        }
-                    }
-                }
-            }
+                .ok_or_else(|| invalid_arguments("query missing"))?;
+            let root = workdir::root()?.unwrap_or_else(|| PathBuf::from("."));
+            let matches = search(&root, query, &config::file_search()?)?;
             if matches.is_empty() {
-                Ok("No matches found".to_string())
+                Ok("No matches found".into())
             } else {
-                Ok(matches.join("\n"))
+                Ok(matches.join("\n").into())
             }
         }
-        _ => Err(anyhow!("unknown action")),
+        _ => Err(invalid_arguments("unknown action")),
+    }
+}
+
+/// Number of leading bytes inspected when deciding whether a file is binary.
+const BINARY_SNIFF_LEN: usize = 8192;
+
+fn read_searchable(path: &Path, max_file_size: u64) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > max_file_size {
+        return None;
     }
+    let bytes = fs::read(path).ok()?;
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_LEN)];
+    if sniff.contains(&0) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// File in the project root with gitignore-style patterns of paths that the
+/// `search` action never reads or returns.
+pub const IGNORE_FILE: &str = ".taskterignore";
+
+/// Patterns loaded from [`IGNORE_FILE`], matched against paths relative to
+/// the project root.
+struct ProjectIgnore {
+    project: PathBuf,
+    matcher: Gitignore,
+}
+
+impl ProjectIgnore {
+    /// Loads the ignore file from the current directory, if there is one.
+    fn load() -> Result<Option<Self>> {
+        let project = std::env::current_dir()?.canonicalize()?;
+        let file = project.join(IGNORE_FILE);
+        if !file.is_file() {
+            return Ok(None);
+        }
+        let mut builder = GitignoreBuilder::new(&project);
+        if let Some(err) = builder.add(&file) {
+            return Err(anyhow!("Invalid {IGNORE_FILE}: {err}"));
+        }
+        let matcher = builder
+            .build()
+            .map_err(|err| anyhow!("Invalid {IGNORE_FILE}: {err}"))?;
+        Ok(Some(Self { project, matcher }))
+    }
+
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let relative = if path.is_absolute() {
+            path.strip_prefix(&self.project)
+        } else {
+            path.strip_prefix(".")
+        }
+        .unwrap_or(path);
+        self.matcher.matched(relative, is_dir).is_ignore()
+    }
+}
+
+/// Searches text files below `root` for `query` using a bounded pool of
+/// worker threads.
+///
+/// Directories listed in `settings.ignore_dirs`, paths matched by
+/// [`IGNORE_FILE`], binary files and files larger than
+/// `settings.max_file_size` are skipped. Matches are returned sorted.
+///
+/// # Errors
+///
+/// Returns an error if the directory tree cannot be walked or the ignore
+/// file is invalid.
+pub fn search(root: &Path, query: &str, settings: &FileSearchResolved) -> Result<Vec<String>> {
+    let ignore = ProjectIgnore::load()?;
+    let mut files: Vec<PathBuf> = Vec::new();
+    let walker = walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            let is_dir = entry.file_type().is_dir();
+            if ignore
+                .as_ref()
+                .is_some_and(|ignore| ignore.is_ignored(entry.path(), is_dir))
+            {
+                return false;
+            }
+            !is_dir
+                || !settings
+                    .ignore_dirs
+                    .iter()
+                    .any(|ignored| entry.file_name() == ignored.as_str())
+        });
+    for entry in walker {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            files.push(entry.into_path());
+        }
+    }
+
+    let next = AtomicUsize::new(0);
+    let matches = Mutex::new(Vec::new());
+    let workers = settings.concurrency.clamp(1, files.len().max(1));
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = files.get(index) else {
+                    break;
+                };
+                if let Some(contents) = read_searchable(path, settings.max_file_size) {
+                    if contents.contains(query) {
+                        matches
+                            .lock()
+                            .expect("search results lock poisoned")
+                            .push(path.display().to_string());
+                    }
+                }
+            });
+        }
+    });
+
+    let mut matches = matches.into_inner().expect("search results lock poisoned");
+    matches.sort();
+    Ok(matches)
 }
 
 /// Registers the tool in the provided map.
@@ -1,37 +1,58 @@
 use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::process::Command;
+use std::time::Duration;
 
 use crate::agent::FunctionDeclaration;
-use crate::tools::Tool;
+use crate::tools::{invalid_arguments, process, workdir, Tool, ToolOutput};
 use std::collections::HashMap;
 
 const DECL_JSON: &str = include_str!("../../tools/run_bash.json");
 
+static DECLARATION: Lazy<FunctionDeclaration> =
+    Lazy::new(|| serde_json::from_str(DECL_JSON).expect("invalid run_bash.json"));
+
 /// Returns the function declaration for this tool.
 pub fn declaration() -> FunctionDeclaration {
-    serde_json::from_str(DECL_JSON).expect("invalid run_bash.json")
+    DECLARATION.clone()
 }
 
-/// Runs a shell command using `sh -c`.
+/// Runs a shell command using `sh -c`, inside the agent workdir when one is
+/// active.
+///
+/// The command runs in its own process group. When the optional
+/// `timeout_secs` argument elapses, the whole group is killed so background
+/// children do not outlive the call.
 ///
 /// # Errors
 ///
 /// Returns an error if the `command` argument is missing, if the command fails
-/// to execute, or if it exits with a non-zero status.
-pub fn execute(args: &Value) -> Result<String> {
+/// to execute, if it exits with a non-zero status, or if it times out.
+pub fn execute(args: &Value) -> Result<ToolOutput> {
     let command = args["command"]
         .as_str()
-        .ok_or_else(|| anyhow!("command missing"))?;
+        .ok_or_else(|| invalid_arguments("command missing"))?;
+    let timeout = args.get("timeout_secs").and_then(Value::as_u64);
 
-    let output = Command::new("sh").arg("-c").arg(command).output()?;
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    if let Some(dir) = workdir::root()? {
+        cmd.current_dir(dir);
+    }
+    let finished = process::run(cmd, timeout.map(Duration::from_secs))?;
 
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    if let (true, Some(secs)) = (finished.timed_out, timeout) {
+        Err(anyhow!("Command timed out after {secs}s"))
+    } else if finished.status.success() {
+        Ok(String::from_utf8_lossy(&finished.stdout)
+            .trim()
+            .to_string()
+            .into())
     } else {
         Err(anyhow!(
             "Command failed: {}",
-            String::from_utf8_lossy(&output.stderr)
+            String::from_utf8_lossy(&finished.stderr)
         ))
     }
 }
@@ -1,37 +1,82 @@
 use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
 use serde_json::Value;
+use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
 
 use crate::agent::FunctionDeclaration;
-use crate::tools::Tool;
+use crate::tools::{invalid_arguments, process, workdir, Tool, ToolOutput};
 use std::collections::HashMap;
 
 const DECL_JSON: &str = include_str!("../../tools/run_python.json");
 
+static DECLARATION: Lazy<FunctionDeclaration> =
+    Lazy::new(|| serde_json::from_str(DECL_JSON).expect("invalid run_python.json"));
+
 /// Returns the function declaration for this tool.
 pub fn declaration() -> FunctionDeclaration {
-    serde_json::from_str(DECL_JSON).expect("invalid run_python.json")
+    DECLARATION.clone()
 }
 
-/// Executes a Python snippet using the system `python3`.
+/// Resolves a script path and ensures it stays inside the project directory.
+fn project_script(path: &str) -> Result<PathBuf> {
+    let root = std::env::current_dir()?.canonicalize()?;
+    let script = workdir::resolve(path)?
+        .canonicalize()
+        .map_err(|e| anyhow!("Script {path} not found: {e}"))?;
+    if !script.starts_with(&root) {
+        return Err(invalid_arguments(format!(
+            "Script {path} is outside the project directory"
+        )));
+    }
+    if !script.is_file() {
+        return Err(anyhow!("Script {path} is not a file"));
+    }
+    Ok(script)
+}
+
+/// Executes Python using the system `python3`, either an inline `code`
+/// snippet or a script file at `path` inside the project.
+///
+/// Python runs in its own process group, and the optional `timeout_secs`
+/// argument kills the whole group like it does for `run_bash`.
 ///
 /// # Errors
 ///
-/// Returns an error if the `code` argument is missing, if `python3` cannot be
-/// executed, or if the script exits with a non-zero status.
-pub fn execute(args: &Value) -> Result<String> {
-    let code = args["code"]
-        .as_str()
-        .ok_or_else(|| anyhow!("code missing"))?;
+/// Returns an error if neither or both of `code` and `path` are given, if the
+/// script is missing or outside the project, if `python3` cannot be executed,
+/// if the script exits with a non-zero status, or if it times out.
+pub fn execute(args: &Value) -> Result<ToolOutput> {
+    let mut cmd = Command::new("python3");
+    if let Some(dir) = workdir::root()? {
+        cmd.current_dir(dir);
+    }
+    match (args["code"].as_str(), args["path"].as_str()) {
+        (Some(code), None) => cmd.arg("-c").arg(code),
+        (None, Some(path)) => cmd.arg(project_script(path)?),
+        (Some(_), Some(_)) => {
+            return Err(invalid_arguments(
+                "Provide either `code` or `path`, not both",
+            ))
+        }
+        (None, None) => return Err(invalid_arguments("code missing: provide `code` or `path`")),
+    };
 
-    let output = Command::new("python3").arg("-c").arg(code).output()?;
+    let timeout = args.get("timeout_secs").and_then(Value::as_u64);
+    let finished = process::run(cmd, timeout.map(Duration::from_secs))?;
 
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    if let (true, Some(secs)) = (finished.timed_out, timeout) {
+        Err(anyhow!("Python timed out after {secs}s"))
+    } else if finished.status.success() {
+        Ok(String::from_utf8_lossy(&finished.stdout)
+            .trim()
+            .to_string()
+            .into())
     } else {
         Err(anyhow!(
             "Python execution failed: {}",
-            String::from_utf8_lossy(&output.stderr)
+            String::from_utf8_lossy(&finished.stderr)
         ))
     }
 }
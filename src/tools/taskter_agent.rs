@@ -1,9 +1,10 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
+use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::process::Command;
 
 use crate::agent::FunctionDeclaration;
-use crate::tools::Tool;
+use crate::tools::{StructuredAction, Tool, ToolOutput};
 use std::collections::HashMap;
 
 const DECL_JSON: &str = include_str!("../../tools/taskter_agent.json");
@@ -15,38 +16,56 @@ fn taskter_bin() -> std::path::PathBuf {
         .unwrap_or_else(|_| "taskter".into())
 }
 
+/// Subcommands accepted by the structured form.
+const ACTIONS: &[StructuredAction] = &[
+    StructuredAction {
+        name: "add",
+        required: &["prompt", "model"],
+        optional: &[
+            "tools",
+            "provider",
+            "workdir",
+            "retry_on_failure",
+            "execution_budget_secs",
+        ],
+    },
+    StructuredAction {
+        name: "list",
+        required: &[],
+        optional: &["limit", "offset"],
+    },
+    StructuredAction {
+        name: "remove",
+        required: &["id"],
+        optional: &[],
+    },
+    StructuredAction {
+        name: "update",
+        required: &["id"],
+        optional: &["prompt", "tools", "model", "provider", "retry_on_failure"],
+    },
+];
+
+static DECLARATION: Lazy<FunctionDeclaration> =
+    Lazy::new(|| serde_json::from_str(DECL_JSON).expect("invalid taskter_agent.json"));
+
 pub fn declaration() -> FunctionDeclaration {
-    serde_json::from_str(DECL_JSON).expect("invalid taskter_agent.json")
+    DECLARATION.clone()
 }
 
-/// Invokes the `taskter agent` subcommand.
+/// Invokes the `taskter agent` subcommand, given either positional `args` or
+/// an `action` with named fields (see [`crate::tools::subcommand_args`]).
 ///
 /// # Errors
 ///
-/// Returns an error if the `args` array is missing, contains non-string
-/// arguments, or if the command fails to run or exits with a non-zero status.
-pub fn execute(args: &Value) -> Result<String> {
-    let arg_list = args["args"]
-        .as_array()
-        .ok_or_else(|| anyhow!("args missing"))?;
+/// Returns an error if the arguments are invalid for either form, or if the
+/// command fails to run or exits with a non-zero status after the configured
+/// retries.
+pub fn execute(args: &Value) -> Result<ToolOutput> {
     let mut cmd = Command::new(taskter_bin());
-    cmd.arg("agent");
-    for a in arg_list {
-        if let Some(s) = a.as_str() {
-            cmd.arg(s);
-        } else {
-            return Err(anyhow!("args must be strings"));
-        }
-    }
-    let output = cmd.output()?;
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        Err(anyhow!(
-            "Command failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ))
-    }
+    cmd.arg("agent")
+        .args(crate::tools::subcommand_args(args, ACTIONS)?);
+    crate::tools::run_subprocess(&mut cmd).map(ToolOutput::from_stdout)
 }
 
 pub fn register(map: &mut HashMap<&'static str, Tool>) {
@@ -1,9 +1,10 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
+use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::process::Command;
 
 use crate::agent::FunctionDeclaration;
-use crate::tools::Tool;
+use crate::tools::{invalid_arguments, Tool, ToolOutput};
 use std::collections::HashMap;
 
 const DECL_JSON: &str = include_str!("../../tools/taskter_tools.json");
@@ -15,8 +16,11 @@ fn taskter_bin() -> std::path::PathBuf {
         .unwrap_or_else(|_| "taskter".into())
 }
 
+static DECLARATION: Lazy<FunctionDeclaration> =
+    Lazy::new(|| serde_json::from_str(DECL_JSON).expect("invalid taskter_tools.json"));
+
 pub fn declaration() -> FunctionDeclaration {
-    serde_json::from_str(DECL_JSON).expect("invalid taskter_tools.json")
+    DECLARATION.clone()
 }
 
 /// Invokes the `taskter tools` subcommand.
@@ -24,29 +28,22 @@ pub fn declaration() -> FunctionDeclaration {
 /// # Errors
 ///
 /// Returns an error if the `args` array is missing, contains non-string
-/// arguments, or if the command fails to run or exits with a non-zero status.
-pub fn execute(args: &Value) -> Result<String> {
+/// arguments, or if the command fails to run or exits with a non-zero status
+/// after the configured retries.
+pub fn execute(args: &Value) -> Result<ToolOutput> {
     let arg_list = args["args"]
         .as_array()
-        .ok_or_else(|| anyhow!("args missing"))?;
+        .ok_or_else(|| invalid_arguments("args missing"))?;
     let mut cmd = Command::new(taskter_bin());
     cmd.arg("tools");
     for a in arg_list {
         if let Some(s) = a.as_str() {
             cmd.arg(s);
         } else {
-            return Err(anyhow!("args must be strings"));
+            return Err(invalid_arguments("args must be strings"));
         }
     }
-    let output = cmd.output()?;
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        Err(anyhow!(
-            "Command failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ))
-    }
+    crate::tools::run_subprocess(&mut cmd).map(ToolOutput::from_stdout)
 }
 
 pub fn register(map: &mut HashMap<&'static str, Tool>) {
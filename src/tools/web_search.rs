@@ -1,21 +1,30 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
+use once_cell::sync::Lazy;
 use serde_json::Value;
 use std::collections::HashMap;
 
 use crate::agent::FunctionDeclaration;
-use crate::tools::Tool;
+use crate::tools::{invalid_arguments, Tool, ToolOutput};
 
 const DECL_JSON: &str = include_str!("../../tools/web_search.json");
 
+static DECLARATION: Lazy<FunctionDeclaration> =
+    Lazy::new(|| serde_json::from_str(DECL_JSON).expect("invalid web_search.json"));
+
 pub fn declaration() -> FunctionDeclaration {
-    serde_json::from_str(DECL_JSON).expect("invalid web_search.json")
+    DECLARATION.clone()
 }
 
 async fn search_online(query: &str) -> Result<String> {
-    let endpoint = std::env::var("SEARCH_API_ENDPOINT")
-        .unwrap_or_else(|_| "https://api.duckduckgo.com".to_string());
+    let settings = crate::config::web_search()?;
+    // The environment variable still wins so tests can point at a mock server.
+    let endpoint = std::env::var("SEARCH_API_ENDPOINT").unwrap_or(settings.endpoint);
     let url = reqwest::Url::parse_with_params(&endpoint, &[("q", query), ("format", "json")])?;
-    let resp = reqwest::get(url).await?;
+    let mut builder = crate::tools::http_client_builder()?;
+    if let Some(user_agent) = settings.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    let resp = builder.build()?.get(url).send().await?;
     let json: Value = resp.json().await?;
     if let Some(text) = json["AbstractText"].as_str() {
         if !text.is_empty() {
@@ -30,18 +39,19 @@ async fn search_online(query: &str) -> Result<String> {
     Ok("No results found".to_string())
 }
 
-/// Performs a simple web search using DuckDuckGo.
+/// Performs a simple web search using DuckDuckGo, or the API configured in
+/// `tools.search.endpoint`.
 ///
 /// # Errors
 ///
 /// Returns an error if the `query` argument is missing or if the HTTP request
 /// fails.
-pub fn execute(args: &Value) -> Result<String> {
+pub fn execute(args: &Value) -> Result<ToolOutput> {
     let query = args["query"]
         .as_str()
-        .ok_or_else(|| anyhow!("query missing"))?;
+        .ok_or_else(|| invalid_arguments("query missing"))?;
     let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(search_online(query))
+    rt.block_on(search_online(query)).map(ToolOutput::Text)
 }
 
 pub fn register(map: &mut HashMap<&'static str, Tool>) {
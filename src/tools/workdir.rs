@@ -0,0 +1,103 @@
+//! Per-agent working directory that confines file tools during a run.
+//!
+//! The project directory is the outer bound: an agent's `workdir` must lie
+//! inside it, and file-tool paths must stay inside the workdir.
+
+use anyhow::{anyhow, Result};
+use std::cell::RefCell;
+use std::path::{Component, Path, PathBuf};
+
+thread_local! {
+    static WORKDIR: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous workdir when a scoped call returns or panics.
+struct Restore(Option<PathBuf>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        WORKDIR.with(|w| *w.borrow_mut() = self.0.take());
+    }
+}
+
+/// Runs `f` with `workdir` (relative to the project directory) as the scope
+/// for file tools on this thread.
+pub(crate) fn scoped<T>(workdir: Option<&Path>, f: impl FnOnce() -> T) -> T {
+    let previous = WORKDIR.with(|w| w.replace(workdir.map(Path::to_path_buf)));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Returns the canonical directory tools should work in while an agent
+/// workdir is active.
+///
+/// # Errors
+///
+/// Returns an error if the workdir does not exist or lies outside the project
+/// directory.
+pub(crate) fn root() -> Result<Option<PathBuf>> {
+    let Some(workdir) = WORKDIR.with(|w| w.borrow().clone()) else {
+        return Ok(None);
+    };
+    check(&workdir).map(Some)
+}
+
+/// Returns the canonical form of `workdir`, which is relative to the project
+/// directory.
+///
+/// # Errors
+///
+/// Returns an error if the directory does not exist, is not a directory or
+/// lies outside the project directory.
+pub(crate) fn check(workdir: &Path) -> Result<PathBuf> {
+    let project = std::env::current_dir()?.canonicalize()?;
+    let dir = project
+        .join(workdir)
+        .canonicalize()
+        .map_err(|e| anyhow!("Agent workdir {} not found: {e}", workdir.display()))?;
+    if !dir.starts_with(&project) {
+        return Err(anyhow!(
+            "Agent workdir {} is outside the project directory",
+            workdir.display()
+        ));
+    }
+    if !dir.is_dir() {
+        return Err(anyhow!(
+            "Agent workdir {} is not a directory",
+            workdir.display()
+        ));
+    }
+    Ok(dir)
+}
+
+/// Resolves a file-tool path against the active agent workdir. Without a
+/// workdir the path is returned unchanged.
+///
+/// # Errors
+///
+/// Returns an error if the path escapes the workdir.
+pub(crate) fn resolve(path: &str) -> Result<PathBuf> {
+    let Some(root) = root()? else {
+        return Ok(PathBuf::from(path));
+    };
+    let mut resolved = root.clone();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => {
+                resolved = PathBuf::from(component.as_os_str());
+            }
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(part) => resolved.push(part),
+        }
+    }
+    let real = resolved.canonicalize().unwrap_or_else(|_| resolved.clone());
+    if !resolved.starts_with(&root) || !real.starts_with(&root) {
+        return Err(crate::tools::invalid_arguments(format!(
+            "Path {path} is outside the agent workdir"
+        )));
+    }
+    Ok(resolved)
+}
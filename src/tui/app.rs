@@ -1,13 +1,17 @@
 //! Application state and logic for the terminal UI.
 
+use super::input;
 use crate::agent::Agent;
 use crate::config;
 use crate::store::{self, Board, Okr, Task, TaskStatus};
+use crossterm::event::{Event, MouseEventKind};
 use ratatui::widgets::ListState;
+use std::collections::HashMap;
 use std::fs;
 use std::sync::{Arc, Mutex};
+use tokio::task::AbortHandle;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum View {
     Board,
     TaskDescription,
@@ -19,6 +23,8 @@ pub enum View {
     Agents,
     Okrs,
     Commands,
+    /// Details of the agent assigned to the selected task.
+    AgentDetail,
 }
 
 pub struct App {
@@ -35,7 +41,21 @@ pub struct App {
     pub editing_description: bool,
     pub logs: String,
     pub okrs: Vec<Okr>,
+    /// Whether the OKRs popup shows the collapsible tree instead of the flat list.
+    pub okr_tree_view: bool,
+    /// Expanded state of each objective in the tree view, indexed like `okrs`.
+    pub okr_expanded: Vec<bool>,
+    /// Index of the highlighted objective in the tree view.
+    pub selected_okr: usize,
     pub popup_scroll: u16,
+    /// Scroll offsets of closed popups that reopen where they were left.
+    pub saved_scroll: HashMap<View, u16>,
+    /// Agent runs started from the TUI, keyed by task id, so they can be cancelled.
+    pub executions: HashMap<usize, AbortHandle>,
+    /// Set when the whole screen must be repainted, e.g. after a resize.
+    pub redraw_requested: bool,
+    /// Why the last board change could not be saved, shown in the status bar.
+    pub board_error: Option<String>,
 }
 
 impl App {
@@ -61,12 +81,175 @@ impl App {
                 .and_then(|path| fs::read_to_string(path).ok())
                 .unwrap_or_default(),
             okrs: store::load_okrs().unwrap_or_default(),
+            okr_tree_view: false,
+            okr_expanded: Vec::new(),
+            selected_okr: 0,
             popup_scroll: 0,
+            saved_scroll: HashMap::new(),
+            executions: HashMap::new(),
+            redraw_requested: false,
+            board_error: None,
         };
         app.selected_task[0].select(Some(0));
         app
     }
 
+    /// Whether `view` reopens at the scroll offset it was closed at.
+    fn remembers_scroll(view: View) -> bool {
+        matches!(view, View::Logs | View::Okrs | View::Commands)
+    }
+
+    /// Shows `view`, restoring its last scroll offset if it remembers one and
+    /// starting at the top otherwise.
+    pub fn open_popup(&mut self, view: View) {
+        self.current_view = view;
+        self.popup_scroll = if Self::remembers_scroll(view) {
+            self.saved_scroll.get(&view).copied().unwrap_or(0)
+        } else {
+            0
+        };
+    }
+
+    /// Returns to the board, saving the popup's scroll offset for views that
+    /// remember it.
+    pub fn close_popup(&mut self) {
+        if Self::remembers_scroll(self.current_view) {
+            self.saved_scroll
+                .insert(self.current_view, self.popup_scroll);
+        }
+        self.current_view = View::Board;
+        self.popup_scroll = 0;
+    }
+
+    /// Replaces the OKRs shown in the popup and collapses every objective.
+    pub fn set_okrs(&mut self, okrs: Vec<Okr>) {
+        self.okr_expanded = vec![false; okrs.len()];
+        self.okrs = okrs;
+        self.selected_okr = 0;
+    }
+
+    /// Switches the OKRs popup between the flat list and the tree view.
+    pub fn toggle_okr_view(&mut self) {
+        self.okr_tree_view = !self.okr_tree_view;
+    }
+
+    /// Expands or collapses the objective at `index`. Out-of-range indices are
+    /// ignored.
+    pub fn toggle_okr_expanded(&mut self, index: usize) {
+        if index >= self.okrs.len() {
+            return;
+        }
+        self.okr_expanded.resize(self.okrs.len(), false);
+        self.okr_expanded[index] = !self.okr_expanded[index];
+    }
+
+    /// Returns whether the objective at `index` shows its key results in the
+    /// tree view.
+    pub fn is_okr_expanded(&self, index: usize) -> bool {
+        self.okr_expanded.get(index).copied().unwrap_or(false)
+    }
+
+    pub fn next_okr(&mut self) {
+        if !self.okrs.is_empty() {
+            self.selected_okr = (self.selected_okr + 1) % self.okrs.len();
+        }
+    }
+
+    pub fn prev_okr(&mut self) {
+        if !self.okrs.is_empty() {
+            self.selected_okr = (self.selected_okr + self.okrs.len() - 1) % self.okrs.len();
+        }
+    }
+
+    /// Updates state for terminal events other than key presses, which are
+    /// handled by the event loop.
+    ///
+    /// A resize requests a full redraw so the layout is recomputed right away.
+    /// Mouse scrolling moves through tasks on the board and scrolls popups.
+    /// Pasted text goes into the input being edited, if any.
+    pub fn handle_terminal_event(&mut self, event: &Event) {
+        match event {
+            Event::Resize(_, _) => self.redraw_requested = true,
+            Event::Mouse(mouse) => match (mouse.kind, self.current_view) {
+                (MouseEventKind::ScrollDown, View::Board) => self.next_task(),
+                (MouseEventKind::ScrollUp, View::Board) => self.prev_task(),
+                (MouseEventKind::ScrollDown, _) => {
+                    self.popup_scroll = self.popup_scroll.saturating_add(1);
+                }
+                (MouseEventKind::ScrollUp, _) => {
+                    self.popup_scroll = self.popup_scroll.saturating_sub(1);
+                }
+                _ => {}
+            },
+            Event::Paste(text) => {
+                if let Some(buffer) = self.active_input() {
+                    input::push_pasted(buffer, text);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The text field that typing edits in the current view.
+    pub fn active_input(&mut self) -> Option<&mut String> {
+        match self.current_view {
+            View::AddComment => Some(&mut self.comment_input),
+            View::AddTask | View::UpdateTask if self.editing_description => {
+                Some(&mut self.new_task_description)
+            }
+            View::AddTask | View::UpdateTask => Some(&mut self.new_task_title),
+            _ => None,
+        }
+    }
+
+    /// Applies `change` to the board on disk under the board lock and shows the
+    /// saved board, which includes writes made by other processes since the
+    /// last reload. A failure, such as a lock timeout, is kept in
+    /// `board_error` instead.
+    pub fn save_change(&mut self, change: impl FnOnce(&mut Board)) {
+        let saved = store::update_board(|board| {
+            change(board);
+            board.clone()
+        });
+        match saved {
+            Ok(board) => {
+                *self.board.lock().unwrap() = board;
+                self.board_error = None;
+            }
+            Err(err) => self.board_error = Some(format!("Could not save the board: {err}")),
+        }
+    }
+
+    /// Remembers the handle of an agent run started for `task_id`.
+    pub fn track_execution(&mut self, task_id: usize, handle: AbortHandle) {
+        self.executions.insert(task_id, handle);
+    }
+
+    /// Forgets runs that have already completed.
+    pub fn prune_finished_executions(&mut self) {
+        self.executions.retain(|_, handle| !handle.is_finished());
+    }
+
+    /// Aborts the agent run for `task_id`, returning the task to "To Do" and
+    /// unassigning it. Returns `false` when no run is tracked for the task or
+    /// the run has already finished, so its result is kept.
+    ///
+    /// Tools run synchronously, so the abort takes effect at the run's next
+    /// await point, after any tool call in progress has returned; no command
+    /// process is left behind. A long command is bounded by its own
+    /// `timeout_secs`, which kills its process group.
+    pub fn cancel_execution(&mut self, task_id: usize) -> bool {
+        let Some(handle) = self.executions.remove(&task_id) else {
+            return false;
+        };
+        if handle.is_finished() {
+            return false;
+        }
+        handle.abort();
+        self.board.lock().unwrap().return_task(task_id);
+        true
+    }
+
     pub fn next_column(&mut self) {
         self.selected_column = (self.selected_column + 1) % 3;
         self.ensure_selected_task();
@@ -128,15 +311,19 @@ impl App {
             .collect()
     }
 
-    pub fn move_task_to_next_column(&mut self) {
-        self.move_task(1);
+    /// Moves the selected task one column right, returning its id and new
+    /// status so the caller can persist the move.
+    pub fn move_task_to_next_column(&mut self) -> Option<(usize, TaskStatus)> {
+        self.move_task(1)
     }
 
-    pub fn move_task_to_prev_column(&mut self) {
-        self.move_task(-1);
+    /// Moves the selected task one column left, returning its id and new
+    /// status so the caller can persist the move.
+    pub fn move_task_to_prev_column(&mut self) -> Option<(usize, TaskStatus)> {
+        self.move_task(-1)
     }
 
-    fn move_task(&mut self, direction: i8) {
+    fn move_task(&mut self, direction: i8) -> Option<(usize, TaskStatus)> {
         let task_id_to_move =
             if let Some(selected_index) = self.selected_task[self.selected_column].selected() {
                 let tasks_in_column = self.tasks_in_current_column();
@@ -147,34 +334,22 @@ impl App {
 
         if let Some(task_id) = task_id_to_move {
             let new_status_index;
+            let destination_status;
             {
-                if let Some(task) = self
-                    .board
-                    .lock()
-                    .unwrap()
-                    .tasks
-                    .iter_mut()
-                    .find(|t| t.id == task_id)
-                {
-                    let current_status_index = task.status.clone() as usize;
-                    let next = (current_status_index as i8 + direction + 3) % 3;
-                    task.status = match next {
-                        0 => TaskStatus::ToDo,
-                        1 => TaskStatus::InProgress,
-                        _ => TaskStatus::Done,
-                    };
-                    new_status_index = usize::from(next.unsigned_abs());
-                } else {
-                    return;
-                }
+                let mut board = self.board.lock().unwrap();
+                let task = board.tasks.iter().find(|t| t.id == task_id)?;
+                let current_status_index = task.status.clone() as usize;
+                let next = (current_status_index as i8 + direction + 3) % 3;
+                destination_status = match next {
+                    0 => TaskStatus::ToDo,
+                    1 => TaskStatus::InProgress,
+                    _ => TaskStatus::Done,
+                };
+                new_status_index = usize::from(next.unsigned_abs());
+                board.move_task(task_id, destination_status.clone());
             }
 
             // Select the moved task in its new column
-            let destination_status = match new_status_index {
-                0 => TaskStatus::ToDo,
-                1 => TaskStatus::InProgress,
-                _ => TaskStatus::Done,
-            };
             let tasks_in_destination: Vec<Task> = self
                 .board
                 .lock()
@@ -197,7 +372,9 @@ impl App {
                     self.selected_task[self.selected_column].select(Some(tasks_left.len() - 1));
                 }
             }
+            return Some((task_id, destination_status));
         }
+        None
     }
 
     pub fn get_selected_task(&self) -> Option<Task> {
@@ -209,6 +386,22 @@ impl App {
             })
     }
 
+    /// Opens the detail popup for the agent assigned to the selected task,
+    /// selecting it in `agent_list_state`. Returns `false` and leaves the view
+    /// unchanged when the task has no agent or the agent no longer exists.
+    pub fn show_task_agent(&mut self) -> bool {
+        let Some(agent_id) = self.get_selected_task().and_then(|t| t.agent_id) else {
+            return false;
+        };
+        let Some(index) = self.agents.iter().position(|a| a.id == agent_id) else {
+            return false;
+        };
+        self.agent_list_state.select(Some(index));
+        self.current_view = View::AgentDetail;
+        self.popup_scroll = 0;
+        true
+    }
+
     pub fn unassign_selected_task(&mut self) {
         if let Some(task_id) = self.get_selected_task().map(|t| t.id) {
             if let Some(task) = self
@@ -224,3 +417,104 @@ impl App {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(id: usize) -> Agent {
+        Agent {
+            id,
+            system_prompt: format!("agent {id}"),
+            tools: Vec::new(),
+            model: "gpt-4.1".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        }
+    }
+
+    fn task(agent_id: Option<usize>) -> Task {
+        Task {
+            id: 1,
+            title: "Task".into(),
+            description: None,
+            status: TaskStatus::ToDo,
+            agent_id,
+            comment: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn show_task_agent_selects_the_assigned_agent() {
+        let board = Board {
+            tasks: vec![task(Some(7))],
+            ..Default::default()
+        };
+        let mut app = App::new(board, vec![agent(3), agent(7)]);
+        app.current_view = View::TaskDescription;
+
+        assert!(app.show_task_agent());
+        assert!(matches!(app.current_view, View::AgentDetail));
+        assert_eq!(app.agent_list_state.selected(), Some(1));
+    }
+
+    #[test]
+    fn show_task_agent_ignores_unassigned_tasks() {
+        let board = Board {
+            tasks: vec![task(None)],
+            ..Default::default()
+        };
+        let mut app = App::new(board, vec![agent(3)]);
+        app.current_view = View::TaskDescription;
+
+        assert!(!app.show_task_agent());
+        assert!(matches!(app.current_view, View::TaskDescription));
+    }
+
+    #[test]
+    fn popups_reopen_at_their_saved_scroll_offset() {
+        let board = Board {
+            tasks: Vec::new(),
+            ..Default::default()
+        };
+        let mut app = App::new(board, Vec::new());
+
+        app.open_popup(View::Logs);
+        assert_eq!(app.popup_scroll, 0);
+        app.popup_scroll = 12;
+        app.close_popup();
+        assert!(matches!(app.current_view, View::Board));
+        assert_eq!(app.popup_scroll, 0);
+
+        app.open_popup(View::Commands);
+        assert_eq!(app.popup_scroll, 0);
+        app.popup_scroll = 4;
+        app.close_popup();
+
+        app.open_popup(View::Logs);
+        assert_eq!(app.popup_scroll, 12);
+        app.close_popup();
+        app.open_popup(View::Commands);
+        assert_eq!(app.popup_scroll, 4);
+        app.close_popup();
+    }
+
+    #[test]
+    fn other_popups_always_open_at_the_top() {
+        let board = Board {
+            tasks: Vec::new(),
+            ..Default::default()
+        };
+        let mut app = App::new(board, Vec::new());
+
+        app.open_popup(View::Agents);
+        app.popup_scroll = 5;
+        app.close_popup();
+        app.open_popup(View::Agents);
+        assert_eq!(app.popup_scroll, 0);
+        assert!(app.saved_scroll.is_empty());
+    }
+}
@@ -1,10 +1,15 @@
 use super::app::{App, View};
+use super::input;
 use super::render::ui;
 use crate::agent::{self};
 use crate::config;
-use crate::store::{self, Task, TaskStatus};
+use crate::logging::{self, LogEvent};
+use crate::store::{self, Board, Task, TaskStatus};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -14,7 +19,10 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::{mpsc::channel, Arc};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// How often the running-agents file is re-read in case a watcher event is missed.
+const RUNNING_AGENTS_POLL: Duration = Duration::from_secs(1);
 
 /// Runs the interactive terminal user interface.
 ///
@@ -23,9 +31,13 @@ use std::time::Duration;
 /// Returns an error if the terminal cannot be initialized or if an underlying
 /// I/O operation fails.
 pub fn run_tui() -> anyhow::Result<()> {
+    let mouse = mouse_capture_enabled(config::tui());
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
+    if mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -35,10 +47,13 @@ pub fn run_tui() -> anyhow::Result<()> {
     let res = run_app(&mut terminal, app);
 
     disable_raw_mode()?;
+    if mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
     execute!(
         terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableBracketedPaste,
+        LeaveAlternateScreen
     )?;
     terminal.show_cursor()?;
 
@@ -49,6 +64,12 @@ pub fn run_tui() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Mouse capture stops the terminal's own text selection, so it is only
+/// enabled when `tui.mouse` is set.
+fn mouse_capture_enabled(tui: anyhow::Result<config::TuiResolved>) -> bool {
+    tui.map(|cfg| cfg.mouse).unwrap_or(false)
+}
+
 fn ensure_file(path: &Path, contents: &str) -> io::Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
@@ -59,6 +80,41 @@ fn ensure_file(path: &Path, contents: &str) -> io::Result<()> {
     Ok(())
 }
 
+/// Records the outcome of a TUI agent run on `board`.
+fn apply_run_result(
+    board: &mut Board,
+    task_id: usize,
+    agent_id: usize,
+    result: anyhow::Result<agent::ExecutionResult>,
+) {
+    let author = store::CommentAuthor::Agent(agent_id);
+    let (comment, author) = match result {
+        Ok(agent::ExecutionResult::Success { comment, .. }) => {
+            board.move_task(task_id, TaskStatus::Done);
+            (comment, Some(author))
+        }
+        Ok(agent::ExecutionResult::Failure { comment, .. }) => {
+            board.return_task(task_id);
+            (comment, Some(author))
+        }
+        Err(_) => {
+            board.return_task(task_id);
+            ("Failed to execute task.".to_string(), None)
+        }
+    };
+    if let Some(task) = board.task_mut(task_id) {
+        task.comment = Some(comment);
+        task.comment_author = author;
+    }
+}
+
+/// Logs that the result of a TUI agent run could not be saved, since the
+/// run finishes outside the event loop that shows board errors.
+fn log_save_failure(task_id: usize, agent_id: usize, err: &anyhow::Error) {
+    let message = format!("Could not save the result of task {task_id}: {err}");
+    let _ = logging::append(&LogEvent::new("board_save_failed", message).agent(agent_id));
+}
+
 fn file_tail(path: &Path, fallback: &str) -> PathBuf {
     path.file_name()
         .map(PathBuf::from)
@@ -89,8 +145,14 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
     ensure_file(&agents_path, "[]")?;
     ensure_file(&running_agents_path, "[]")?;
 
+    // The board is saved by renaming a temporary file over it, which replaces
+    // the watched inode, so its directory is watched instead of the file.
+    let board_dir = match board_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
     for path in [
-        &board_path,
+        &board_dir,
         &okrs_path,
         &log_path,
         &agents_path,
@@ -101,7 +163,14 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
             .map_err(io::Error::other)?;
     }
 
+    let mut last_running_poll = Instant::now();
     loop {
+        if last_running_poll.elapsed() >= RUNNING_AGENTS_POLL {
+            if let Ok(running) = crate::agent::load_running_agents() {
+                app.running_agents = running;
+            }
+            last_running_poll = Instant::now();
+        }
         while let Ok(res) = rx.try_recv() {
             if let Ok(event) = res {
                 for p in event.paths {
@@ -130,40 +199,51 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
             }
         }
 
+        app.prune_finished_executions();
+        if app.redraw_requested {
+            terminal.clear()?;
+            app.redraw_requested = false;
+        }
         terminal.draw(|f| ui(f, &mut app))?;
 
         if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
+            let event = event::read()?;
+            app.handle_terminal_event(&event);
+            if let Event::Key(key) = event {
                 match app.current_view {
                     View::Board => match key.code {
-                        KeyCode::Char('q') => {
-                            store::save_board(&app.board.lock().unwrap()).unwrap();
-                            return Ok(());
-                        }
+                        // Every change is saved as it is made.
+                        KeyCode::Char('q') => return Ok(()),
                         KeyCode::Right | KeyCode::Tab => app.next_column(),
                         KeyCode::Left => app.prev_column(),
                         KeyCode::Down => app.next_task(),
                         KeyCode::Up => app.prev_task(),
-                        KeyCode::Char('l') => app.move_task_to_next_column(),
-                        KeyCode::Char('h') => app.move_task_to_prev_column(),
-                        KeyCode::Enter => {
-                            if app.get_selected_task().is_some() {
-                                app.current_view = View::TaskDescription;
+                        KeyCode::Char('l') => {
+                            if let Some((id, status)) = app.move_task_to_next_column() {
+                                app.save_change(|board| {
+                                    board.move_task(id, status);
+                                });
                             }
                         }
-                        KeyCode::Char('a') => {
-                            if app.get_selected_task().is_some() {
-                                app.current_view = View::AssignAgent;
-                                app.agent_list_state.select(Some(0));
-                                app.popup_scroll = 0;
+                        KeyCode::Char('h') => {
+                            if let Some((id, status)) = app.move_task_to_prev_column() {
+                                app.save_change(|board| {
+                                    board.move_task(id, status);
+                                });
                             }
                         }
-                        KeyCode::Char('c') => {
-                            if app.get_selected_task().is_some() {
-                                app.current_view = View::AddComment;
-                                app.comment_input.clear();
-                                app.popup_scroll = 0;
-                            }
+                        KeyCode::Enter if app.get_selected_task().is_some() => {
+                            app.current_view = View::TaskDescription;
+                        }
+                        KeyCode::Char('a') if app.get_selected_task().is_some() => {
+                            app.current_view = View::AssignAgent;
+                            app.agent_list_state.select(Some(0));
+                            app.popup_scroll = 0;
+                        }
+                        KeyCode::Char('c') if app.get_selected_task().is_some() => {
+                            app.current_view = View::AddComment;
+                            app.comment_input.clear();
+                            app.popup_scroll = 0;
                         }
                         KeyCode::Char('n') => {
                             app.new_task_title.clear();
@@ -181,40 +261,49 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                                 app.popup_scroll = 0;
                             }
                         }
+                        KeyCode::Esc => {
+                            if let Some(task_id) = app.get_selected_task().map(|t| t.id) {
+                                if app.cancel_execution(task_id) {
+                                    app.save_change(|board| {
+                                        board.return_task(task_id);
+                                    });
+                                }
+                            }
+                        }
                         KeyCode::Char('r') => {
-                            app.unassign_selected_task();
-                            store::save_board(&app.board.lock().unwrap()).unwrap();
+                            if let Some(task_id) = app.get_selected_task().map(|t| t.id) {
+                                app.save_change(|board| {
+                                    if let Some(task) = board.task_mut(task_id) {
+                                        task.agent_id = None;
+                                    }
+                                });
+                            }
                         }
                         KeyCode::Char('d') => {
                             if let Some(task_id) = app.get_selected_task().map(|t| t.id) {
-                                app.board.lock().unwrap().tasks.retain(|t| t.id != task_id);
+                                app.save_change(|board| board.tasks.retain(|t| t.id != task_id));
                                 let tasks = app.tasks_in_current_column();
                                 if !tasks.is_empty() {
                                     app.selected_task[app.selected_column].select(Some(0));
                                 } else {
                                     app.selected_task[app.selected_column].select(None);
                                 }
-                                store::save_board(&app.board.lock().unwrap()).unwrap();
                             }
                         }
                         KeyCode::Char('L') => {
                             app.logs = fs::read_to_string(&log_path).unwrap_or_default();
-                            app.current_view = View::Logs;
-                            app.popup_scroll = 0;
+                            app.open_popup(View::Logs);
                         }
                         KeyCode::Char('A') => {
                             app.agents = crate::agent::load_agents().unwrap_or_default();
-                            app.current_view = View::Agents;
-                            app.popup_scroll = 0;
+                            app.open_popup(View::Agents);
                         }
                         KeyCode::Char('O') => {
-                            app.okrs = store::load_okrs().unwrap_or_default();
-                            app.current_view = View::Okrs;
-                            app.popup_scroll = 0;
+                            app.set_okrs(store::load_okrs().unwrap_or_default());
+                            app.open_popup(View::Okrs);
                         }
                         KeyCode::Char('?') => {
-                            app.current_view = View::Commands;
-                            app.popup_scroll = 0;
+                            app.open_popup(View::Commands);
                         }
                         _ => {}
                     },
@@ -223,6 +312,9 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                             app.current_view = View::Board;
                             app.popup_scroll = 0;
                         }
+                        KeyCode::Char('g') => {
+                            app.show_task_agent();
+                        }
                         KeyCode::Down => {
                             app.popup_scroll = app.popup_scroll.saturating_add(1);
                         }
@@ -254,12 +346,12 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                             if let Some(selected_agent_index) = app.agent_list_state.selected() {
                                 if let Some(agent) = app.agents.get(selected_agent_index).cloned() {
                                     if let Some(task) = app.get_selected_task() {
-                                        let mut board = app.board.lock().unwrap();
-                                        if let Some(task_to_update) =
-                                            board.tasks.iter_mut().find(|t| t.id == task.id)
-                                        {
-                                            task_to_update.agent_id = Some(agent.id);
-                                        }
+                                        app.save_change(|board| {
+                                            if let Some(task_to_update) = board.task_mut(task.id) {
+                                                task_to_update.agent_id = Some(agent.id);
+                                            }
+                                        });
+                                        let (handle_tx, handle_rx) = channel();
                                         let agent_clone = agent.clone();
                                         let task_clone = task.clone();
                                         let board_clone = Arc::clone(&app.board);
@@ -269,49 +361,42 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                                                 .enable_all()
                                                 .build()
                                                 .expect("failed to build runtime");
-                                            rt.block_on(async move {
+                                            let execution = rt.spawn(async move {
                                                 let result = agent::execute_task(
                                                     &agent_clone,
                                                     Some(&task_clone),
                                                 )
                                                 .await;
-                                                let mut board = board_clone.lock().unwrap();
-                                                if let Some(task) = board
-                                                    .tasks
-                                                    .iter_mut()
-                                                    .find(|t| t.id == task_clone.id)
-                                                {
-                                                    match result {
-                                                        Ok(result) => match result {
-                                                            agent::ExecutionResult::Success {
-                                                                comment,
-                                                            } => {
-                                                                task.status =
-                                                                    store::TaskStatus::Done;
-                                                                task.comment = Some(comment);
-                                                            }
-                                                            agent::ExecutionResult::Failure {
-                                                                comment,
-                                                            } => {
-                                                                task.status =
-                                                                    store::TaskStatus::ToDo;
-                                                                task.comment = Some(comment);
-                                                                task.agent_id = None;
-                                                            }
-                                                        },
-                                                        Err(_) => {
-                                                            task.status = store::TaskStatus::ToDo;
-                                                            task.comment = Some(
-                                                                "Failed to execute task."
-                                                                    .to_string(),
-                                                            );
-                                                            task.agent_id = None;
-                                                        }
+                                                let task_id = task_clone.id;
+                                                let agent_id = agent_clone.id;
+                                                // The run can take minutes, so the result is
+                                                // applied to a fresh copy of the board under
+                                                // the board lock.
+                                                let saved =
+                                                    store::update_board_async(move |board| {
+                                                        apply_run_result(
+                                                            board, task_id, agent_id, result,
+                                                        );
+                                                        board.clone()
+                                                    })
+                                                    .await;
+                                                match saved {
+                                                    Ok(board) => {
+                                                        *board_clone.lock().unwrap() = board
+                                                    }
+                                                    Err(err) => {
+                                                        log_save_failure(task_id, agent_id, &err)
                                                     }
                                                 }
-                                                store::save_board(&board).unwrap();
                                             });
+                                            let _ = handle_tx.send(execution.abort_handle());
+                                            // A cancelled run ends with a JoinError; the
+                                            // board was already reset by the canceller.
+                                            let _ = rt.block_on(execution);
                                         });
+                                        if let Ok(handle) = handle_rx.recv() {
+                                            app.track_execution(task.id, handle);
+                                        }
                                     }
                                 }
                             }
@@ -326,22 +411,17 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                         }
                         KeyCode::Enter => {
                             if let Some(task_id) = app.get_selected_task().map(|t| t.id) {
-                                if let Some(task) = app
-                                    .board
-                                    .lock()
-                                    .unwrap()
-                                    .tasks
-                                    .iter_mut()
-                                    .find(|t| t.id == task_id)
-                                {
-                                    task.comment = Some(app.comment_input.clone());
-                                }
-                                store::save_board(&app.board.lock().unwrap()).unwrap();
+                                let comment = app.comment_input.clone();
+                                app.save_change(|board| {
+                                    if let Some(task) = board.task_mut(task_id) {
+                                        task.set_comment(comment, store::CommentAuthor::User);
+                                    }
+                                });
                             }
                             app.current_view = View::Board;
                         }
                         KeyCode::Backspace => {
-                            app.comment_input.pop();
+                            input::pop_grapheme(&mut app.comment_input);
                         }
                         KeyCode::Char(c) => {
                             app.comment_input.push(c);
@@ -358,28 +438,33 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                         }
                         KeyCode::Backspace => {
                             if app.editing_description {
-                                app.new_task_description.pop();
+                                input::pop_grapheme(&mut app.new_task_description);
                             } else {
-                                app.new_task_title.pop();
+                                input::pop_grapheme(&mut app.new_task_title);
                             }
                         }
                         KeyCode::Enter => {
                             if app.editing_description {
-                                let new_id = app.board.lock().unwrap().next_task_id();
-                                let task = Task {
-                                    id: new_id,
-                                    title: app.new_task_title.clone(),
-                                    description: if app.new_task_description.is_empty() {
-                                        None
-                                    } else {
-                                        Some(app.new_task_description.clone())
-                                    },
-                                    status: TaskStatus::ToDo,
-                                    agent_id: None,
-                                    comment: None,
+                                let title = app.new_task_title.clone();
+                                let description = if app.new_task_description.is_empty() {
+                                    None
+                                } else {
+                                    Some(app.new_task_description.clone())
                                 };
-                                app.board.lock().unwrap().tasks.push(task);
-                                store::save_board(&app.board.lock().unwrap()).unwrap();
+                                app.save_change(|board| {
+                                    let task = Task {
+                                        id: board.next_task_id(),
+                                        title,
+                                        description,
+                                        status: TaskStatus::ToDo,
+                                        agent_id: None,
+                                        comment: None,
+                                        comment_author: None,
+                                        estimate: None,
+                                        completed_at: None,
+                                    };
+                                    board.tasks.push(task);
+                                });
                                 app.current_view = View::Board;
                                 app.popup_scroll = 0;
                                 app.editing_description = false;
@@ -404,30 +489,26 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                         }
                         KeyCode::Backspace => {
                             if app.editing_description {
-                                app.new_task_description.pop();
+                                input::pop_grapheme(&mut app.new_task_description);
                             } else {
-                                app.new_task_title.pop();
+                                input::pop_grapheme(&mut app.new_task_title);
                             }
                         }
                         KeyCode::Enter => {
                             if app.editing_description {
                                 if let Some(task_id) = app.get_selected_task().map(|t| t.id) {
-                                    if let Some(task) = app
-                                        .board
-                                        .lock()
-                                        .unwrap()
-                                        .tasks
-                                        .iter_mut()
-                                        .find(|t| t.id == task_id)
-                                    {
-                                        task.title = app.new_task_title.clone();
-                                        task.description = if app.new_task_description.is_empty() {
-                                            None
-                                        } else {
-                                            Some(app.new_task_description.clone())
-                                        };
-                                    }
-                                    store::save_board(&app.board.lock().unwrap()).unwrap();
+                                    let title = app.new_task_title.clone();
+                                    let description = if app.new_task_description.is_empty() {
+                                        None
+                                    } else {
+                                        Some(app.new_task_description.clone())
+                                    };
+                                    app.save_change(|board| {
+                                        if let Some(task) = board.task_mut(task_id) {
+                                            task.title = title;
+                                            task.description = description;
+                                        }
+                                    });
                                 }
                                 app.current_view = View::Board;
                                 app.editing_description = false;
@@ -442,9 +523,19 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                         }
                         _ => {}
                     },
-                    View::Logs | View::Agents | View::Okrs | View::Commands => match key.code {
-                        KeyCode::Char('q' | '?') | KeyCode::Esc => {
-                            app.current_view = View::Board;
+                    View::Okrs if app.okr_tree_view => match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => app.close_popup(),
+                        KeyCode::Char('t') => app.toggle_okr_view(),
+                        KeyCode::Down => app.next_okr(),
+                        KeyCode::Up => app.prev_okr(),
+                        KeyCode::Enter | KeyCode::Char(' ') => {
+                            app.toggle_okr_expanded(app.selected_okr);
+                        }
+                        _ => {}
+                    },
+                    View::AgentDetail => match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => {
+                            app.current_view = View::TaskDescription;
                             app.popup_scroll = 0;
                         }
                         KeyCode::Down => {
@@ -455,8 +546,35 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
                         }
                         _ => {}
                     },
+                    View::Okrs if key.code == KeyCode::Char('t') => app.toggle_okr_view(),
+                    View::Logs | View::Agents | View::Okrs | View::Commands => match key.code {
+                        KeyCode::Char('q' | '?') | KeyCode::Esc => app.close_popup(),
+                        KeyCode::Down => {
+                            app.popup_scroll = app.popup_scroll.saturating_add(1);
+                        }
+                        KeyCode::Up => {
+                            app.popup_scroll = app.popup_scroll.saturating_sub(1);
+                        }
+                        _ => {}
+                    },
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mouse_capture_follows_tui_mouse_flag() {
+        let tui = |mouse| config::TuiResolved {
+            mouse,
+            ..Default::default()
+        };
+        assert!(mouse_capture_enabled(Ok(tui(true))));
+        assert!(!mouse_capture_enabled(Ok(tui(false))));
+        assert!(!mouse_capture_enabled(Err(anyhow::anyhow!("bad config"))));
+    }
+}
@@ -0,0 +1,51 @@
+//! Editing helpers for the TUI's text inputs.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Removes the last user-perceived character from `buffer`, so an emoji or a
+/// letter with combining accents is erased in one keypress.
+pub fn pop_grapheme(buffer: &mut String) -> Option<String> {
+    let (start, grapheme) = buffer.grapheme_indices(true).next_back()?;
+    let grapheme = grapheme.to_string();
+    buffer.truncate(start);
+    Some(grapheme)
+}
+
+/// Appends pasted `text` to a single-line input, turning line breaks into
+/// spaces since `Enter` submits the field.
+pub fn push_pasted(buffer: &mut String, text: &str) {
+    let text = text.replace("\r\n", " ");
+    buffer.extend(text.chars().map(|c| match c {
+        '\n' | '\r' => ' ',
+        c => c,
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pop_grapheme_removes_whole_cluster() {
+        // "e" followed by a combining acute accent.
+        let mut text = String::from("cafe\u{301}");
+        assert_eq!(pop_grapheme(&mut text).as_deref(), Some("e\u{301}"));
+        assert_eq!(text, "caf");
+
+        let mut family = String::from("hi 👨‍👩‍👧");
+        assert_eq!(pop_grapheme(&mut family).as_deref(), Some("👨‍👩‍👧"));
+        assert_eq!(family, "hi ");
+
+        let mut accented = String::from("Ü");
+        assert_eq!(pop_grapheme(&mut accented).as_deref(), Some("Ü"));
+        assert!(accented.is_empty());
+        assert_eq!(pop_grapheme(&mut accented), None);
+    }
+
+    #[test]
+    fn push_pasted_flattens_line_breaks() {
+        let mut text = String::from("Fix ");
+        push_pasted(&mut text, "login\r\nbug\nnow");
+        assert_eq!(text, "Fix login bug now");
+    }
+}
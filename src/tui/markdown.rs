@@ -0,0 +1,139 @@
+//! Minimal Markdown to ratatui conversion used for agent comments.
+//!
+//! Only a small subset is supported: ATX headings, bullet and numbered lists,
+//! and inline `**bold**`, `*italic*` and `` `code` `` spans. Anything else is
+//! rendered verbatim.
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+/// Converts Markdown text into styled lines.
+pub(crate) fn to_lines(text: &str) -> Vec<Line<'static>> {
+    text.lines().map(convert_line).collect()
+}
+
+fn convert_line(raw: &str) -> Line<'static> {
+    let trimmed = raw.trim_start();
+    let indent = raw.len() - trimmed.len();
+
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+        let mut style = Style::default().add_modifier(Modifier::BOLD);
+        if level == 1 {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        return Line::from(Span::styled(trimmed[level..].trim().to_string(), style));
+    }
+
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = trimmed.strip_prefix(marker) {
+            let mut spans = vec![Span::raw(format!("{}• ", " ".repeat(indent)))];
+            spans.extend(inline_spans(rest));
+            return Line::from(spans);
+        }
+    }
+
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        if let Some(rest) = trimmed[digits..].strip_prefix(". ") {
+            let mut spans = vec![Span::raw(format!(
+                "{}{}. ",
+                " ".repeat(indent),
+                &trimmed[..digits]
+            ))];
+            spans.extend(inline_spans(rest));
+            return Line::from(spans);
+        }
+    }
+
+    Line::from(inline_spans(raw))
+}
+
+fn inline_spans(text: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let styled = [
+            ("**", Style::default().add_modifier(Modifier::BOLD)),
+            ("`", Style::default().fg(Color::Cyan)),
+            ("*", Style::default().add_modifier(Modifier::ITALIC)),
+        ]
+        .into_iter()
+        .find_map(|(delim, style)| {
+            let inner = rest.strip_prefix(delim)?;
+            let end = inner.find(delim)?;
+            (end > 0).then(|| (inner[..end].to_string(), style, delim.len() * 2 + end))
+        });
+
+        if let Some((content, style, consumed)) = styled {
+            if !plain.is_empty() {
+                spans.push(Span::raw(std::mem::take(&mut plain)));
+            }
+            spans.push(Span::styled(content, style));
+            rest = &rest[consumed..];
+        } else {
+            let ch = rest.chars().next().unwrap_or_default();
+            plain.push(ch);
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+
+    if !plain.is_empty() || spans.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_of(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn headings_are_bold_without_markers() {
+        let lines = to_lines("# Summary\n## Details");
+        assert_eq!(text_of(&lines[0]), "Summary");
+        assert!(lines[0].spans[0]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD | Modifier::UNDERLINED));
+        assert_eq!(text_of(&lines[1]), "Details");
+        assert!(lines[1].spans[0]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn bullet_lists_use_bullet_glyph() {
+        let lines = to_lines("- first\n* second\n  + nested\n1. numbered");
+        assert_eq!(text_of(&lines[0]), "• first");
+        assert_eq!(text_of(&lines[1]), "• second");
+        assert_eq!(text_of(&lines[2]), "  • nested");
+        assert_eq!(text_of(&lines[3]), "1. numbered");
+    }
+
+    #[test]
+    fn inline_emphasis_is_styled() {
+        let lines = to_lines("Done **well** with `code`");
+        let spans = &lines[0].spans;
+        assert_eq!(text_of(&lines[0]), "Done well with code");
+        assert_eq!(spans[1].content, "well");
+        assert!(spans[1].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(spans[3].content, "code");
+        assert_eq!(spans[3].style.fg, Some(Color::Cyan));
+    }
+
+    #[test]
+    fn plain_text_is_unchanged() {
+        let lines = to_lines("2 * 3 = 6");
+        assert_eq!(text_of(&lines[0]), "2 * 3 = 6");
+    }
+}
@@ -2,6 +2,8 @@
 
 pub mod app;
 mod handlers;
+mod input;
+mod markdown;
 mod render;
 
 pub use handlers::run_tui;
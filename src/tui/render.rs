@@ -1,4 +1,8 @@
 use super::app::{App, View};
+use super::markdown;
+use crate::agent::Agent;
+use crate::config;
+use crate::logging;
 use crate::store::TaskStatus;
 use ratatui::{
     prelude::*,
@@ -6,7 +10,19 @@ use ratatui::{
     widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
 };
 
+/// Smallest terminal size the board layout is rendered at.
+const MIN_TERMINAL_WIDTH: u16 = 40;
+const MIN_TERMINAL_HEIGHT: u16 = 10;
+/// Popups are never shrunk below this size unless the terminal itself is smaller.
+const MIN_POPUP_WIDTH: u16 = 30;
+const MIN_POPUP_HEIGHT: u16 = 5;
+
 pub(crate) fn ui(f: &mut Frame, app: &mut App) {
+    let area = f.area();
+    if area.width < MIN_TERMINAL_WIDTH || area.height < MIN_TERMINAL_HEIGHT {
+        render_too_small(f, area);
+        return;
+    }
     render_board(f, app);
     match app.current_view {
         View::TaskDescription => render_task_description(f, app),
@@ -18,15 +34,70 @@ pub(crate) fn ui(f: &mut Frame, app: &mut App) {
         View::Agents => render_agents_list(f, app),
         View::Okrs => render_okrs(f, app),
         View::Commands => render_commands(f, app),
+        View::AgentDetail => render_agent_detail(f, app),
         _ => {}
     }
 }
 
+/// Marker shown before a task title: `⟳` while its agent is executing, `*`
+/// when an agent is assigned but idle, nothing otherwise.
+fn task_prefix(agent_id: Option<usize>, running_agents: &[usize]) -> &'static str {
+    match agent_id {
+        Some(id) if running_agents.contains(&id) => "⟳ ",
+        Some(_) => "* ",
+        None => "",
+    }
+}
+
+/// Shortens `text` to at most `width` characters, ending in `…` when cut.
+fn truncate_with_ellipsis(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut cut: String = text.chars().take(width - 1).collect();
+    cut.push('…');
+    cut
+}
+
+/// Column title with the selected position, e.g. `ToDo [3/12]`.
+fn column_title(status: &TaskStatus, selected: Option<usize>, len: usize) -> String {
+    match selected {
+        Some(index) if len > 0 => format!("{status:?} [{}/{len}]", index.min(len - 1) + 1),
+        _ => format!("{status:?} [{len}]"),
+    }
+}
+
+/// Title bar text: the board name when one was set at `init`.
+fn board_title(name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("Taskter — {name}"),
+        None => "Taskter".to_string(),
+    }
+}
+
 fn render_board(f: &mut Frame, app: &mut App) {
     let v_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .constraints(
+            [
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ]
+            .as_ref(),
+        )
         .split(f.area());
+    let title = board_title(app.board.lock().unwrap().name.as_deref());
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            title,
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        v_chunks[0],
+    );
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(
@@ -37,12 +108,14 @@ fn render_board(f: &mut Frame, app: &mut App) {
             ]
             .as_ref(),
         )
-        .split(v_chunks[0]);
+        .split(v_chunks[1]);
 
     for (i, status) in [TaskStatus::ToDo, TaskStatus::InProgress, TaskStatus::Done]
         .iter()
         .enumerate()
     {
+        // Leave room for the borders on both sides.
+        let width = usize::from(chunks[i].width.saturating_sub(2));
         let tasks: Vec<ListItem> = app
             .board
             .lock()
@@ -51,23 +124,18 @@ fn render_board(f: &mut Frame, app: &mut App) {
             .iter()
             .filter(|t| t.status == *status)
             .map(|t| {
-                let title = if let Some(id) = t.agent_id {
-                    if app.running_agents.contains(&id) {
-                        format!("▶ {}", t.title)
-                    } else {
-                        format!("* {}", t.title)
-                    }
-                } else {
-                    t.title.clone()
-                };
-                ListItem::new(title)
+                ListItem::new(truncate_with_ellipsis(
+                    &format!(
+                        "{}{}",
+                        task_prefix(t.agent_id, &app.running_agents),
+                        t.title
+                    ),
+                    width,
+                ))
             })
             .collect();
-        let mut list = List::new(tasks).block(
-            Block::default()
-                .title(format!("{status:?}"))
-                .borders(Borders::ALL),
-        );
+        let title = column_title(status, app.selected_task[i].selected(), tasks.len());
+        let mut list = List::new(tasks).block(Block::default().title(title).borders(Borders::ALL));
         if app.selected_column == i {
             list = list.highlight_style(
                 Style::default()
@@ -90,8 +158,13 @@ fn render_board(f: &mut Frame, app: &mut App) {
                 .join(", ")
         )
     };
-    let status = Paragraph::new(running_text);
-    f.render_widget(status, v_chunks[1]);
+    let status = match &app.board_error {
+        Some(err) => {
+            Paragraph::new(format!("{running_text} | {err}")).style(Style::default().fg(Color::Red))
+        }
+        None => Paragraph::new(running_text),
+    };
+    f.render_widget(status, v_chunks[2]);
 }
 
 fn render_task_description(f: &mut Frame, app: &mut App) {
@@ -105,14 +178,31 @@ fn render_task_description(f: &mut Frame, app: &mut App) {
         ];
 
         if let Some(agent_id) = task.agent_id {
-            text.push(Line::from(format!("Assigned to agent: {agent_id}")));
+            text.push(Line::from(format!(
+                "Assigned to agent: {agent_id} (g for details)"
+            )));
         }
 
         if let Some(comment) = &task.comment {
-            text.push(Line::from(Span::styled(
-                format!("Comment: {comment}"),
-                Style::default().fg(Color::Yellow),
-            )));
+            let render_markdown = config::tui()
+                .map(|cfg| cfg.render_markdown)
+                .unwrap_or(false);
+            let label = match task.comment_author {
+                Some(author) => format!("Comment ({author}):"),
+                None => "Comment:".to_string(),
+            };
+            if render_markdown {
+                text.push(Line::from(Span::styled(
+                    label,
+                    Style::default().fg(Color::Yellow),
+                )));
+                text.extend(markdown::to_lines(comment));
+            } else {
+                text.push(Line::from(Span::styled(
+                    format!("{label} {comment}"),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
         }
 
         let block = Block::default()
@@ -128,6 +218,26 @@ fn render_task_description(f: &mut Frame, app: &mut App) {
     }
 }
 
+/// Number of tool names shown before the rest are summarized as `+N`.
+const ASSIGN_TOOL_PREVIEW: usize = 3;
+
+/// Formats an agent for the assign popup as `prompt — model [tools]`.
+fn assign_agent_label(agent: &Agent) -> String {
+    let prompt = agent.system_prompt.lines().next().unwrap_or_default();
+    let mut tools: Vec<&str> = agent
+        .tools
+        .iter()
+        .take(ASSIGN_TOOL_PREVIEW)
+        .map(|t| t.name.as_str())
+        .collect();
+    let hidden = agent.tools.len().saturating_sub(ASSIGN_TOOL_PREVIEW);
+    let more = format!("+{hidden}");
+    if hidden > 0 {
+        tools.push(&more);
+    }
+    format!("{prompt} — {} [{}]", agent.model, tools.join(", "))
+}
+
 fn render_assign_agent(f: &mut Frame, app: &mut App) {
     if app.agents.is_empty() {
         let block = Block::default().title("Assign Agent").borders(Borders::ALL);
@@ -143,7 +253,7 @@ fn render_assign_agent(f: &mut Frame, app: &mut App) {
     let agents: Vec<ListItem> = app
         .agents
         .iter()
-        .map(|a| ListItem::new(a.system_prompt.as_str()))
+        .map(|a| ListItem::new(assign_agent_label(a)))
         .collect();
 
     let agent_list = List::new(agents)
@@ -159,6 +269,57 @@ fn render_assign_agent(f: &mut Frame, app: &mut App) {
     f.render_stateful_widget(agent_list, area, &mut app.agent_list_state);
 }
 
+fn render_agent_detail(f: &mut Frame, app: &mut App) {
+    let Some(agent) = app
+        .agent_list_state
+        .selected()
+        .and_then(|index| app.agents.get(index))
+    else {
+        return;
+    };
+    let status = if app.running_agents.contains(&agent.id) {
+        "running"
+    } else {
+        "idle"
+    };
+    let mut text = vec![
+        Line::from(Span::styled(
+            assign_agent_label(agent),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!("Status: {status}")),
+        Line::from(format!(
+            "Provider: {}",
+            crate::providers::resolve_provider_name(agent)
+        )),
+    ];
+    if let Some(schedule) = &agent.schedule {
+        let repeat = if agent.repeat { " (repeats)" } else { "" };
+        text.push(Line::from(format!("Schedule: {schedule}{repeat}")));
+    }
+    if let Some(workdir) = &agent.workdir {
+        text.push(Line::from(format!("Workdir: {}", workdir.display())));
+    }
+    text.push(Line::from("System prompt:"));
+    text.extend(
+        agent
+            .system_prompt
+            .lines()
+            .map(|l| Line::from(l.to_string())),
+    );
+
+    let block = Block::default()
+        .title(format!("Agent {}", agent.id))
+        .borders(Borders::ALL);
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .wrap(Wrap { trim: true })
+        .scroll((app.popup_scroll, 0));
+    let area = centered_rect(60, 40, f.area());
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
 fn render_add_comment(f: &mut Frame, app: &mut App) {
     let block = Block::default().title("Add Comment").borders(Borders::ALL);
     let paragraph = Paragraph::new(app.comment_input.as_str())
@@ -232,7 +393,7 @@ fn render_update_task(f: &mut Frame, app: &mut App) {
 
 fn render_logs(f: &mut Frame, app: &mut App) {
     let block = Block::default().title("Logs").borders(Borders::ALL);
-    let paragraph = Paragraph::new(app.logs.as_str())
+    let paragraph = Paragraph::new(logging::format_for_display(&app.logs))
         .block(block)
         .wrap(Wrap { trim: true })
         .scroll((app.popup_scroll, 0));
@@ -261,6 +422,10 @@ fn render_agents_list(f: &mut Frame, app: &mut App) {
 }
 
 fn render_okrs(f: &mut Frame, app: &mut App) {
+    if app.okr_tree_view {
+        render_okr_tree(f, app);
+        return;
+    }
     let mut lines = Vec::new();
     for okr in &app.okrs {
         lines.push(Line::from(Span::styled(
@@ -286,6 +451,40 @@ fn render_okrs(f: &mut Frame, app: &mut App) {
     f.render_widget(paragraph, area);
 }
 
+fn render_okr_tree(f: &mut Frame, app: &mut App) {
+    let mut lines = Vec::new();
+    for (i, okr) in app.okrs.iter().enumerate() {
+        let expanded = app.is_okr_expanded(i);
+        let marker = if expanded { "▾" } else { "▸" };
+        let mut style = Style::default().add_modifier(Modifier::BOLD);
+        if i == app.selected_okr {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        lines.push(Line::from(Span::styled(
+            format!("{marker} {}", okr.objective),
+            style,
+        )));
+        if expanded {
+            for kr in &okr.key_results {
+                lines.push(Line::from(format!(
+                    "    - {} ({:.0}%)",
+                    kr.name,
+                    kr.progress * 100.0
+                )));
+            }
+        }
+    }
+    let block = Block::default()
+        .title("OKRs (tree: Enter to expand, t for flat view)")
+        .borders(Borders::ALL);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    let area = centered_rect(60, 50, f.area());
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
 fn render_commands(f: &mut Frame, app: &mut App) {
     let lines = vec![
         Line::from("q - Quit"),
@@ -297,10 +496,12 @@ fn render_commands(f: &mut Frame, app: &mut App) {
         Line::from("d - Delete task"),
         Line::from("a - Assign agent"),
         Line::from("r - Unassign agent"),
+        Line::from("Esc - Cancel the running agent"),
         Line::from("c - Add comment"),
+        Line::from("g - Show the task's agent (from the task description)"),
         Line::from("L - View logs"),
         Line::from("A - List agents"),
-        Line::from("O - Show OKRs"),
+        Line::from("O - Show OKRs (t toggles tree view)"),
     ];
     let block = Block::default().title("Commands").borders(Borders::ALL);
     let paragraph = Paragraph::new(lines)
@@ -312,8 +513,33 @@ fn render_commands(f: &mut Frame, app: &mut App) {
     f.render_widget(paragraph, area);
 }
 
+fn render_too_small(f: &mut Frame, area: Rect) {
+    let message = Paragraph::new(format!(
+        "Terminal too small ({}x{}); resize to at least {MIN_TERMINAL_WIDTH}x{MIN_TERMINAL_HEIGHT}.",
+        area.width, area.height
+    ))
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
+    f.render_widget(message, area);
+}
+
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
+///
+/// The popup is kept at least `MIN_POPUP_WIDTH`x`MIN_POPUP_HEIGHT` where `r`
+/// allows it and never extends outside `r`.
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup = percent_rect(percent_x, percent_y, r);
+    let width = popup.width.max(MIN_POPUP_WIDTH).min(r.width);
+    let height = popup.height.max(MIN_POPUP_HEIGHT).min(r.height);
+    Rect {
+        x: r.x + (r.width - width) / 2,
+        y: r.y + (r.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+fn percent_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -332,3 +558,100 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contains(outer: Rect, inner: Rect) -> bool {
+        inner.x >= outer.x
+            && inner.y >= outer.y
+            && inner.right() <= outer.right()
+            && inner.bottom() <= outer.bottom()
+    }
+
+    #[test]
+    fn centered_rect_fits_tiny_area() {
+        let area = Rect::new(0, 0, 1, 1);
+        let popup = centered_rect(60, 25, area);
+        assert!(contains(area, popup));
+        assert!(popup.width <= 1 && popup.height <= 1);
+    }
+
+    #[test]
+    fn centered_rect_enforces_minimum_size() {
+        let area = Rect::new(5, 3, 50, 12);
+        let popup = centered_rect(60, 15, area);
+        assert!(contains(area, popup));
+        assert_eq!(popup.width, MIN_POPUP_WIDTH);
+        assert_eq!(popup.height, MIN_POPUP_HEIGHT);
+    }
+
+    #[test]
+    fn truncate_with_ellipsis_cuts_long_titles() {
+        assert_eq!(
+            truncate_with_ellipsis("Write the release notes", 10),
+            "Write the…"
+        );
+        assert_eq!(truncate_with_ellipsis("Short", 10), "Short");
+        assert_eq!(truncate_with_ellipsis("Exactly10!", 10), "Exactly10!");
+        assert_eq!(truncate_with_ellipsis("⟳ Überprüfen", 4), "⟳ Ü…");
+        assert_eq!(truncate_with_ellipsis("anything", 0), "");
+    }
+
+    #[test]
+    fn column_title_shows_scroll_position() {
+        assert_eq!(column_title(&TaskStatus::ToDo, Some(2), 12), "ToDo [3/12]");
+        assert_eq!(column_title(&TaskStatus::Done, None, 4), "Done [4]");
+        assert_eq!(
+            column_title(&TaskStatus::InProgress, Some(0), 0),
+            "InProgress [0]"
+        );
+    }
+
+    #[test]
+    fn board_title_includes_name() {
+        assert_eq!(board_title(Some("Apollo")), "Taskter — Apollo");
+        assert_eq!(board_title(None), "Taskter");
+    }
+
+    #[test]
+    fn task_prefix_marks_running_agents() {
+        let running = [2];
+        assert_eq!(task_prefix(Some(2), &running), "⟳ ");
+        assert_eq!(task_prefix(Some(3), &running), "* ");
+        assert_eq!(task_prefix(None, &running), "");
+    }
+
+    fn agent_with_tools(tools: &[&str]) -> Agent {
+        Agent {
+            id: 1,
+            system_prompt: "helper\nwith details".into(),
+            tools: tools
+                .iter()
+                .map(|name| crate::agent::FunctionDeclaration {
+                    name: (*name).into(),
+                    description: None,
+                    parameters: serde_json::json!({}),
+                })
+                .collect(),
+            model: "gpt-4.1".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn assign_agent_label_shows_model_and_tools() {
+        let agent = agent_with_tools(&["email", "run_bash"]);
+        assert_eq!(
+            assign_agent_label(&agent),
+            "helper — gpt-4.1 [email, run_bash]"
+        );
+
+        let agent = agent_with_tools(&["a", "b", "c", "d", "e"]);
+        assert_eq!(assign_agent_label(&agent), "helper — gpt-4.1 [a, b, c, +2]");
+    }
+}
@@ -26,6 +26,7 @@ fn save_agents_persists_to_disk() {
             provider: None,
             schedule: None,
             repeat: false,
+            ..Default::default()
         };
         agent::save_agents(std::slice::from_ref(&agent)).expect("save failed");
         let stored: Vec<Agent> =
@@ -52,6 +53,7 @@ fn list_agents_returns_saved_agents() {
             provider: None,
             schedule: None,
             repeat: false,
+            ..Default::default()
         };
         agent::save_agents(std::slice::from_ref(&agent)).unwrap();
         let listed = agent::list_agents().unwrap();
@@ -72,6 +74,7 @@ fn delete_agent_removes_entry() {
             provider: None,
             schedule: None,
             repeat: false,
+            ..Default::default()
         };
         let a2 = Agent {
             id: 2,
@@ -81,6 +84,7 @@ fn delete_agent_removes_entry() {
             provider: None,
             schedule: None,
             repeat: false,
+            ..Default::default()
         };
         agent::save_agents(&[a1.clone(), a2.clone()]).unwrap();
         agent::delete_agent(1).unwrap();
@@ -89,3 +93,58 @@ fn delete_agent_removes_entry() {
         assert_eq!(remaining[0].id, a2.id);
     });
 }
+
+#[test]
+fn unknown_tools_reports_missing_builtins() {
+    with_temp_dir(|| {
+        let agent = Agent {
+            id: 1,
+            system_prompt: "p".into(),
+            tools: vec![
+                taskter::tools::builtin_declaration("run_bash").unwrap(),
+                FunctionDeclaration {
+                    name: "renamed_tool".into(),
+                    description: None,
+                    parameters: serde_json::json!({}),
+                },
+            ],
+            model: "m".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            agent::unknown_tools(&agent),
+            vec!["renamed_tool".to_string()]
+        );
+    });
+}
+
+#[test]
+fn concurrent_running_guards_do_not_lose_updates() {
+    with_temp_dir(|| {
+        const AGENTS: usize = 8;
+        let started = std::sync::Barrier::new(AGENTS + 1);
+        let release = std::sync::Barrier::new(AGENTS + 1);
+
+        std::thread::scope(|scope| {
+            for id in 1..=AGENTS {
+                let (started, release) = (&started, &release);
+                scope.spawn(move || {
+                    let _guard = agent::RunningAgentGuard::new(id);
+                    started.wait();
+                    release.wait();
+                });
+            }
+
+            started.wait();
+            let mut running = agent::load_running_agents().unwrap();
+            running.sort_unstable();
+            assert_eq!(running, (1..=AGENTS).collect::<Vec<_>>());
+            release.wait();
+        });
+
+        assert!(agent::load_running_agents().unwrap().is_empty());
+    });
+}
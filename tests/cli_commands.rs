@@ -2,6 +2,7 @@ use assert_cmd::cargo::cargo_bin_cmd;
 use predicates::prelude::*;
 use serde_json::Value;
 use std::fs;
+use taskter::store::TaskStatus;
 
 mod common;
 pub use common::with_temp_dir;
@@ -347,6 +348,19 @@ fn update_agent_changes_configuration() {
             serde_json::from_str(&fs::read_to_string(taskter::config::AGENTS_FILE).unwrap())
                 .unwrap();
         assert!(agents[0]["provider"].is_null());
+
+        // allow failed runs to be retried
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "update", "--id", "1", "--retry-on-failure", "2"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Agent 1 updated."));
+
+        let agents: Vec<Value> =
+            serde_json::from_str(&fs::read_to_string(taskter::config::AGENTS_FILE).unwrap())
+                .unwrap();
+        assert_eq!(agents[0]["retry_on_failure"], 2);
+        assert_eq!(agents[0]["system_prompt"], "new helper");
     });
 }
 
@@ -468,3 +482,1710 @@ fn schedule_agent_updates_file() {
         assert!(agents[0]["schedule"].is_null());
     });
 }
+
+#[test]
+fn schedule_list_all_includes_unscheduled_agents() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        for prompt in ["scheduled", "idle"] {
+            cargo_bin_cmd!("taskter")
+                .args([
+                    "agent",
+                    "add",
+                    "--prompt",
+                    prompt,
+                    "--tools",
+                    "email",
+                    "--model",
+                    "gemini-2.5-flash",
+                ])
+                .assert()
+                .success();
+        }
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "schedule",
+                "set",
+                "--id",
+                "1",
+                "--cron",
+                "0 0 * * * *",
+            ])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "schedule", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("1: 0 0 * * * *"))
+            .stdout(predicate::str::contains("2: no schedule").not());
+
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "schedule", "list", "--all"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("1: 0 0 * * * *"))
+            .stdout(predicate::str::contains("2: no schedule"));
+    });
+}
+
+#[test]
+fn okrs_add_rejects_empty_objective() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        cargo_bin_cmd!("taskter")
+            .args(["okrs", "add", "-o", "   ", "-k", "Faster"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Objective cannot be empty"));
+
+        let okrs: Value =
+            serde_json::from_str(&fs::read_to_string(taskter::config::OKRS_FILE).unwrap()).unwrap();
+        assert!(okrs.as_array().unwrap().is_empty());
+    });
+}
+
+#[test]
+fn okrs_add_requires_key_results() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        cargo_bin_cmd!("taskter")
+            .args(["okrs", "add", "-o", "Improve UI"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "At least one key result is required",
+            ));
+    });
+}
+
+#[test]
+fn okrs_add_collapses_duplicate_key_results() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "okrs",
+                "add",
+                "-o",
+                " Improve UI ",
+                "-k",
+                "Faster",
+                " Faster ",
+                "Better",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("OKR added successfully"))
+            .stderr(predicate::str::contains("duplicate key result"));
+
+        let okrs: Value =
+            serde_json::from_str(&fs::read_to_string(taskter::config::OKRS_FILE).unwrap()).unwrap();
+        assert_eq!(okrs[0]["objective"], "Improve UI");
+        let key_results = okrs[0]["key_results"].as_array().unwrap();
+        assert_eq!(key_results.len(), 2);
+        assert_eq!(key_results[0]["name"], "Faster");
+        assert_eq!(key_results[1]["name"], "Better");
+    });
+}
+
+#[test]
+fn quiet_flag_suppresses_success_output() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        cargo_bin_cmd!("taskter")
+            .args(["--quiet", "task", "add", "--title", "Silent task"])
+            .assert()
+            .success()
+            .stdout(predicate::str::is_empty());
+
+        let board: Value =
+            serde_json::from_str(&fs::read_to_string(taskter::config::BOARD_FILE).unwrap())
+                .unwrap();
+        assert_eq!(board["tasks"][0]["title"], "Silent task");
+
+        // Data output is still printed in quiet mode.
+        cargo_bin_cmd!("taskter")
+            .args(["task", "list", "-q"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Silent task"));
+    });
+}
+
+#[test]
+fn task_update_patches_only_given_fields() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "-t", "Old title", "-d", "Keep me"])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("taskter")
+            .args(["task", "update", "--id", "1", "--title", "New title"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Task 1 updated."));
+
+        let board: Value =
+            serde_json::from_str(&fs::read_to_string(taskter::config::BOARD_FILE).unwrap())
+                .unwrap();
+        assert_eq!(board["tasks"][0]["title"], "New title");
+        assert_eq!(board["tasks"][0]["description"], "Keep me");
+
+        cargo_bin_cmd!("taskter")
+            .args(["task", "update", "--id", "1", "--clear-description"])
+            .assert()
+            .success();
+
+        let board: Value =
+            serde_json::from_str(&fs::read_to_string(taskter::config::BOARD_FILE).unwrap())
+                .unwrap();
+        assert_eq!(board["tasks"][0]["title"], "New title");
+        assert!(board["tasks"][0]["description"].is_null());
+    });
+}
+
+#[test]
+fn mutating_command_requires_init_unless_auto_init() {
+    with_temp_dir(|| {
+        fs::remove_dir_all(taskter::config::DIR).unwrap();
+
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "--title", "Orphan"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("taskter init"));
+        assert!(!std::path::Path::new(taskter::config::DIR).exists());
+
+        // Read-only commands still work without a board.
+        cargo_bin_cmd!("taskter")
+            .args(["task", "list"])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("taskter")
+            .args(["--auto-init", "task", "add", "--title", "Orphan"])
+            .assert()
+            .success();
+
+        let board: Value =
+            serde_json::from_str(&fs::read_to_string(taskter::config::BOARD_FILE).unwrap())
+                .unwrap();
+        assert_eq!(board["tasks"][0]["title"], "Orphan");
+    });
+}
+
+#[test]
+fn agent_prompt_can_be_read_from_file() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        let prompt = "You are a careful reviewer.\nAlways cite line numbers.\n";
+        fs::write("prompt.md", prompt).unwrap();
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--prompt-file",
+                "prompt.md",
+                "--tools",
+                "run_bash",
+                "--model",
+                "gemini-2.5-pro",
+            ])
+            .assert()
+            .success();
+
+        let agents: Vec<Value> =
+            serde_json::from_str(&fs::read_to_string(taskter::config::AGENTS_FILE).unwrap())
+                .unwrap();
+        assert_eq!(agents[0]["system_prompt"], prompt);
+
+        fs::write("prompt.md", "Updated prompt").unwrap();
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "update", "--id", "1", "--prompt-file", "prompt.md"])
+            .assert()
+            .success();
+
+        let agents: Vec<Value> =
+            serde_json::from_str(&fs::read_to_string(taskter::config::AGENTS_FILE).unwrap())
+                .unwrap();
+        assert_eq!(agents[0]["system_prompt"], "Updated prompt");
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--prompt",
+                "inline",
+                "--prompt-file",
+                "prompt.md",
+                "--tools",
+                "run_bash",
+                "--model",
+                "gemini-2.5-pro",
+            ])
+            .assert()
+            .failure();
+    });
+}
+
+#[test]
+fn execute_follow_streams_tool_logs() {
+    with_temp_dir(|| {
+        let mut server = mockito::Server::new();
+        let tool_call = |command: &str| {
+            serde_json::json!({
+                "message": {
+                    "role": "assistant",
+                    "tool_calls": [{
+                        "function": {"name": "run_bash", "arguments": {"command": command}}
+                    }]
+                }
+            })
+            .to_string()
+        };
+        let _first = server
+            .mock("POST", "/api/chat")
+            .with_body(tool_call("echo first"))
+            .expect(1)
+            .create();
+        let _second = server
+            .mock("POST", "/api/chat")
+            .with_body(tool_call("echo second"))
+            .expect(1)
+            .create();
+        let _done = server
+            .mock("POST", "/api/chat")
+            .with_body(r#"{"message": {"role": "assistant", "content": "All done"}}"#)
+            .expect(1)
+            .create();
+
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--prompt",
+                "helper",
+                "--tools",
+                "run_bash",
+                "--model",
+                "llama3",
+                "--provider",
+                "ollama",
+            ])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "--title", "Two steps"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "assign", "--task-id", "1", "--agent-id", "1"])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("taskter")
+            .env("TASKTER__PROVIDERS__OLLAMA__BASE_URL", server.url())
+            .args(["task", "execute", "--task-id", "1", "--follow"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Task 1 executed successfully."))
+            .stdout(predicate::str::contains(
+                "Agent finished after 2 tool calls.",
+            ))
+            .stderr(predicate::str::contains("calling tool run_bash"))
+            .stderr(predicate::str::contains(
+                "Tool run_bash responded with first",
+            ))
+            .stderr(predicate::str::contains(
+                "Tool run_bash responded with second",
+            ));
+    });
+}
+
+#[test]
+fn task_list_supports_limit_and_offset() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        for i in 1..=5 {
+            cargo_bin_cmd!("taskter")
+                .args(["task", "add", "--title", &format!("Task number {i}")])
+                .assert()
+                .success();
+        }
+
+        let output = cargo_bin_cmd!("taskter")
+            .args(["task", "list", "--limit", "2", "--offset", "1"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let listed: Vec<&str> = stdout
+            .lines()
+            .filter(|line| line.contains("Task number"))
+            .collect();
+        assert_eq!(listed, vec!["  [2] Task number 2", "  [3] Task number 3"]);
+        assert!(stdout.contains("Showing 2-3 of 5 tasks."));
+
+        // Without pagination flags no footer is printed.
+        cargo_bin_cmd!("taskter")
+            .args(["task", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Showing").not());
+    });
+}
+
+#[test]
+fn comments_record_their_author() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "--title", "Send email"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--prompt",
+                "email agent",
+                "--tools",
+                "email",
+                "--model",
+                "gemini-2.5-flash",
+            ])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "assign", "--task-id", "1", "--agent-id", "1"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "execute", "--task-id", "1"])
+            .assert()
+            .success();
+
+        let read_board = || -> Value {
+            serde_json::from_str(&fs::read_to_string(taskter::config::BOARD_FILE).unwrap()).unwrap()
+        };
+        assert_eq!(read_board()["tasks"][0]["comment_author"]["agent"], 1);
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "task",
+                "comment",
+                "--task-id",
+                "1",
+                "--comment",
+                "Looks good",
+            ])
+            .assert()
+            .success();
+        let board = read_board();
+        assert_eq!(board["tasks"][0]["comment"], "Looks good");
+        assert_eq!(board["tasks"][0]["comment_author"], "user");
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "task",
+                "comment",
+                "--task-id",
+                "1",
+                "--comment",
+                "Follow-up sent",
+                "--from-agent",
+                "1",
+            ])
+            .assert()
+            .success();
+        assert_eq!(read_board()["tasks"][0]["comment_author"]["agent"], 1);
+    });
+}
+
+#[test]
+fn execute_timeout_returns_task_to_todo() {
+    with_temp_dir(|| {
+        let mut server = mockito::Server::new();
+        let _slow = server
+            .mock("POST", "/api/chat")
+            .with_chunked_body(|w| {
+                std::thread::sleep(std::time::Duration::from_secs(5));
+                w.write_all(br#"{"message": {"role": "assistant", "content": "Too late"}}"#)
+            })
+            .create();
+
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--prompt",
+                "helper",
+                "--tools",
+                "run_bash",
+                "--model",
+                "llama3",
+                "--provider",
+                "ollama",
+            ])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "--title", "Slow"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "assign", "--task-id", "1", "--agent-id", "1"])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("taskter")
+            .env("TASKTER__PROVIDERS__OLLAMA__BASE_URL", server.url())
+            .args(["task", "execute", "--task-id", "1", "--timeout", "1"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Task 1 failed to execute."));
+
+        let board: Value =
+            serde_json::from_str(&fs::read_to_string(taskter::config::BOARD_FILE).unwrap())
+                .unwrap();
+        assert_eq!(board["tasks"][0]["status"], "ToDo");
+        assert_eq!(board["tasks"][0]["comment"], "execution timed out");
+        assert!(board["tasks"][0]["agent_id"].is_null());
+    });
+}
+
+#[test]
+fn agent_add_warns_about_unrecognized_model() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent", "add", "--prompt", "helper", "--tools", "run_bash", "--model", "gtp-4.1",
+            ])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Did you mean `gpt-4.1`?"));
+
+        let agents: Vec<Value> =
+            serde_json::from_str(&fs::read_to_string(taskter::config::AGENTS_FILE).unwrap())
+                .unwrap();
+        assert_eq!(agents[0]["model"], "gtp-4.1");
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent", "add", "--prompt", "helper", "--tools", "run_bash", "--model", "gpt4",
+            ])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Did you mean `gpt-4"));
+
+        let agents: Vec<Value> =
+            serde_json::from_str(&fs::read_to_string(taskter::config::AGENTS_FILE).unwrap())
+                .unwrap();
+        assert_eq!(agents[1]["model"], "gpt4");
+
+        // Recognized models and explicit providers stay quiet.
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent", "add", "--prompt", "helper", "--tools", "run_bash", "--model", "gpt-4o",
+            ])
+            .assert()
+            .success()
+            .stderr(predicate::str::is_empty());
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--prompt",
+                "helper",
+                "--tools",
+                "run_bash",
+                "--model",
+                "llama3",
+                "--provider",
+                "ollama",
+            ])
+            .assert()
+            .success()
+            .stderr(predicate::str::is_empty());
+    });
+}
+
+#[test]
+fn agent_add_validates_workdir() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        fs::create_dir("docs").unwrap();
+        let add = |workdir: &str| {
+            let mut cmd = cargo_bin_cmd!("taskter");
+            cmd.args([
+                "agent",
+                "add",
+                "--prompt",
+                "helper",
+                "--tools",
+                "project_files",
+                "--model",
+                "gemini-2.5-flash",
+                "--workdir",
+                workdir,
+            ]);
+            cmd
+        };
+
+        add("missing")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Agent workdir missing not found"));
+        add("..")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("outside the project directory"));
+        add("config.toml")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("is not a directory"));
+        let agents: Vec<Value> =
+            serde_json::from_str(&fs::read_to_string(taskter::config::AGENTS_FILE).unwrap())
+                .unwrap();
+        assert!(agents.is_empty());
+
+        add("docs").assert().success();
+        let agents: Vec<Value> =
+            serde_json::from_str(&fs::read_to_string(taskter::config::AGENTS_FILE).unwrap())
+                .unwrap();
+        assert_eq!(agents[0]["workdir"], "docs");
+    });
+}
+
+#[test]
+fn init_is_a_noop_on_existing_board_unless_forced() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "--title", "Keep me"])
+            .assert()
+            .success();
+        let board_path = std::path::Path::new(".taskter/board.json");
+        let board = fs::read_to_string(board_path).unwrap();
+
+        cargo_bin_cmd!("taskter")
+            .arg("init")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Skipped .taskter/board.json"))
+            .stdout(predicate::str::contains("already initialized"));
+        assert_eq!(fs::read_to_string(board_path).unwrap(), board);
+        assert!(!std::path::Path::new(".taskter/board.json.bak").exists());
+
+        cargo_bin_cmd!("taskter")
+            .args(["init", "--force"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Replaced .taskter/board.json"));
+        assert_eq!(
+            fs::read_to_string(".taskter/board.json.bak").unwrap(),
+            board
+        );
+        let reset: Value = serde_json::from_str(&fs::read_to_string(board_path).unwrap()).unwrap();
+        assert_eq!(reset["tasks"].as_array().unwrap().len(), 0);
+    });
+}
+
+#[test]
+fn board_file_override_is_accepted_after_subcommand() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "task",
+                "add",
+                "--title",
+                "Redirected",
+                "--board-file",
+                "alt.json",
+            ])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["--board-file", "alt.json", "task", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Redirected"));
+
+        let alt: Value = serde_json::from_str(&fs::read_to_string("alt.json").unwrap()).unwrap();
+        assert_eq!(alt["tasks"][0]["title"], "Redirected");
+        let default: Value =
+            serde_json::from_str(&fs::read_to_string(".taskter/board.json").unwrap()).unwrap();
+        assert!(default["tasks"].as_array().unwrap().is_empty());
+    });
+}
+
+#[test]
+fn schema_command_describes_task_status_values() {
+    with_temp_dir(|| {
+        let output = cargo_bin_cmd!("taskter")
+            .args(["schema", "task"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let schema: Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(
+            schema["$defs"]["TaskStatus"]["enum"],
+            serde_json::json!(["ToDo", "InProgress", "Done"])
+        );
+
+        cargo_bin_cmd!("taskter")
+            .args(["schema", "--out-dir", "schemas"])
+            .assert()
+            .success();
+        for name in ["board", "task", "agent", "okr", "function-declaration"] {
+            assert!(std::path::Path::new(&format!("schemas/{name}.schema.json")).exists());
+        }
+    });
+}
+
+#[test]
+fn agent_add_interactive_wizard_creates_agent() {
+    use expectrl::{Eof, Expect, Session};
+    use std::process::Command as PCommand;
+
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        let bin = cargo_bin_cmd!("taskter");
+        let mut cmd = PCommand::new(bin.get_program());
+        cmd.args(["agent", "add", "--interactive"]);
+        let mut session = Session::spawn(cmd).unwrap();
+
+        session.expect("System prompt: ").unwrap();
+        session.send_line("Release helper").unwrap();
+        session.expect("Tools (").unwrap();
+        session.send_line("run_bsh").unwrap();
+        session.expect("did you mean `run_bash`").unwrap();
+        session.send_line("run_bash, web_search").unwrap();
+        session.expect("Model (").unwrap();
+        session.send_line("gpt-4.1").unwrap();
+        session.expect("Provider (").unwrap();
+        session.send_line("openai").unwrap();
+        session.expect("Agent added successfully.").unwrap();
+        session.expect(Eof).unwrap();
+
+        let agents: Vec<Value> =
+            serde_json::from_str(&fs::read_to_string(taskter::config::AGENTS_FILE).unwrap())
+                .unwrap();
+        assert_eq!(agents[0]["system_prompt"], "Release helper");
+        assert_eq!(agents[0]["model"], "gpt-4.1");
+        assert_eq!(agents[0]["provider"], "openai");
+        let tools: Vec<&str> = agents[0]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(tools, ["run_bash", "web_search"]);
+
+        // Without a terminal the wizard refuses to run.
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "add", "--interactive"])
+            .write_stdin("ignored\n")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "--interactive requires a terminal",
+            ));
+    });
+}
+
+#[test]
+fn mcp_serve_reports_readiness_and_exits_cleanly_on_shutdown() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter")
+            .args(["mcp", "serve"])
+            .env("TASKTER_MCP_READY_LINE", "1")
+            .write_stdin("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"shutdown\"}\n")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains(
+                "taskter MCP ready, protocol 2025-06-18",
+            ))
+            .stdout(predicate::str::contains("\"id\":1"));
+
+        // A truncated frame is a transport error and must not look like a clean exit.
+        cargo_bin_cmd!("taskter")
+            .args(["mcp", "serve"])
+            .write_stdin("Content-Length: 50\r\n\r\n{\"a\"")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("taskter MCP ready").not());
+    });
+}
+
+#[test]
+fn show_velocity_reports_completed_and_remaining_points() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        for (title, estimate) in [("Small", "2"), ("Large", "5"), ("Open", "3")] {
+            cargo_bin_cmd!("taskter")
+                .args(["task", "add", "-t", title, "--estimate", estimate])
+                .assert()
+                .success();
+        }
+        for id in ["1", "2"] {
+            cargo_bin_cmd!("taskter")
+                .args(["task", "complete", "--id", id])
+                .assert()
+                .success();
+        }
+
+        let board: Value =
+            serde_json::from_str(&fs::read_to_string(taskter::config::BOARD_FILE).unwrap())
+                .unwrap();
+        assert!(board["tasks"][0]["completed_at"].is_string());
+        assert!(board["tasks"][2]["completed_at"].is_null());
+
+        cargo_bin_cmd!("taskter")
+            .args(["show", "velocity", "--days", "14"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Completed in the last 14 days: 7 points",
+            ))
+            .stdout(predicate::str::contains("Remaining: 3 points"));
+
+        cargo_bin_cmd!("taskter")
+            .args(["show", "velocity", "--days", &u32::MAX.to_string()])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(": 7 points"));
+    });
+}
+
+#[test]
+fn okrs_list_renders_markdown() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        fs::write(
+            taskter::config::OKRS_FILE,
+            r#"[{"objective":"Improve UI","key_results":[
+                {"name":"Faster","progress":0.4},
+                {"name":"Better","progress":1.0}]}]"#,
+        )
+        .unwrap();
+
+        cargo_bin_cmd!("taskter")
+            .args(["okrs", "list", "--format", "markdown"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("## Improve UI\n"))
+            .stdout(predicate::str::contains("- [ ] Faster (40%)"))
+            .stdout(predicate::str::contains("- [x] Better (100%)"));
+    });
+}
+
+#[test]
+fn task_list_reads_board_from_stdin() {
+    with_temp_dir(|| {
+        let board = r#"{"tasks":[
+            {"id":1,"title":"Piped task","description":null,"status":"ToDo","agent_id":null,"comment":null},
+            {"id":2,"title":"Finished task","description":null,"status":"Done","agent_id":null,"comment":null}]}"#;
+
+        cargo_bin_cmd!("taskter")
+            .args(["--board-file", "-", "task", "list"])
+            .write_stdin(board)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[1] Piped task"))
+            .stdout(predicate::str::contains("[2] Finished task"));
+
+        cargo_bin_cmd!("taskter")
+            .args(["--board-file", "-", "task", "complete", "--id", "1"])
+            .write_stdin(board)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("read from stdin"));
+    });
+}
+
+#[test]
+fn tools_call_reads_args_from_stdin() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter")
+            .args(["tools", "call", "run_python", "--args-stdin"])
+            .write_stdin(r#"{"code":"print(7)"}"#)
+            .assert()
+            .success()
+            .stdout("7\n");
+
+        cargo_bin_cmd!("taskter")
+            .args(["tools", "call", "run_python", "--args-stdin"])
+            .write_stdin("{not json")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid JSON tool arguments"));
+    });
+}
+
+#[test]
+fn description_uses_configured_line_endings() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        cargo_bin_cmd!("taskter")
+            .env("TASKTER__STORAGE__LINE_ENDINGS", "crlf")
+            .args(["description", "Line one\nLine two\r\nLine three"])
+            .assert()
+            .success();
+        let written = fs::read_to_string(taskter::config::DESCRIPTION_FILE).unwrap();
+        assert_eq!(written, "Line one\r\nLine two\r\nLine three");
+
+        cargo_bin_cmd!("taskter")
+            .args(["show", "description"])
+            .assert()
+            .success()
+            .stdout("Line one\r\nLine two\r\nLine three\n");
+
+        cargo_bin_cmd!("taskter")
+            .args(["description", &written])
+            .assert()
+            .success();
+        let written = fs::read_to_string(taskter::config::DESCRIPTION_FILE).unwrap();
+        assert_eq!(written, "Line one\nLine two\nLine three");
+    });
+}
+
+#[test]
+fn logs_prune_drops_old_entries_and_files() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        let recent = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        fs::write(
+            taskter::config::LOG_FILE,
+            format!(
+                "[2020-01-01 09:00:00] Old entry\ncontinued\n\
+                 {{\"timestamp\":\"2020-01-02 09:00:00\",\"message\":\"old json\"}}\n\
+                 [{recent}] Recent entry\n"
+            ),
+        )
+        .unwrap();
+        let responses = fs::File::create(taskter::config::RESPONSES_LOG_FILE).unwrap();
+        std::io::Write::write_all(&mut &responses, b"provider=ollama json={}\n").unwrap();
+        responses
+            .set_modified(
+                std::time::SystemTime::now() - std::time::Duration::from_secs(30 * 86_400),
+            )
+            .unwrap();
+        drop(responses);
+
+        cargo_bin_cmd!("taskter")
+            .args(["logs", "prune", "--older-than", "7d"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Pruned 2 log entries and 1 files"));
+
+        let logs = fs::read_to_string(taskter::config::LOG_FILE).unwrap();
+        assert_eq!(logs, format!("[{recent}] Recent entry\n"));
+        assert!(!std::path::Path::new(taskter::config::RESPONSES_LOG_FILE).exists());
+
+        cargo_bin_cmd!("taskter")
+            .args(["logs", "prune", "--older-than", "7x"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("invalid age"));
+
+        cargo_bin_cmd!("taskter")
+            .args(["logs", "prune", "--older-than", "9999999999999999d"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("too large"));
+
+        cargo_bin_cmd!("taskter")
+            .args(["logs", "prune", "--older-than", "100000000w"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("earliest supported date"));
+    });
+}
+
+#[test]
+fn assign_accepts_multiple_task_ids() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "add", "-p", "helper", "-m", "gemini-2.5-pro"])
+            .assert()
+            .success();
+        for title in ["One", "Two", "Three"] {
+            cargo_bin_cmd!("taskter")
+                .args(["task", "add", "-t", title])
+                .assert()
+                .success();
+        }
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "task",
+                "assign",
+                "--agent-id",
+                "1",
+                "--task-ids",
+                "1",
+                "2",
+                "3",
+                "9",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Agent 1 assigned to task 3."))
+            .stdout(predicate::str::contains("Task with id 9 not found."));
+
+        let board: Value =
+            serde_json::from_str(&fs::read_to_string(taskter::config::BOARD_FILE).unwrap())
+                .unwrap();
+        for task in board["tasks"].as_array().unwrap() {
+            assert_eq!(task["agent_id"], 1);
+        }
+
+        cargo_bin_cmd!("taskter")
+            .args(["task", "assign", "--agent-id", "5", "--task-id", "1"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Agent with id 5 not found."));
+    });
+}
+
+#[test]
+fn init_records_board_name_and_creation_time() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter")
+            .args(["init", "--name", "Apollo"])
+            .assert()
+            .success();
+        let board: Value =
+            serde_json::from_str(&fs::read_to_string(".taskter/board.json").unwrap()).unwrap();
+        assert_eq!(board["name"], "Apollo");
+        let created_at = board["created_at"].as_str().expect("created_at recorded");
+        assert!(chrono::DateTime::parse_from_rfc3339(created_at).is_ok());
+
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "--title", "First"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["show", "stats"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Board: Apollo"))
+            .stdout(predicate::str::contains("Created: "))
+            .stdout(predicate::str::contains("ToDo: 1"))
+            .stdout(predicate::str::contains("Total: 1"));
+    });
+}
+
+#[test]
+fn no_fallback_fails_a_run_without_api_key() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--prompt",
+                "mailer",
+                "--tools",
+                "send_email",
+                "--model",
+                "gemini-2.5-flash",
+            ])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "--title", "Send report"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "assign", "--task-id", "1", "--agent-id", "1"])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("taskter")
+            .args(["task", "execute", "--task-id", "1", "--no-fallback"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Task 1 failed to execute."));
+
+        let board: Value =
+            serde_json::from_str(&fs::read_to_string(".taskter/board.json").unwrap()).unwrap();
+        assert_eq!(board["tasks"][0]["status"], "ToDo");
+        assert!(board["tasks"][0]["comment"]
+            .as_str()
+            .unwrap()
+            .contains("simulation is disabled"));
+    });
+}
+
+#[test]
+fn repl_answers_each_turn_until_quit() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--prompt",
+                "mailer",
+                "--tools",
+                "send_email",
+                "--model",
+                "gemini-2.5-flash",
+            ])
+            .assert()
+            .success();
+
+        let out = cargo_bin_cmd!("taskter")
+            .args(["repl", "--agent-id", "1"])
+            .write_stdin("Draft a status email\n\nNow send it\n:quit\nNever read\n")
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("Chatting with agent 1"), "{output}");
+        assert_eq!(
+            output
+                .matches("Tool available. Task considered complete.")
+                .count(),
+            2,
+            "{output}"
+        );
+
+        cargo_bin_cmd!("taskter")
+            .args(["repl", "--agent-id", "9"])
+            .write_stdin(":quit\n")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Agent with id 9 not found."));
+    });
+}
+
+#[test]
+fn update_agent_sets_schedule_and_once() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--prompt",
+                "helper",
+                "--tools",
+                "email",
+                "--model",
+                "gemini-2.5-flash",
+            ])
+            .assert()
+            .success();
+
+        let read_agents = || -> Vec<Value> {
+            serde_json::from_str(&fs::read_to_string(taskter::config::AGENTS_FILE).unwrap())
+                .unwrap()
+        };
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "update",
+                "--id",
+                "1",
+                "--model",
+                "gemini-2.5-pro",
+                "--cron",
+                "0 * * * * *",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Agent 1 updated."));
+        let agents = read_agents();
+        assert_eq!(agents[0]["model"], "gemini-2.5-pro");
+        assert_eq!(agents[0]["schedule"], "0 * * * * *");
+        assert_eq!(agents[0]["repeat"], true);
+
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "update", "--id", "1", "--once"])
+            .assert()
+            .success();
+        let agents = read_agents();
+        assert_eq!(agents[0]["schedule"], "0 * * * * *");
+        assert_eq!(agents[0]["repeat"], false);
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent", "update", "--id", "1", "--prompt", "changed", "--cron", "not cron",
+            ])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid cron expression"));
+        assert_eq!(read_agents()[0]["system_prompt"], "helper");
+    });
+}
+
+#[test]
+fn show_commands_emit_json_on_request() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter")
+            .args(["init", "--name", "Apollo"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["description", "Land on the moon"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "-t", "Launch", "--estimate", "3"])
+            .assert()
+            .success();
+
+        let json_output = |what: &str| -> Value {
+            let out = cargo_bin_cmd!("taskter")
+                .args(["show", what, "--format", "json"])
+                .assert()
+                .success()
+                .get_output()
+                .stdout
+                .clone();
+            serde_json::from_slice(&out).unwrap()
+        };
+
+        assert_eq!(
+            json_output("description"),
+            serde_json::json!({"description": "Land on the moon"})
+        );
+
+        let stats = json_output("stats");
+        assert_eq!(stats["name"], "Apollo");
+        assert!(stats["created_at"].is_string());
+        assert_eq!(
+            stats["counts"],
+            serde_json::json!({"ToDo": 1, "InProgress": 0, "Done": 0})
+        );
+        assert_eq!(stats["total"], 1);
+
+        let velocity = json_output("velocity");
+        assert_eq!(
+            velocity,
+            serde_json::json!({"days": 7, "completed": 0, "remaining": 3})
+        );
+    });
+}
+
+#[test]
+fn agent_list_with_tasks_shows_workload() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        for prompt in ["busy", "idle"] {
+            cargo_bin_cmd!("taskter")
+                .args(["agent", "add", "-p", prompt, "-m", "gemini-2.5-pro"])
+                .assert()
+                .success();
+        }
+        for title in ["First", "Second", "Unassigned"] {
+            cargo_bin_cmd!("taskter")
+                .args(["task", "add", "-t", title])
+                .assert()
+                .success();
+        }
+        cargo_bin_cmd!("taskter")
+            .args(["task", "assign", "--task-ids", "1", "2", "--agent-id", "1"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "complete", "--id", "2"])
+            .assert()
+            .success();
+
+        let out = cargo_bin_cmd!("taskter")
+            .args(["agent", "list", "--with-tasks"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(out).unwrap();
+        let line = |prefix: &str| {
+            output
+                .lines()
+                .find(|l| l.starts_with(prefix))
+                .unwrap_or_else(|| panic!("no line for {prefix} in {output}"))
+                .to_string()
+        };
+        assert!(line("1: busy").ends_with("[2 tasks (1 done)]"), "{output}");
+        assert!(line("2: idle").ends_with("[0 tasks (0 done)]"), "{output}");
+
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("tasks (").not());
+    });
+}
+
+#[test]
+fn okrs_diff_marks_key_result_changes() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args(["okrs", "add", "-o", "Grow", "-k", "Users", "Docs"])
+            .assert()
+            .success();
+        let output = cargo_bin_cmd!("taskter")
+            .args(["okrs", "snapshot"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let name = String::from_utf8(output.stdout).unwrap();
+        let name = name
+            .trim()
+            .trim_start_matches("OKR snapshot ")
+            .trim_end_matches(" saved.");
+
+        let mut okrs: Value =
+            serde_json::from_str(&fs::read_to_string(taskter::config::OKRS_FILE).unwrap()).unwrap();
+        okrs[0]["key_results"][0]["progress"] = serde_json::json!(0.4);
+        fs::write(taskter::config::OKRS_FILE, okrs.to_string()).unwrap();
+
+        cargo_bin_cmd!("taskter")
+            .args(["okrs", "diff", name, "current", "--no-color"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("↑ Grow / Users: 0% -> 40% (+40%)"))
+            .stdout(predicate::str::contains("= Grow / Docs: 0% -> 0% (+0%)"))
+            .stdout(predicate::str::contains("\x1b[").not());
+    });
+}
+
+#[test]
+fn json_format_reports_errors_as_json() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        fs::write(taskter::config::BOARD_FILE, "{not json").unwrap();
+
+        let output = cargo_bin_cmd!("taskter")
+            .args(["show", "stats", "--format", "json"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stdout.is_empty());
+        let error: Value = serde_json::from_slice(&output.stderr).unwrap();
+        assert_eq!(error["code"], 1);
+        assert!(!error["error"].as_str().unwrap().is_empty());
+
+        cargo_bin_cmd!("taskter")
+            .args(["show", "stats"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::starts_with("Error: "));
+    });
+}
+
+#[test]
+fn task_move_transitions_tasks_in_the_source_status() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        for title in ["One", "Two", "Three"] {
+            cargo_bin_cmd!("taskter")
+                .args(["task", "add", "--title", title])
+                .assert()
+                .success();
+        }
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "task",
+                "move",
+                "--status-from",
+                "ToDo",
+                "--status-to",
+                "InProgress",
+                "--ids",
+                "1",
+                "2",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Moved 2 task(s) from ToDo to InProgress.",
+            ));
+        let board = taskter::store::load_board().unwrap();
+        let statuses: Vec<TaskStatus> = board.tasks.iter().map(|t| t.status.clone()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                TaskStatus::InProgress,
+                TaskStatus::InProgress,
+                TaskStatus::ToDo
+            ]
+        );
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "task",
+                "move",
+                "--status-from",
+                "todo",
+                "--status-to",
+                "in-progress",
+                "--ids",
+                "2",
+                "3",
+            ])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains(
+                "Warning: task 2 is InProgress, not ToDo; skipped.",
+            ))
+            .stdout(predicate::str::contains(
+                "Moved 1 task(s) from ToDo to InProgress.",
+            ));
+
+        cargo_bin_cmd!("taskter")
+            .args([
+                "task",
+                "move",
+                "--status-from",
+                "InProgress",
+                "--status-to",
+                "Done",
+                "--all",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Moved 3 task(s) from InProgress to Done.",
+            ));
+        let board = taskter::store::load_board().unwrap();
+        assert!(board
+            .tasks
+            .iter()
+            .all(|t| t.status == TaskStatus::Done && t.completed_at.is_some()));
+    });
+}
+
+#[test]
+fn agent_add_from_template_prefills_prompt_tools_and_model() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "add", "--from-template", "researcher"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--from-template",
+                "researcher",
+                "--model",
+                "gpt-4o",
+                "--tools",
+                "run_bash",
+            ])
+            .assert()
+            .success();
+
+        let agents: Value =
+            serde_json::from_str(&fs::read_to_string(".taskter/agents.json").unwrap()).unwrap();
+        let tool_names = |agent: &Value| -> Vec<String> {
+            agent["tools"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|t| t["name"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            tool_names(&agents[0]),
+            vec!["web_search", "get_project_description"]
+        );
+        assert_eq!(agents[0]["model"], "gemini-2.5-pro");
+        assert!(agents[0]["system_prompt"]
+            .as_str()
+            .unwrap()
+            .contains("research assistant"));
+        assert_eq!(tool_names(&agents[1]), vec!["run_bash"]);
+        assert_eq!(agents[1]["model"], "gpt-4o");
+
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "add", "--from-template", "poet"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "Available templates: coder, researcher, emailer",
+            ));
+    });
+}
+
+#[test]
+fn task_list_groups_tasks_by_agent() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent", "add", "--prompt", "helper", "--tools", "run_bash", "--model", "gpt-4o",
+            ])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent", "add", "--prompt", "writer", "--tools", "run_bash", "--model", "gpt-4o",
+            ])
+            .assert()
+            .success();
+        for title in ["Fix build", "Write docs", "Triage", "Release"] {
+            cargo_bin_cmd!("taskter")
+                .args(["task", "add", "--title", title])
+                .assert()
+                .success();
+        }
+        for (task, agent) in [("1", "2"), ("2", "1"), ("4", "2")] {
+            cargo_bin_cmd!("taskter")
+                .args(["task", "assign", "--task-id", task, "--agent-id", agent])
+                .assert()
+                .success();
+        }
+
+        let output = cargo_bin_cmd!("taskter")
+            .args(["task", "list", "--group-by", "agent"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "Agent 1:\n  [2] Write docs\n\nAgent 2:\n  [1] Fix build\n  [4] Release\n\nUnassigned:\n  [3] Triage\n"
+        );
+    });
+}
+
+#[test]
+fn show_board_prints_tasks_under_their_columns() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        for title in ["Write docs", "Fix parser", "Ship release"] {
+            cargo_bin_cmd!("taskter")
+                .args(["task", "add", "--title", title])
+                .assert()
+                .success();
+        }
+        cargo_bin_cmd!("taskter")
+            .args([
+                "task",
+                "move",
+                "--status-from",
+                "ToDo",
+                "--status-to",
+                "InProgress",
+            ])
+            .args(["--ids", "2"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "complete", "--id", "3"])
+            .assert()
+            .success();
+
+        let output = cargo_bin_cmd!("taskter")
+            .args(["show", "board", "--width", "90"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        let header = lines
+            .iter()
+            .find(|line| line.contains("ToDo [1]"))
+            .expect("header row");
+        let in_progress = header.find("InProgress [1]").expect("InProgress header");
+        let done = header.find("Done [1]").expect("Done header");
+        let column_of = |title: &str| {
+            let line = lines.iter().find(|line| line.contains(title)).unwrap();
+            let pos = line.find(title).unwrap();
+            if pos >= done {
+                "Done"
+            } else if pos >= in_progress {
+                "InProgress"
+            } else {
+                "ToDo"
+            }
+        };
+        assert_eq!(column_of("1: Write docs"), "ToDo");
+        assert_eq!(column_of("2: Fix parser"), "InProgress");
+        assert_eq!(column_of("3: Ship release"), "Done");
+        assert!(lines.iter().all(|line| line.chars().count() <= 90));
+    });
+}
+
+#[test]
+fn providers_list_shows_endpoints_and_masks_keys() {
+    with_temp_dir(|| {
+        let output = cargo_bin_cmd!("taskter")
+            .args(["providers", "list"])
+            .env("TASKTER__PROVIDERS__OPENAI__API_KEY", "sk-test-secret-abcd")
+            .env(
+                "TASKTER__PROVIDERS__OLLAMA__BASE_URL",
+                "http://ollama.local:11434",
+            )
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let output = String::from_utf8(output).unwrap();
+        for provider in ["openai", "gemini", "ollama"] {
+            assert!(
+                output.lines().any(|line| line == provider),
+                "{provider} missing from:\n{output}"
+            );
+        }
+        assert!(output.contains("api key: sk-…abcd"), "{output}");
+        assert!(!output.contains("secret"), "{output}");
+        assert!(output.contains("chat endpoint: http://ollama.local:11434/api/chat"));
+        assert!(output.contains("request style: auto (by model)"));
+    });
+}
+
+#[test]
+fn agent_add_expands_tool_patterns() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        for tools in ["taskter_*", "all"] {
+            cargo_bin_cmd!("taskter")
+                .args(["agent", "add", "--prompt", "helper", "--tools", tools])
+                .args(["--model", "gemini-2.5-pro"])
+                .assert()
+                .success();
+        }
+
+        let agents = taskter::agent::load_agents().unwrap();
+        let names = |id: usize| -> Vec<String> {
+            let agent = agents.iter().find(|a| a.id == id).unwrap();
+            let mut names: Vec<String> = agent.tools.iter().map(|t| t.name.clone()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            names(1),
+            vec![
+                "taskter_agent",
+                "taskter_okrs",
+                "taskter_task",
+                "taskter_tools"
+            ]
+        );
+
+        let mut every: Vec<String> = taskter::tools::builtin_names()
+            .into_iter()
+            .filter_map(taskter::tools::builtin_declaration)
+            .map(|decl| decl.name)
+            .collect();
+        every.sort();
+        every.dedup();
+        assert_eq!(names(2), every);
+
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "add", "--prompt", "helper", "--tools", "nothing_*"])
+            .args(["--model", "gemini-2.5-pro"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "No built-in tools match `nothing_*`",
+            ));
+    });
+}
+
+#[test]
+fn task_list_porcelain_prints_fixed_tab_separated_columns() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "add", "--prompt", "helper", "--tools", "run_bash"])
+            .args(["--model", "gemini-2.5-pro"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "--title", "Ship release", "--estimate", "3"])
+            .args(["--description", "Tag\tand\npublish"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "add", "--title", "Plain"])
+            .assert()
+            .success();
+        cargo_bin_cmd!("taskter")
+            .args(["task", "assign", "--task-id", "1", "--agent-id", "1"])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("taskter")
+            .args(["task", "list", "--porcelain"])
+            .assert()
+            .success()
+            .stdout("1\tToDo\t1\t3\tShip release\tTag\\tand\\npublish\n2\tToDo\t\t\tPlain\t\n");
+    });
+}
+
+#[test]
+fn agent_list_porcelain_prints_fixed_tab_separated_columns() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+        cargo_bin_cmd!("taskter")
+            .args([
+                "agent",
+                "add",
+                "--prompt",
+                "Review\tcode",
+                "--tools",
+                "run_bash",
+            ])
+            .args(["--tools", "get_description", "--model", "gpt-4.1"])
+            .assert()
+            .success();
+
+        cargo_bin_cmd!("taskter")
+            .args(["agent", "list", "--porcelain"])
+            .assert()
+            .success()
+            .stdout("1\topenai\tgpt-4.1\tno\trun_bash,get_project_description\tReview\\tcode\n");
+    });
+}
+
+#[test]
+fn scheduler_run_rejects_a_window_too_large_to_represent() {
+    with_temp_dir(|| {
+        cargo_bin_cmd!("taskter")
+            .args(["scheduler", "run", "--for", "9999999999999999d"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("too large"));
+    });
+}
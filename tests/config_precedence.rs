@@ -5,7 +5,7 @@ use std::path::PathBuf;
 use taskter::config::{self, ConfigOverrides};
 
 mod common;
-use common::with_temp_dir;
+use common::{disable_host_config_guard, with_temp_dir, EnvVarGuard};
 
 #[test]
 fn layering_respects_flag_env_and_file_order() {
@@ -35,3 +35,227 @@ fn layering_respects_flag_env_and_file_order() {
         assert_eq!(config::dir().expect("dir"), PathBuf::from("./from-config"));
     });
 }
+
+#[test]
+fn config_dir_fragments_merge_in_sorted_order() {
+    with_temp_dir(|| {
+        std::env::remove_var("TASKTER__PATHS__DATA_DIR");
+
+        let dir = PathBuf::from("conf.d");
+        std::fs::create_dir_all(&dir).expect("failed to create config dir");
+        std::fs::write(
+            dir.join("10-paths.toml"),
+            "[paths]\ndata_dir = \"./from-first\"\nboard_file = \"./first-board.json\"\n",
+        )
+        .expect("failed to write fragment");
+        std::fs::write(
+            dir.join("20-paths.toml"),
+            "[paths]\ndata_dir = \"./from-second\"\n",
+        )
+        .expect("failed to write fragment");
+        std::fs::write(dir.join("notes.txt"), "ignored").expect("failed to write note");
+
+        let mut overrides = ConfigOverrides {
+            config_dir: Some(dir.clone()),
+            ..ConfigOverrides::default()
+        };
+        config::init(&overrides).expect("init with config dir");
+        assert_eq!(config::dir().expect("dir"), PathBuf::from("./from-second"));
+        assert_eq!(
+            config::board_path().expect("board"),
+            PathBuf::from("./first-board.json")
+        );
+
+        let config_path = PathBuf::from("config.toml");
+        std::fs::write(&config_path, "[paths]\ndata_dir = \"./from-config\"\n")
+            .expect("failed to write config file");
+        overrides.config_file = Some(config_path);
+        config::init(&overrides).expect("init with config file and dir");
+        assert_eq!(config::dir().expect("dir"), PathBuf::from("./from-config"));
+        assert_eq!(
+            config::board_path().expect("board"),
+            PathBuf::from("./first-board.json")
+        );
+    });
+}
+
+#[test]
+fn config_home_override_relocates_default_config() {
+    with_temp_dir(|| {
+        let home = std::env::current_dir().expect("cwd").join("config-home");
+        std::fs::create_dir_all(&home).expect("failed to create config home");
+        std::fs::write(
+            home.join("config.toml"),
+            "[paths]\ndata_dir = \"./from-config-home\"\n",
+        )
+        .expect("failed to write config file");
+
+        let _home = EnvVarGuard::set("TASKTER_CONFIG_HOME", home.to_str().expect("utf-8 path"));
+        assert_eq!(
+            config::default_config_path(),
+            Some(home.join("config.toml"))
+        );
+
+        config::init(&ConfigOverrides::default()).expect("init with config home");
+        assert_eq!(
+            config::dir().expect("dir"),
+            PathBuf::from("./from-config-home")
+        );
+    });
+}
+
+#[test]
+fn api_key_is_read_from_file_unless_inline_key_is_set() {
+    with_temp_dir(|| {
+        let _host = disable_host_config_guard();
+        std::fs::write("openai.key", "sk-from-file\n").expect("failed to write key file");
+
+        let config_path = PathBuf::from("config.toml");
+        std::fs::write(
+            &config_path,
+            "[providers.openai]\napi_key_file = \"openai.key\"\n",
+        )
+        .expect("failed to write config file");
+        let overrides = ConfigOverrides {
+            config_file: Some(config_path.clone()),
+            ..ConfigOverrides::default()
+        };
+        config::init(&overrides).expect("init with key file");
+        assert_eq!(
+            config::openai().expect("openai").api_key.as_deref(),
+            Some("sk-from-file")
+        );
+
+        std::fs::write(
+            &config_path,
+            "[providers.openai]\napi_key = \"sk-inline\"\napi_key_file = \"openai.key\"\n",
+        )
+        .expect("failed to write config file");
+        config::force_reload().expect("reload with inline key");
+        assert_eq!(
+            config::openai().expect("openai").api_key.as_deref(),
+            Some("sk-inline")
+        );
+    });
+}
+
+#[test]
+fn default_provider_applies_to_unrecognized_models() {
+    with_temp_dir(|| {
+        let agent = taskter::agent::Agent {
+            id: 1,
+            system_prompt: "helper".into(),
+            tools: Vec::new(),
+            model: "in-house-model".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        };
+        assert_eq!(taskter::providers::resolve_provider_name(&agent), "gemini");
+
+        std::fs::write("config.toml", "[providers]\ndefault = \"openai\"\n")
+            .expect("failed to write config file");
+        config::force_reload().expect("reload with default provider");
+        assert_eq!(taskter::providers::resolve_provider_name(&agent), "openai");
+        assert_eq!(taskter::providers::select_provider(&agent).name(), "openai");
+
+        std::fs::write("config.toml", "[providers]\ndefault = \"nope\"\n")
+            .expect("failed to write config file");
+        assert!(config::force_reload().is_err());
+    });
+}
+
+#[test]
+fn profile_overrides_base_provider_key() {
+    with_temp_dir(|| {
+        let _host = disable_host_config_guard();
+        let config_path = PathBuf::from("config.toml");
+        std::fs::write(
+            &config_path,
+            "[providers.openai]\napi_key = \"sk-base\"\n\n\
+             [profiles.work.providers.openai]\napi_key = \"sk-work\"\n",
+        )
+        .expect("failed to write config file");
+
+        let mut overrides = ConfigOverrides {
+            config_file: Some(config_path.clone()),
+            ..ConfigOverrides::default()
+        };
+        config::init(&overrides).expect("init without profile");
+        assert_eq!(
+            config::openai().expect("openai").api_key.as_deref(),
+            Some("sk-base")
+        );
+
+        overrides.profile = Some("work".into());
+        config::init(&overrides).expect("init with profile flag");
+        assert_eq!(
+            config::openai().expect("openai").api_key.as_deref(),
+            Some("sk-work")
+        );
+
+        overrides.profile = None;
+        let _profile = EnvVarGuard::set("TASKTER_PROFILE", "work");
+        config::init(&overrides).expect("init with profile env");
+        assert_eq!(
+            config::openai().expect("openai").api_key.as_deref(),
+            Some("sk-work")
+        );
+    });
+}
+
+#[test]
+fn unknown_profile_is_an_error() {
+    with_temp_dir(|| {
+        let _host = disable_host_config_guard();
+        let config_path = PathBuf::from("config.toml");
+        std::fs::write(&config_path, "[profiles.work]\n").expect("failed to write config file");
+
+        let overrides = ConfigOverrides {
+            config_file: Some(config_path),
+            profile: Some("personal".into()),
+            ..ConfigOverrides::default()
+        };
+        let err = config::init(&overrides).unwrap_err();
+        assert!(
+            format!("{err:#}")
+                .contains("Unknown config profile 'personal'; available profiles: work"),
+            "{err:#}"
+        );
+        config::init(&ConfigOverrides::default()).expect("reset overrides");
+    });
+}
+
+#[test]
+fn env_file_populates_provider_keys() {
+    with_temp_dir(|| {
+        let _host = disable_host_config_guard();
+        std::env::remove_var("TASKTER__PROVIDERS__OPENAI__API_KEY");
+        let env_path = PathBuf::from("work.env");
+        std::fs::write(
+            &env_path,
+            "TASKTER__PROVIDERS__OPENAI__API_KEY=sk-from-env-file\n",
+        )
+        .expect("failed to write env file");
+
+        let overrides = ConfigOverrides {
+            env_file: Some(env_path),
+            ..ConfigOverrides::default()
+        };
+        let result = config::init(&overrides).and_then(|()| config::provider_api_key("openai"));
+        std::env::remove_var("TASKTER__PROVIDERS__OPENAI__API_KEY");
+        assert_eq!(
+            result.expect("provider key").as_deref(),
+            Some("sk-from-env-file")
+        );
+
+        let missing = ConfigOverrides {
+            env_file: Some(PathBuf::from("missing.env")),
+            ..ConfigOverrides::default()
+        };
+        let err = config::init(&missing).expect_err("missing env file should fail");
+        assert!(format!("{err:#}").contains("missing.env"), "{err:#}");
+        config::init(&ConfigOverrides::default()).expect("reset config");
+    });
+}
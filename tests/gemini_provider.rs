@@ -0,0 +1,122 @@
+use serde_json::json;
+
+use taskter::agent::{Agent, FunctionDeclaration};
+use taskter::providers::{gemini::GeminiProvider, ModelAction, ModelProvider};
+use taskter::tools::ToolOutput;
+
+fn base_agent() -> Agent {
+    Agent {
+        id: 7,
+        system_prompt: "You are helpful.".to_string(),
+        tools: vec![FunctionDeclaration {
+            name: "run_bash".to_string(),
+            description: Some("Execute a bash command and return its output".to_string()),
+            parameters: json!({
+                "type": "object",
+                "properties": {"command": {"type": "string"}},
+                "required": ["command"]
+            }),
+        }],
+        model: "gemini-2.5-pro".to_string(),
+        provider: Some("gemini".to_string()),
+        schedule: None,
+        repeat: false,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn sequential_tool_calls_keep_pairs_correlated() {
+    let provider = GeminiProvider;
+    let agent = base_agent();
+    let mut history = provider.build_history(&agent, "List files twice");
+
+    for (command, output) in [("ls", "a.txt"), ("ls -a", ".git a.txt")] {
+        let response = json!({
+            "candidates": [{
+                "content": {"parts": [{"functionCall": {"name": "run_bash", "args": {"command": command}}}]}
+            }]
+        });
+        let ModelAction::ToolCall {
+            name,
+            args,
+            call_id,
+        } = provider.parse_response(&response).unwrap()
+        else {
+            panic!("expected tool call");
+        };
+        assert!(call_id.is_none());
+        provider.append_tool_result(
+            &agent,
+            &mut history,
+            &name,
+            &args,
+            &output.into(),
+            call_id.as_deref(),
+        );
+    }
+
+    assert_eq!(history.len(), 5);
+    for (turn, expected_id) in [(1, "call_0"), (3, "call_1")] {
+        let call = &history[turn]["parts"][0]["functionCall"];
+        let response = &history[turn + 1]["parts"][0]["functionResponse"];
+        assert_eq!(call["id"], expected_id);
+        assert_eq!(response["id"], expected_id);
+        assert_eq!(call["name"], response["name"]);
+    }
+    assert_eq!(
+        history[3]["parts"][0]["functionCall"]["args"]["command"],
+        "ls -a"
+    );
+    assert_eq!(
+        history[4]["parts"][0]["functionResponse"]["response"]["content"],
+        ".git a.txt"
+    );
+}
+
+#[test]
+fn returned_call_ids_are_preserved() {
+    let provider = GeminiProvider;
+    let response = json!({
+        "candidates": [{
+            "content": {"parts": [{"functionCall": {"id": "abc", "name": "run_bash", "args": {}}}]}
+        }]
+    });
+    match provider.parse_response(&response).unwrap() {
+        ModelAction::ToolCall { call_id, .. } => assert_eq!(call_id.as_deref(), Some("abc")),
+        ModelAction::Text { .. } => panic!("expected tool call"),
+    }
+}
+
+#[test]
+fn json_tool_output_becomes_structured_function_response() {
+    let provider = GeminiProvider;
+    let agent = base_agent();
+    let mut history = Vec::new();
+    let output = ToolOutput::Json(json!({"rows": 2, "columns": ["a", "b"]}));
+    provider.append_tool_result(
+        &agent,
+        &mut history,
+        "run_bash",
+        &json!({"command": "ls"}),
+        &output,
+        Some("call_9"),
+    );
+    assert_eq!(
+        history[1]["parts"][0]["functionResponse"]["response"],
+        json!({"rows": 2, "columns": ["a", "b"]})
+    );
+
+    provider.append_tool_result(
+        &agent,
+        &mut history,
+        "run_bash",
+        &json!({"command": "ls"}),
+        &ToolOutput::Json(json!([1, 2])),
+        Some("call_10"),
+    );
+    assert_eq!(
+        history[3]["parts"][0]["functionResponse"]["response"],
+        json!({"content": [1, 2]})
+    );
+}
@@ -1,7 +1,7 @@
 #![allow(clippy::manual_string_new, clippy::used_underscore_binding)]
 
 use serde_json::json;
-use taskter::agent::{self, Agent, ExecutionResult, FunctionDeclaration};
+use taskter::agent::{self, Agent, ExecutionResult, FinalAction, FunctionDeclaration, RunStats};
 use taskter::store::{self, Board, KeyResult, Okr, Task, TaskStatus};
 
 mod common;
@@ -19,10 +19,12 @@ fn board_roundtrip_persists_tasks() {
             status: TaskStatus::ToDo,
             agent_id: None,
             comment: None,
+            ..Default::default()
         };
 
         let board = Board {
             tasks: vec![task.clone()],
+            ..Default::default()
         };
 
         // When
@@ -56,6 +58,84 @@ fn okr_roundtrip_persists_data() {
     });
 }
 
+#[test]
+fn save_okrs_clamps_progress_into_range() {
+    with_temp_dir(|| {
+        let okr = Okr {
+            objective: "Ship".to_string(),
+            key_results: vec![
+                KeyResult {
+                    name: "Overshoot".to_string(),
+                    progress: 1.5,
+                },
+                KeyResult {
+                    name: "Negative".to_string(),
+                    progress: -0.2,
+                },
+                KeyResult {
+                    name: "Valid".to_string(),
+                    progress: 0.4,
+                },
+            ],
+        };
+
+        store::save_okrs(&[okr]).expect("failed to save okrs");
+        let loaded = store::load_okrs().expect("failed to load okrs");
+
+        let progress: Vec<f32> = loaded[0].key_results.iter().map(|kr| kr.progress).collect();
+        assert_eq!(progress, vec![1.0, 0.0, 0.4]);
+        let log = std::fs::read_to_string(".taskter/logs.log").unwrap();
+        assert!(
+            log.contains("key result `Overshoot` is outside 0.0-1.0"),
+            "{log}"
+        );
+        assert!(!log.contains("`Valid`"), "{log}");
+    });
+}
+
+#[test]
+fn okr_snapshots_are_written_and_diffed() {
+    with_temp_dir(|| {
+        let okr = |progress: f32| Okr {
+            objective: "Ship".to_string(),
+            key_results: vec![
+                KeyResult {
+                    name: "Beta".to_string(),
+                    progress,
+                },
+                KeyResult {
+                    name: "Docs".to_string(),
+                    progress: 0.5,
+                },
+            ],
+        };
+
+        store::save_okrs(&[okr(0.2)]).expect("failed to save okrs");
+        let first = store::snapshot_okrs().expect("failed to snapshot");
+        let path = taskter::config::okr_history_dir()
+            .unwrap()
+            .join(format!("{first}.json"));
+        assert!(path.exists());
+
+        store::save_okrs(&[okr(0.7)]).expect("failed to save okrs");
+        let second = store::snapshot_okrs().expect("failed to snapshot");
+        assert_eq!(
+            store::list_okr_snapshots().unwrap(),
+            vec![first.clone(), second.clone()]
+        );
+
+        let deltas = store::diff_okrs(
+            &store::load_okr_snapshot(&first).unwrap(),
+            &store::load_okr_snapshot(&second).unwrap(),
+        );
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].key_result, "Beta");
+        assert_eq!(deltas[0].before, Some(0.2));
+        assert_eq!(deltas[0].after, Some(0.7));
+        assert!((deltas[0].change() - 0.5).abs() < 1e-6);
+    });
+}
+
 #[test]
 fn comment_roundtrip_persists_changes() {
     with_temp_dir(|| {
@@ -67,7 +147,9 @@ fn comment_roundtrip_persists_changes() {
                 status: TaskStatus::ToDo,
                 agent_id: None,
                 comment: None,
+                ..Default::default()
             }],
+            ..Default::default()
         };
 
         store::save_board(&board).expect("failed to save board");
@@ -99,6 +181,7 @@ async fn agent_executes_email_task_successfully() {
         provider: Some("gemini".into()),
         schedule: None,
         repeat: false,
+        ..Default::default()
     };
 
     let task = Task {
@@ -108,6 +191,7 @@ async fn agent_executes_email_task_successfully() {
         status: TaskStatus::ToDo,
         agent_id: Some(1),
         comment: None,
+        ..Default::default()
     };
 
     // When
@@ -119,6 +203,43 @@ async fn agent_executes_email_task_successfully() {
     assert!(matches!(result, ExecutionResult::Success { .. }));
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn run_agent_accepts_arbitrary_prompt() {
+    let _host_config_guard = disable_host_config_guard();
+    std::env::remove_var("TASKTER__PROVIDERS__GEMINI__API_KEY");
+    std::env::remove_var("GEMINI_API_KEY");
+    taskter::config::force_reload().expect("failed to clear test config state");
+    // Given
+    let agent = Agent {
+        id: 1,
+        system_prompt: "You are an email sender".into(),
+        tools: vec![FunctionDeclaration {
+            name: "send_email".into(),
+            description: Some("".into()),
+            parameters: json!({}),
+        }],
+        model: "gemini-2.5-flash".into(),
+        provider: Some("gemini".into()),
+        schedule: None,
+        repeat: false,
+        ..Default::default()
+    };
+
+    // When
+    let result = agent::run_agent(&agent, "Email the weekly report")
+        .await
+        .expect("run failed");
+
+    // Then
+    assert_eq!(
+        result,
+        ExecutionResult::Success {
+            comment: "Tool available. Task considered complete.".into(),
+            stats: RunStats::new(0, FinalAction::Simulated),
+        }
+    );
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn agent_execution_fails_without_tool() {
     let _host_config_guard = disable_host_config_guard();
@@ -131,6 +252,7 @@ async fn agent_execution_fails_without_tool() {
         provider: Some("gemini".into()),
         schedule: None,
         repeat: false,
+        ..Default::default()
     };
 
     let task = Task {
@@ -140,6 +262,7 @@ async fn agent_execution_fails_without_tool() {
         status: TaskStatus::ToDo,
         agent_id: Some(1),
         comment: None,
+        ..Default::default()
     };
 
     // When
@@ -166,6 +289,7 @@ async fn agent_execution_fails_on_network_error_without_tool() {
         provider: Some("gemini".into()),
         schedule: None,
         repeat: false,
+        ..Default::default()
     };
 
     let task = Task {
@@ -175,6 +299,7 @@ async fn agent_execution_fails_on_network_error_without_tool() {
         status: TaskStatus::ToDo,
         agent_id: Some(1),
         comment: None,
+        ..Default::default()
     };
 
     let result = agent::execute_task(&agent, Some(&task))
@@ -193,5 +318,831 @@ fn run_python_tool_executes_code() {
     let _host_config_guard = disable_host_config_guard();
     let result = taskter::tools::execute_tool("run_python", &json!({ "code": "print(40 + 2)" }))
         .expect("execution failed");
-    assert_eq!(result.trim(), "42");
+    assert_eq!(result.to_string().trim(), "42");
+}
+
+#[test]
+fn json_log_format_emits_parseable_events() {
+    with_temp_dir(|| {
+        let _format_guard = common::EnvVarGuard::set("TASKTER__LOGGING__FORMAT", "json");
+        taskter::config::force_reload().expect("failed to apply json log format");
+
+        let agent = Agent {
+            id: 7,
+            system_prompt: "You are an email sender".into(),
+            tools: vec![FunctionDeclaration {
+                name: "send_email".into(),
+                description: None,
+                parameters: json!({}),
+            }],
+            model: "gemini-2.5-flash".into(),
+            provider: Some("gemini".into()),
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        };
+        let task = Task {
+            id: 1,
+            title: "Send email".into(),
+            description: None,
+            status: TaskStatus::ToDo,
+            agent_id: Some(7),
+            comment: None,
+            ..Default::default()
+        };
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::execute_task(&agent, Some(&task)))
+            .expect("execution failed");
+        assert!(matches!(result, ExecutionResult::Success { .. }));
+
+        let contents = std::fs::read_to_string(taskter::config::LOG_FILE).unwrap();
+        let events: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("log line is not JSON"))
+            .collect();
+        assert!(!events.is_empty());
+        for event in &events {
+            assert_eq!(event["agent_id"], 7);
+            assert!(event["event"].is_string());
+        }
+        assert_eq!(events.last().unwrap()["event"], "finished");
+    });
+}
+
+#[test]
+fn webhook_receives_execution_outcome() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let mut server = mockito::Server::new();
+        let hook = server
+            .mock("POST", "/hook")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "agent_id": 3,
+                "task_id": 1,
+                "outcome": "success",
+            })))
+            .with_status(204)
+            .expect(1)
+            .create();
+        let _url_guard = common::EnvVarGuard::set(
+            "TASKTER__INTEGRATIONS__WEBHOOK_URL",
+            &format!("{}/hook", server.url()),
+        );
+        taskter::config::force_reload().expect("failed to apply webhook url");
+
+        let agent = Agent {
+            id: 3,
+            system_prompt: "You are an email sender".into(),
+            tools: vec![FunctionDeclaration {
+                name: "send_email".into(),
+                description: None,
+                parameters: json!({}),
+            }],
+            model: "gemini-2.5-flash".into(),
+            provider: Some("gemini".into()),
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        };
+        let task = Task {
+            id: 1,
+            title: "Send email".into(),
+            description: None,
+            status: TaskStatus::ToDo,
+            agent_id: Some(3),
+            comment: None,
+            ..Default::default()
+        };
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::execute_task(&agent, Some(&task)))
+            .expect("execution failed");
+        assert!(matches!(result, ExecutionResult::Success { .. }));
+        hook.assert();
+    });
+}
+
+#[test]
+fn run_agent_counts_tool_calls_before_final_text() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let mut server = mockito::Server::new();
+        let tool_call = |id: &str, command: &str| {
+            json!({"message": {"role": "assistant", "tool_calls": [{
+                "id": id,
+                "type": "function",
+                "function": {"name": "run_bash", "arguments": json!({"command": command}).to_string()}
+            }]}})
+            .to_string()
+        };
+        // Mockito serves matching mocks in creation order until each has its hit.
+        let first = server
+            .mock("POST", "/api/chat")
+            .with_body(tool_call("call_1", "echo one"))
+            .expect(1)
+            .create();
+        let second = server
+            .mock("POST", "/api/chat")
+            .with_body(tool_call("call_2", "echo two"))
+            .expect(1)
+            .create();
+        let last = server
+            .mock("POST", "/api/chat")
+            .with_body(json!({"message": {"role": "assistant", "content": "all done"}}).to_string())
+            .expect(1)
+            .create();
+        let _url_guard =
+            common::EnvVarGuard::set("TASKTER__PROVIDERS__OLLAMA__BASE_URL", &server.url());
+        taskter::config::force_reload().expect("failed to apply ollama url");
+
+        let agent = Agent {
+            id: 5,
+            system_prompt: "Run commands".into(),
+            tools: vec![taskter::tools::run_bash::declaration()],
+            model: "ollama:llama3".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::run_agent(&agent, "Say hello twice"))
+            .expect("run failed");
+
+        assert_eq!(
+            result,
+            ExecutionResult::Success {
+                comment: "all done".into(),
+                stats: RunStats::new(2, FinalAction::Text),
+            }
+        );
+        first.assert();
+        second.assert();
+        last.assert();
+    });
+}
+
+#[test]
+fn execute_task_retries_after_tool_failure() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let mut server = mockito::Server::new();
+        let failing = server
+            .mock("POST", "/api/chat")
+            .with_body(
+                json!({"message": {"role": "assistant", "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "run_bash", "arguments": "{\"command\":\"exit 1\"}"}
+                }]}})
+                .to_string(),
+            )
+            .expect(1)
+            .create();
+        let retried = server
+            .mock("POST", "/api/chat")
+            .with_body(
+                json!({"message": {"role": "assistant", "content": "recovered"}}).to_string(),
+            )
+            .expect(1)
+            .create();
+        let _url_guard =
+            common::EnvVarGuard::set("TASKTER__PROVIDERS__OLLAMA__BASE_URL", &server.url());
+        taskter::config::force_reload().expect("failed to apply ollama url");
+
+        let agent = Agent {
+            id: 6,
+            system_prompt: "Run commands".into(),
+            tools: vec![taskter::tools::run_bash::declaration()],
+            model: "ollama:llama3".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            retry_on_failure: 1,
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::execute_task(&agent, None))
+            .expect("execution failed");
+
+        assert_eq!(
+            result,
+            ExecutionResult::Success {
+                comment: "recovered".into(),
+                stats: RunStats::new(0, FinalAction::Text),
+            }
+        );
+        failing.assert();
+        retried.assert();
+        let log = std::fs::read_to_string(".taskter/logs.log").unwrap();
+        assert!(log.contains("retrying (attempt 2 of 2)"), "{log}");
+    });
+}
+
+#[test]
+fn execute_task_does_not_retry_invalid_tool_arguments() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let mut server = mockito::Server::new();
+        let call = server
+            .mock("POST", "/api/chat")
+            .with_body(
+                json!({"message": {"role": "assistant", "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "run_bash", "arguments": "{}"}
+                }]}})
+                .to_string(),
+            )
+            .expect(1)
+            .create();
+        let _url_guard =
+            common::EnvVarGuard::set("TASKTER__PROVIDERS__OLLAMA__BASE_URL", &server.url());
+        taskter::config::force_reload().expect("failed to apply ollama url");
+
+        let agent = Agent {
+            id: 7,
+            system_prompt: "Run commands".into(),
+            tools: vec![taskter::tools::run_bash::declaration()],
+            model: "ollama:llama3".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            retry_on_failure: 2,
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::execute_task(&agent, None))
+            .expect("execution failed");
+
+        assert_eq!(
+            result,
+            ExecutionResult::Failure {
+                comment: "Tool run_bash failed: command missing".into(),
+                stats: RunStats::new(1, FinalAction::ToolInvalid),
+            }
+        );
+        assert!(!result.is_retriable());
+        call.assert();
+    });
+}
+
+/// Runs an Ollama agent that calls `run_bash` once with `touch ran.txt`,
+/// answering the confirmation prompt with `answer`. The second model request
+/// must contain `expected_result` for the mock to match.
+fn run_with_confirmation_answer(answer: &'static str, expected_result: &str) -> ExecutionResult {
+    let mut server = mockito::Server::new();
+    let call = server
+        .mock("POST", "/api/chat")
+        .with_body(
+            json!({"message": {"role": "assistant", "tool_calls": [{
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "run_bash", "arguments": "{\"command\":\"touch ran.txt\"}"}
+            }]}})
+            .to_string(),
+        )
+        .expect(1)
+        .create();
+    let reply = server
+        .mock("POST", "/api/chat")
+        .match_body(mockito::Matcher::Regex(expected_result.into()))
+        .with_body(json!({"message": {"role": "assistant", "content": "done"}}).to_string())
+        .expect(1)
+        .create();
+    let _url_guard =
+        common::EnvVarGuard::set("TASKTER__PROVIDERS__OLLAMA__BASE_URL", &server.url());
+    taskter::config::force_reload().expect("failed to apply ollama url");
+
+    let prompts = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = std::sync::Arc::clone(&prompts);
+    agent::set_confirmation(Some(Box::new(move |name, args| {
+        let mut output = Vec::new();
+        let confirmed =
+            agent::ask_confirmation(&mut answer.as_bytes(), &mut output, name, args).unwrap();
+        seen.lock()
+            .unwrap()
+            .push(String::from_utf8(output).unwrap());
+        confirmed
+    })));
+
+    let agent = Agent {
+        id: 8,
+        system_prompt: "Run commands".into(),
+        tools: vec![taskter::tools::run_bash::declaration()],
+        model: "ollama:llama3".into(),
+        provider: None,
+        schedule: None,
+        repeat: false,
+        ..Default::default()
+    };
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let result = rt.block_on(agent::run_agent(&agent, "Create a marker file"));
+    agent::set_confirmation(None);
+
+    let prompts = prompts.lock().unwrap();
+    assert_eq!(prompts.len(), 1);
+    assert!(
+        prompts[0].contains("Agent wants to run run_bash"),
+        "{}",
+        prompts[0]
+    );
+    call.assert();
+    reply.assert();
+    result.expect("run failed")
+}
+
+#[test]
+fn confirmed_tool_call_runs() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let result = run_with_confirmation_answer("y\n", "touch ran.txt");
+        assert!(matches!(result, ExecutionResult::Success { .. }));
+        assert!(std::path::Path::new("ran.txt").exists());
+    });
+}
+
+#[test]
+fn declined_tool_call_returns_user_declined_result() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let result = run_with_confirmation_answer("n\n", "User declined to run run_bash");
+        assert!(matches!(result, ExecutionResult::Success { .. }));
+        assert!(!std::path::Path::new("ran.txt").exists());
+    });
+}
+
+#[test]
+fn disabled_simulation_fails_without_api_key() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let _fallback_guard =
+            common::EnvVarGuard::set("TASKTER__PROVIDERS__ALLOW_SIMULATION", "false");
+        taskter::config::force_reload().expect("failed to disable simulation");
+
+        let agent = Agent {
+            id: 9,
+            system_prompt: "You are an email sender".into(),
+            tools: vec![taskter::tools::builtin_declaration("send_email").unwrap()],
+            model: "gemini-2.5-flash".into(),
+            provider: Some("gemini".into()),
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::execute_task(&agent, None))
+            .expect("execution failed");
+
+        assert_eq!(
+            result,
+            ExecutionResult::Failure {
+                comment: "No API key configured for provider gemini; simulation is disabled".into(),
+                stats: RunStats::new(0, FinalAction::MissingApiKey),
+            }
+        );
+    });
+}
+
+#[test]
+fn conversation_sends_earlier_turns_with_each_message() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let mut server = mockito::Server::new();
+        let reply = |content: &str| {
+            json!({"message": {"role": "assistant", "content": content}}).to_string()
+        };
+        let first = server
+            .mock("POST", "/api/chat")
+            .with_body(reply("Paris"))
+            .expect(1)
+            .create();
+        let second = server
+            .mock("POST", "/api/chat")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "messages": [
+                    {"role": "system", "content": "Answer briefly"},
+                    {"role": "user", "content": "Capital of France?"},
+                    {"role": "assistant", "content": "Paris"},
+                    {"role": "user", "content": "And of Italy?"}
+                ]
+            })))
+            .with_body(reply("Rome"))
+            .expect(1)
+            .create();
+        let _url_guard =
+            common::EnvVarGuard::set("TASKTER__PROVIDERS__OLLAMA__BASE_URL", &server.url());
+        taskter::config::force_reload().expect("failed to apply ollama url");
+
+        let agent = Agent {
+            id: 6,
+            system_prompt: "Answer briefly".into(),
+            tools: vec![],
+            model: "ollama:llama3".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mut conversation = agent::Conversation::new(agent).expect("conversation");
+        for (message, expected) in [("Capital of France?", "Paris"), ("And of Italy?", "Rome")] {
+            let result = rt
+                .block_on(conversation.send(message))
+                .expect("turn failed");
+            assert_eq!(
+                result,
+                ExecutionResult::Success {
+                    comment: expected.into(),
+                    stats: RunStats::new(0, FinalAction::Text),
+                }
+            );
+        }
+        first.assert();
+        second.assert();
+    });
+}
+
+#[test]
+fn system_prompt_variables_are_expanded_before_the_request() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        std::fs::write(
+            taskter::config::description_path().unwrap(),
+            "Build a moon base\n",
+        )
+        .unwrap();
+        let task = |id, status| Task {
+            id,
+            title: format!("Task {id}"),
+            description: None,
+            status,
+            agent_id: None,
+            comment: None,
+            ..Default::default()
+        };
+        store::save_board(&Board {
+            tasks: vec![task(1, TaskStatus::ToDo), task(2, TaskStatus::Done)],
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut server = mockito::Server::new();
+        let chat = server
+            .mock("POST", "/api/chat")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "messages": [{
+                    "role": "system",
+                    "content": "Project: Build a moon base\nBoard: 2 tasks: 1 to do, 0 in progress, 1 done\nKeep {{unknown}}"
+                }]
+            })))
+            .with_body(json!({"message": {"role": "assistant", "content": "ok"}}).to_string())
+            .expect(1)
+            .create();
+        let _url_guard =
+            common::EnvVarGuard::set("TASKTER__PROVIDERS__OLLAMA__BASE_URL", &server.url());
+        taskter::config::force_reload().expect("failed to apply ollama url");
+
+        let agent = Agent {
+            id: 8,
+            system_prompt:
+                "Project: {{project_description}}\nBoard: {{board_summary}}\nKeep {{unknown}}"
+                    .into(),
+            tools: vec![],
+            model: "ollama:llama3".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::run_agent(&agent, "Status?"))
+            .expect("run failed");
+        assert!(
+            matches!(result, ExecutionResult::Success { .. }),
+            "{result:?}"
+        );
+        chat.assert();
+        assert_eq!(
+            agent::expand_prompt_variables("No placeholders here"),
+            "No placeholders here"
+        );
+    });
+}
+
+#[test]
+fn execute_task_stops_when_execution_budget_is_spent() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let mut server = mockito::Server::new();
+        let looping = server
+            .mock("POST", "/api/chat")
+            .with_body(
+                json!({"message": {"role": "assistant", "tool_calls": [{
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "run_bash", "arguments": "{\"command\":\"sleep 0.3\"}"}
+                }]}})
+                .to_string(),
+            )
+            .expect_at_least(2)
+            .create();
+        let _url_guard =
+            common::EnvVarGuard::set("TASKTER__PROVIDERS__OLLAMA__BASE_URL", &server.url());
+        taskter::config::force_reload().expect("failed to apply ollama url");
+
+        let agent = Agent {
+            id: 7,
+            system_prompt: "Run commands".into(),
+            tools: vec![taskter::tools::run_bash::declaration()],
+            model: "ollama:llama3".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            execution_budget_secs: Some(1),
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::execute_task(&agent, None))
+            .expect("execution failed");
+
+        match result {
+            ExecutionResult::Failure { comment, stats } => {
+                assert_eq!(comment, "execution budget exceeded");
+                assert_eq!(stats.final_action, FinalAction::Interrupted);
+                assert!(stats.tool_calls >= 2, "{stats:?}");
+            }
+            ExecutionResult::Success { .. } => panic!("expected the budget to stop the run"),
+        }
+        looping.assert();
+    });
+}
+
+#[test]
+fn execution_budget_cancels_a_model_request_that_never_answers() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            // Hold every connection open without answering.
+            let _connections: Vec<_> = listener.incoming().collect();
+        });
+        let _url_guard = common::EnvVarGuard::set("TASKTER__PROVIDERS__OLLAMA__BASE_URL", &url);
+        taskter::config::force_reload().expect("failed to apply ollama url");
+
+        let agent = Agent {
+            id: 7,
+            system_prompt: "Answer".into(),
+            tools: Vec::new(),
+            model: "ollama:llama3".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            execution_budget_secs: Some(1),
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let started = std::time::Instant::now();
+        let result = rt
+            .block_on(agent::execute_task(&agent, None))
+            .expect("execution failed");
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        match result {
+            ExecutionResult::Failure { comment, stats } => {
+                assert_eq!(comment, "execution budget exceeded");
+                assert_eq!(stats.final_action, FinalAction::Interrupted);
+            }
+            ExecutionResult::Success { .. } => panic!("expected the budget to stop the run"),
+        }
+    });
+}
+
+#[test]
+fn execution_budget_too_large_for_a_deadline_is_no_limit() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let mut server = mockito::Server::new();
+        let chat = server
+            .mock("POST", "/api/chat")
+            .with_body(json!({"message": {"role": "assistant", "content": "done"}}).to_string())
+            .create();
+        let _url_guard =
+            common::EnvVarGuard::set("TASKTER__PROVIDERS__OLLAMA__BASE_URL", &server.url());
+        taskter::config::force_reload().expect("failed to apply ollama url");
+
+        let agent = Agent {
+            id: 7,
+            system_prompt: "Answer".into(),
+            tools: Vec::new(),
+            model: "ollama:llama3".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            execution_budget_secs: Some(u64::MAX),
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::execute_task(&agent, None))
+            .expect("execution failed");
+
+        assert!(
+            matches!(result, ExecutionResult::Success { .. }),
+            "{result:?}"
+        );
+        chat.assert();
+    });
+}
+
+#[test]
+fn held_board_lock_makes_other_writers_wait_or_fail() {
+    use std::time::{Duration, Instant};
+
+    with_temp_dir(|| {
+        let board = Board {
+            tasks: vec![Task {
+                id: 1,
+                title: "Ship".to_string(),
+                description: None,
+                status: TaskStatus::ToDo,
+                agent_id: None,
+                comment: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        store::save_board(&board).expect("failed to save board");
+
+        let lock = store::lock_board().expect("failed to lock board");
+        let err = store::lock_board_within(Duration::from_millis(150))
+            .err()
+            .expect("second lock must not be granted");
+        assert!(
+            err.to_string()
+                .contains("board is locked by another process"),
+            "{err}"
+        );
+
+        let holder = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            drop(lock);
+        });
+        let started = Instant::now();
+        store::update_board(|board| board.move_task(1, TaskStatus::Done))
+            .expect("update should wait for the lock");
+        assert!(started.elapsed() >= Duration::from_millis(150));
+        holder.join().unwrap();
+
+        let board = store::load_board().unwrap();
+        assert_eq!(board.tasks[0].status, TaskStatus::Done);
+        assert!(!std::path::Path::new(".taskter/board.json.tmp").exists());
+
+        // The lock file stays behind, as after a crash, without blocking writers.
+        assert!(std::path::Path::new(".taskter/board.lock").exists());
+        let _lock =
+            store::lock_board_within(Duration::ZERO).expect("a released lock must be granted");
+    });
+}
+
+#[test]
+fn simulate_tools_runs_a_lone_read_only_tool_offline() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let _key_guard = common::EnvVarGuard::set("GEMINI_API_KEY", "");
+        let _dry_guard = common::EnvVarGuard::set("TASKTER__PROVIDERS__SIMULATE_TOOLS", "true");
+        taskter::config::force_reload().expect("failed to enable tool simulation");
+        std::fs::write(".taskter/description.md", "Offline project").unwrap();
+
+        let agent = Agent {
+            id: 4,
+            system_prompt: "Report the project description".into(),
+            tools: vec![taskter::tools::builtin_declaration("get_description").unwrap()],
+            model: "gemini-2.5-flash".into(),
+            provider: Some("gemini".into()),
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        };
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::execute_task(&agent, None))
+            .expect("execution failed");
+
+        assert_eq!(
+            result,
+            ExecutionResult::Success {
+                comment: "Offline project".into(),
+                stats: RunStats::new(1, FinalAction::Simulated),
+            }
+        );
+    });
+}
+
+#[test]
+fn include_task_comment_adds_previous_feedback_to_the_prompt() {
+    with_temp_dir(|| {
+        let _host_guard = disable_host_config_guard();
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/api/chat")
+            .match_body(mockito::Matcher::Regex(
+                r"Previous Comment \(from agent 6\): The build failed: missing libssl".into(),
+            ))
+            .with_body(json!({"message": {"role": "assistant", "content": "fixed"}}).to_string())
+            .expect(1)
+            .create();
+        let _url_guard =
+            common::EnvVarGuard::set("TASKTER__PROVIDERS__OLLAMA__BASE_URL", &server.url());
+        let _comment_guard =
+            common::EnvVarGuard::set("TASKTER__PROVIDERS__INCLUDE_TASK_COMMENT", "true");
+        taskter::config::force_reload().expect("failed to enable task comments");
+
+        let agent = Agent {
+            id: 6,
+            system_prompt: "Fix the build".into(),
+            tools: Vec::new(),
+            model: "ollama:llama3".into(),
+            provider: None,
+            schedule: None,
+            repeat: false,
+            ..Default::default()
+        };
+        let task = Task {
+            id: 1,
+            title: "Build the release".into(),
+            description: None,
+            status: TaskStatus::ToDo,
+            agent_id: Some(6),
+            comment: Some("The build failed: missing libssl".into()),
+            comment_author: Some(store::CommentAuthor::Agent(6)),
+            ..Default::default()
+        };
+        assert!(agent::task_prompt(&task)
+            .unwrap()
+            .contains("missing libssl"));
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = rt
+            .block_on(agent::execute_task(&agent, Some(&task)))
+            .expect("execution failed");
+        assert!(matches!(result, ExecutionResult::Success { .. }));
+        mock.assert();
+
+        drop(_comment_guard);
+        taskter::config::force_reload().expect("failed to reset config");
+        assert_eq!(agent::task_prompt(&task).unwrap(), "Build the release");
+    });
 }
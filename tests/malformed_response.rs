@@ -1,5 +1,5 @@
 use serde_json::json;
-use taskter::agent::ExecutionResult;
+use taskter::agent::{ExecutionResult, FinalAction, RunStats};
 
 #[test]
 fn missing_tool_name_returns_failure() {
@@ -9,6 +9,7 @@ fn missing_tool_name_returns_failure() {
         .and_then(serde_json::Value::as_str)
         .ok_or_else(|| ExecutionResult::Failure {
             comment: "Malformed API response: missing field `name`".to_string(),
+            stats: RunStats::new(0, FinalAction::Text),
         });
     assert!(matches!(result, Err(ExecutionResult::Failure { .. })));
 }
@@ -21,6 +22,7 @@ fn non_string_text_returns_failure() {
         .and_then(serde_json::Value::as_str)
         .ok_or_else(|| ExecutionResult::Failure {
             comment: "Malformed API response: missing field `text`".to_string(),
+            stats: RunStats::new(0, FinalAction::Text),
         });
     assert!(matches!(result, Err(ExecutionResult::Failure { .. })));
 }
@@ -21,6 +21,7 @@ fn base_agent(model: &str) -> Agent {
         provider: Some("ollama".into()),
         schedule: None,
         repeat: false,
+        ..Default::default()
     }
 }
 
@@ -51,7 +52,7 @@ fn ollama_append_tool_result_shapes_are_correct() {
         &mut history,
         "run_bash",
         &json!({"command": "ls"}),
-        "output",
+        &"output".into(),
         Some("call_42"),
     );
     assert_eq!(history.len(), 2);
@@ -118,3 +119,15 @@ fn ollama_request_body_includes_tools() {
     assert_eq!(body["messages"].as_array().unwrap().len(), 2);
     assert!(body["tools"].as_array().is_some());
 }
+
+#[test]
+fn ollama_error_string_surfaces_message() {
+    let provider = OllamaProvider;
+    let err = provider
+        .parse_response(&json!({"error": "model \"llama9\" not found"}))
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("model \"llama9\" not found"),
+        "{err}"
+    );
+}
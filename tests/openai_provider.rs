@@ -4,6 +4,7 @@ use std::{env, ffi::OsString};
 
 use taskter::agent::{Agent, FunctionDeclaration};
 use taskter::providers::{openai::OpenAIProvider, select_provider, ModelAction, ModelProvider};
+use taskter::tools::ToolOutput;
 
 mod common;
 use common::disable_host_config_guard;
@@ -53,6 +54,7 @@ fn base_agent(model: &str) -> Agent {
         provider: None,
         schedule: None,
         repeat: false,
+        ..Default::default()
     }
 }
 
@@ -159,7 +161,7 @@ fn append_tool_result_shapes_are_correct() {
         &mut hist_chat,
         "run_bash",
         &json!({"command":"ls"}),
-        "ok",
+        &"ok".into(),
         Some("call_abc"),
     );
     assert_eq!(hist_chat.len(), 2);
@@ -180,7 +182,7 @@ fn append_tool_result_shapes_are_correct() {
         &mut hist_resp,
         "run_bash",
         &json!({"command":"echo hi"}),
-        "hello from tool",
+        &"hello from tool".into(),
         Some("call_xyz"),
     );
     assert_eq!(hist_resp.len(), 2);
@@ -240,3 +242,68 @@ fn openai_custom_base_url_is_used() {
     let endpoint = provider.endpoint(&agent);
     assert_eq!(endpoint, "https://example.com/custom/v1/responses");
 }
+
+#[test]
+fn openai_organization_and_project_headers_follow_config() {
+    let _guard = ENV_LOCK.lock().unwrap();
+    let _host_guard = disable_host_config_guard();
+    let provider = OpenAIProvider;
+    let header = |headers: &[(String, String)], name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    };
+
+    let headers = provider.headers("sk-test");
+    assert_eq!(header(&headers, "OpenAI-Organization"), None);
+    assert_eq!(header(&headers, "OpenAI-Project"), None);
+
+    let _org_guard = EnvGuard::set("TASKTER__PROVIDERS__OPENAI__ORGANIZATION", "org-123");
+    let _project_guard = EnvGuard::set("TASKTER__PROVIDERS__OPENAI__PROJECT", "proj_456");
+    let headers = provider.headers("sk-test");
+    assert_eq!(
+        header(&headers, "OpenAI-Organization").as_deref(),
+        Some("org-123")
+    );
+    assert_eq!(
+        header(&headers, "OpenAI-Project").as_deref(),
+        Some("proj_456")
+    );
+}
+
+#[test]
+fn openai_error_body_with_success_status_surfaces_message() {
+    let provider = OpenAIProvider;
+    let body = json!({
+        "error": {
+            "message": "Rate limit reached for gpt-4o",
+            "type": "requests",
+            "code": "rate_limit_exceeded"
+        }
+    });
+    let err = provider.parse_response(&body).unwrap_err();
+    assert!(
+        err.to_string().contains("Rate limit reached for gpt-4o"),
+        "{err}"
+    );
+}
+
+#[test]
+fn json_tool_output_is_serialized_for_chat_completions() {
+    let provider = OpenAIProvider;
+    let agent = base_agent("gpt-4o");
+    let mut history = Vec::new();
+    provider.append_tool_result(
+        &agent,
+        &mut history,
+        "run_bash",
+        &json!({"command":"ls"}),
+        &ToolOutput::Json(json!({"files": ["a.txt"], "count": 1})),
+        Some("call_json"),
+    );
+    assert_eq!(history[1]["role"], "tool");
+    let content = history[1]["content"].as_str().expect("string content");
+    let parsed: serde_json::Value = serde_json::from_str(content).unwrap();
+    assert_eq!(parsed, json!({"files": ["a.txt"], "count": 1}));
+}
@@ -7,8 +7,12 @@ use taskter::{agent, scheduler, store, tools};
 mod common;
 use common::disable_host_config_guard;
 
+/// Both tests change the working directory and global config.
+static SCHEDULER_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
 #[tokio::test]
 async fn scheduler_executes_agent_tasks() {
+    let _lock = SCHEDULER_LOCK.lock().await;
     let _host_config_guard = disable_host_config_guard();
     let tmp = tempfile::tempdir().expect("tmp");
     let orig = std::env::current_dir().unwrap();
@@ -34,6 +38,7 @@ async fn scheduler_executes_agent_tasks() {
         provider: Some("gemini".into()),
         schedule: Some("*/1 * * * * *".into()),
         repeat: false,
+        ..Default::default()
     };
     agent::save_agents(std::slice::from_ref(&agent)).unwrap();
 
@@ -45,6 +50,7 @@ async fn scheduler_executes_agent_tasks() {
             status: TaskStatus::ToDo,
             agent_id: Some(1),
             comment: None,
+            ..Default::default()
         },
         Task {
             id: 2,
@@ -53,9 +59,14 @@ async fn scheduler_executes_agent_tasks() {
             status: TaskStatus::ToDo,
             agent_id: Some(1),
             comment: None,
+            ..Default::default()
         },
     ];
-    store::save_board(&Board { tasks }).unwrap();
+    store::save_board(&Board {
+        tasks,
+        ..Default::default()
+    })
+    .unwrap();
 
     let handle = tokio::spawn(async { scheduler::run().await.unwrap() });
     tokio::time::sleep(Duration::from_secs(3)).await;
@@ -70,3 +81,311 @@ async fn scheduler_executes_agent_tasks() {
     std::env::set_current_dir(orig).unwrap();
     config::init(&ConfigOverrides::default()).expect("reset config state");
 }
+
+#[tokio::test]
+async fn scheduler_activity_goes_to_configured_log() {
+    let _lock = SCHEDULER_LOCK.lock().await;
+    let _host_config_guard = disable_host_config_guard();
+    let tmp = tempfile::tempdir().expect("tmp");
+    let orig = std::env::current_dir().unwrap();
+    std::env::set_current_dir(tmp.path()).unwrap();
+    let data_dir = tmp.path().join(taskter::config::DIR);
+    std::fs::create_dir(&data_dir).unwrap();
+
+    let scheduler_log = tmp.path().join("scheduler.log");
+    let config_path = tmp.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        format!(
+            "[scheduler]\nlog_file = {:?}\n",
+            scheduler_log.display().to_string()
+        ),
+    )
+    .unwrap();
+    let overrides = ConfigOverrides {
+        config_file: Some(config_path),
+        data_dir: Some(data_dir),
+        ..ConfigOverrides::default()
+    };
+    config::init(&overrides).expect("config init");
+
+    // No tasks and no API key: the job fires a simulated run only.
+    let agent = Agent {
+        id: 1,
+        system_prompt: "helper".into(),
+        tools: Vec::new(),
+        model: "gemini-2.5-flash".into(),
+        provider: Some("gemini".into()),
+        schedule: Some("*/1 * * * * *".into()),
+        repeat: false,
+        ..Default::default()
+    };
+    agent::save_agents(std::slice::from_ref(&agent)).unwrap();
+    store::save_board(&Board {
+        tasks: Vec::new(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let handle = tokio::spawn(async { scheduler::run().await.unwrap() });
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    handle.abort();
+    let _ = handle.await;
+
+    let activity = std::fs::read_to_string(&scheduler_log).expect("scheduler log written");
+    assert!(activity.contains("Scheduler fired agent 1"), "{activity}");
+    assert!(
+        activity.contains("Scheduler finished agent 1"),
+        "{activity}"
+    );
+    let main_log = std::fs::read_to_string(config::log_path().unwrap()).unwrap_or_default();
+    assert!(!main_log.contains("Scheduler"), "{main_log}");
+
+    std::env::set_current_dir(orig).unwrap();
+    config::init(&ConfigOverrides::default()).expect("reset config state");
+}
+
+#[tokio::test]
+async fn stop_sentinel_ends_wait_for_stop() {
+    let tmp = tempfile::tempdir().expect("tmp");
+    let sentinel = tmp.path().join("scheduler.stop");
+    let waiter = {
+        let sentinel = sentinel.clone();
+        tokio::spawn(async move {
+            scheduler::wait_for_stop(&sentinel, Duration::from_millis(10)).await;
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!waiter.is_finished());
+
+    std::fs::write(&sentinel, "").unwrap();
+    tokio::time::timeout(Duration::from_secs(2), waiter)
+        .await
+        .expect("wait_for_stop returned")
+        .unwrap();
+    assert!(!sentinel.exists());
+}
+
+#[tokio::test]
+async fn request_stop_shuts_down_running_scheduler() {
+    let _lock = SCHEDULER_LOCK.lock().await;
+    let _host_config_guard = disable_host_config_guard();
+    let tmp = tempfile::tempdir().expect("tmp");
+    let orig = std::env::current_dir().unwrap();
+    std::env::set_current_dir(tmp.path()).unwrap();
+    let data_dir = tmp.path().join(taskter::config::DIR);
+    std::fs::create_dir(&data_dir).unwrap();
+    let config_path = tmp.path().join("config.toml");
+    std::fs::write(&config_path, b"").unwrap();
+    let overrides = ConfigOverrides {
+        config_file: Some(config_path),
+        data_dir: Some(data_dir),
+        ..ConfigOverrides::default()
+    };
+    config::init(&overrides).expect("config init");
+    agent::save_agents(&[]).unwrap();
+
+    let handle = tokio::spawn(scheduler::run());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    scheduler::request_stop().unwrap();
+    let result = tokio::time::timeout(Duration::from_secs(5), handle)
+        .await
+        .expect("scheduler stopped")
+        .unwrap();
+    assert!(result.is_ok(), "{result:?}");
+    assert!(!scheduler::stop_sentinel_path().unwrap().exists());
+
+    std::env::set_current_dir(orig).unwrap();
+    config::init(&ConfigOverrides::default()).expect("reset config state");
+}
+
+#[tokio::test]
+async fn run_for_returns_once_the_duration_elapses() {
+    let _lock = SCHEDULER_LOCK.lock().await;
+    let _host_config_guard = disable_host_config_guard();
+    let tmp = tempfile::tempdir().expect("tmp");
+    let orig = std::env::current_dir().unwrap();
+    std::env::set_current_dir(tmp.path()).unwrap();
+    let data_dir = tmp.path().join(taskter::config::DIR);
+    std::fs::create_dir(&data_dir).unwrap();
+    let config_path = tmp.path().join("config.toml");
+    std::fs::write(&config_path, b"").unwrap();
+    let overrides = ConfigOverrides {
+        config_file: Some(config_path),
+        data_dir: Some(data_dir),
+        ..ConfigOverrides::default()
+    };
+    config::init(&overrides).expect("config init");
+    agent::save_agents(&[]).unwrap();
+
+    let started = std::time::Instant::now();
+    let result = tokio::time::timeout(
+        Duration::from_secs(3),
+        scheduler::run_with(scheduler::RunOptions {
+            run_for: Some(Duration::from_secs(1)),
+            ..Default::default()
+        }),
+    )
+    .await
+    .expect("scheduler returned after --for elapsed");
+    assert!(result.is_ok(), "{result:?}");
+    assert!(started.elapsed() >= Duration::from_secs(1));
+
+    std::env::set_current_dir(orig).unwrap();
+    config::init(&ConfigOverrides::default()).expect("reset config state");
+}
+
+#[tokio::test]
+async fn metrics_endpoint_counts_successful_executions() {
+    let _lock = SCHEDULER_LOCK.lock().await;
+    let _host_config_guard = disable_host_config_guard();
+    let tmp = tempfile::tempdir().expect("tmp");
+    let orig = std::env::current_dir().unwrap();
+    std::env::set_current_dir(tmp.path()).unwrap();
+    let data_dir = tmp.path().join(taskter::config::DIR);
+    std::fs::create_dir(&data_dir).unwrap();
+    let config_path = tmp.path().join("config.toml");
+    std::fs::write(&config_path, b"").unwrap();
+    let overrides = ConfigOverrides {
+        config_file: Some(config_path),
+        data_dir: Some(data_dir),
+        ..ConfigOverrides::default()
+    };
+    config::init(&overrides).expect("config init");
+
+    // Without an API key the send_email agent runs a simulated, successful job.
+    let agent = Agent {
+        id: 1,
+        system_prompt: "helper".into(),
+        tools: vec![tools::builtin_declaration("send_email").unwrap()],
+        model: "gemini-2.5-flash".into(),
+        provider: Some("gemini".into()),
+        schedule: Some("*/1 * * * * *".into()),
+        repeat: false,
+        ..Default::default()
+    };
+    agent::save_agents(std::slice::from_ref(&agent)).unwrap();
+    let task = Task {
+        id: 1,
+        title: "t1".into(),
+        description: None,
+        status: TaskStatus::ToDo,
+        agent_id: Some(1),
+        comment: None,
+        ..Default::default()
+    };
+    store::save_board(&Board {
+        tasks: vec![task],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let handle = tokio::spawn(scheduler::run_with(scheduler::RunOptions {
+        metrics_addr: Some(addr),
+        ..Default::default()
+    }));
+    tokio::time::sleep(Duration::from_secs(3)).await;
+
+    let body = reqwest::get(format!("http://{addr}/metrics"))
+        .await
+        .expect("scrape metrics")
+        .text()
+        .await
+        .unwrap();
+    assert!(
+        body.contains("taskter_scheduler_jobs_fired_total 1"),
+        "{body}"
+    );
+    assert!(
+        body.contains("taskter_scheduler_executions_succeeded_total 1"),
+        "{body}"
+    );
+    assert!(
+        body.contains("taskter_scheduler_executions_failed_total 0"),
+        "{body}"
+    );
+    let missing = reqwest::get(format!("http://{addr}/other")).await.unwrap();
+    assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+
+    scheduler::request_stop().unwrap();
+    tokio::time::timeout(Duration::from_secs(5), handle)
+        .await
+        .expect("scheduler stopped")
+        .unwrap()
+        .unwrap();
+
+    std::env::set_current_dir(orig).unwrap();
+    config::init(&ConfigOverrides::default()).expect("reset config state");
+}
+
+#[tokio::test]
+async fn concurrency_limit_processes_every_task_consistently() {
+    let _lock = SCHEDULER_LOCK.lock().await;
+    let _host_config_guard = disable_host_config_guard();
+    let tmp = tempfile::tempdir().expect("tmp");
+    let orig = std::env::current_dir().unwrap();
+    std::env::set_current_dir(tmp.path()).unwrap();
+    let data_dir = tmp.path().join(taskter::config::DIR);
+    std::fs::create_dir(&data_dir).unwrap();
+
+    let config_path = tmp.path().join("config.toml");
+    std::fs::write(&config_path, b"").unwrap();
+    let overrides = ConfigOverrides {
+        config_file: Some(config_path),
+        data_dir: Some(data_dir),
+        ..ConfigOverrides::default()
+    };
+    config::init(&overrides).expect("config init");
+
+    let agent = Agent {
+        id: 1,
+        system_prompt: "helper".into(),
+        tools: vec![tools::builtin_declaration("send_email").unwrap()],
+        model: "gemini-2.5-flash".into(),
+        provider: Some("gemini".into()),
+        schedule: Some("*/1 * * * * *".into()),
+        repeat: false,
+        ..Default::default()
+    };
+    agent::save_agents(std::slice::from_ref(&agent)).unwrap();
+    let tasks = (1..=2)
+        .map(|id| Task {
+            id,
+            title: format!("t{id}"),
+            description: None,
+            status: TaskStatus::ToDo,
+            agent_id: Some(1),
+            comment: None,
+            ..Default::default()
+        })
+        .collect();
+    store::save_board(&Board {
+        tasks,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let handle = tokio::spawn(scheduler::run_with(scheduler::RunOptions {
+        max_concurrent_tasks: Some(2),
+        ..Default::default()
+    }));
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    handle.abort();
+    let _ = handle.await;
+
+    let board = store::load_board().unwrap();
+    let ids: Vec<usize> = board.tasks.iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![1, 2]);
+    for task in &board.tasks {
+        assert_eq!(task.status, TaskStatus::Done);
+        assert!(task.comment.is_some(), "task {} has no comment", task.id);
+        assert_eq!(task.agent_id, Some(1));
+    }
+
+    std::env::set_current_dir(orig).unwrap();
+    config::init(&ConfigOverrides::default()).expect("reset config state");
+}
@@ -15,7 +15,7 @@ pub use common::with_temp_dir;
 fn get_description_reads_file() {
     with_temp_dir(|| {
         fs::write(taskter::config::DESCRIPTION_FILE, "desc").unwrap();
-        let out = get_description::execute(&json!({})).unwrap();
+        let out = get_description::execute(&json!({})).unwrap().to_string();
         assert_eq!(out, "desc");
     });
 }
@@ -43,13 +43,41 @@ fn web_search_fetches_result() {
             .with_body(r#"{"AbstractText":"Rust lang","RelatedTopics":[]}"#)
             .create();
         std::env::set_var("SEARCH_API_ENDPOINT", server.url());
-        let out = taskter::tools::execute_tool("web_search", &json!({"query":"rust"})).unwrap();
+        let out = taskter::tools::execute_tool("web_search", &json!({"query":"rust"}))
+            .unwrap()
+            .to_string();
         assert_eq!(out, "Rust lang");
         std::env::remove_var("SEARCH_API_ENDPOINT");
         _m.assert();
     });
 }
 
+#[test]
+fn web_search_uses_configured_proxy() {
+    with_temp_dir(|| {
+        let mut proxy = Server::new();
+        let _m = proxy
+            .mock("GET", Matcher::Any)
+            .match_header("host", "search.invalid")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"AbstractText":"Via proxy","RelatedTopics":[]}"#)
+            .create();
+        fs::write(
+            "config.toml",
+            format!("[tools]\nhttp_proxy = \"{}\"\n", proxy.url()),
+        )
+        .unwrap();
+        taskter::config::force_reload().unwrap();
+
+        std::env::set_var("SEARCH_API_ENDPOINT", "http://search.invalid/");
+        let out = taskter::tools::execute_tool("web_search", &json!({"query":"rust"}));
+        std::env::remove_var("SEARCH_API_ENDPOINT");
+        assert_eq!(out.unwrap().to_string(), "Via proxy");
+        _m.assert();
+    });
+}
+
 #[test]
 fn taskter_task_tool_lists_tasks() {
     with_temp_dir(|| {
@@ -64,7 +92,9 @@ fn taskter_task_tool_lists_tasks() {
             .assert()
             .success();
 
-        let out = taskter::tools::execute_tool("taskter_task", &json!({"args": ["list"]})).unwrap();
+        let out = taskter::tools::execute_tool("taskter_task", &json!({"args": ["list"]}))
+            .unwrap()
+            .to_string();
         assert!(out.contains("Demo"));
         std::env::remove_var("TASKTER_BIN");
     });
@@ -93,8 +123,9 @@ fn taskter_agent_tool_lists_agents() {
             .assert()
             .success();
 
-        let out =
-            taskter::tools::execute_tool("taskter_agent", &json!({"args": ["list"]})).unwrap();
+        let out = taskter::tools::execute_tool("taskter_agent", &json!({"args": ["list"]}))
+            .unwrap()
+            .to_string();
         assert!(out.contains("helper"));
         std::env::remove_var("TASKTER_BIN");
     });
@@ -115,11 +146,69 @@ fn taskter_okrs_tool_lists_okrs() {
             .success();
 
         let out = taskter::tools::execute_tool("taskter_okrs", &json!({"args": ["list"]})).unwrap();
-        assert!(out.contains("Improve"));
+        let taskter::tools::ToolOutput::Json(okrs) = &out else {
+            panic!("expected structured output, got {out:?}");
+        };
+        assert_eq!(okrs[0]["objective"], "Improve");
         std::env::remove_var("TASKTER_BIN");
     });
 }
 
+#[cfg(unix)]
+#[test]
+fn taskter_tools_retry_transient_subprocess_failures() {
+    use std::os::unix::fs::PermissionsExt;
+
+    with_temp_dir(|| {
+        // Killed by a signal on the first run, succeeds once the marker
+        // file exists.
+        let dir = std::env::current_dir().unwrap();
+        let flaky = dir.join("flaky-taskter");
+        fs::write(
+            &flaky,
+            "#!/bin/sh\nif [ -f attempted ]; then echo recovered; exit 0; fi\ntouch attempted\necho busy >&2\nkill -TERM $$\n",
+        )
+        .unwrap();
+        fs::set_permissions(&flaky, fs::Permissions::from_mode(0o755)).unwrap();
+        // Exits non-zero on the first run, succeeds once the marker exists.
+        let failing = dir.join("failing-taskter");
+        fs::write(
+            &failing,
+            "#!/bin/sh\nif [ -f attempted ]; then echo recovered; exit 0; fi\ntouch attempted\necho invalid task >&2\nexit 1\n",
+        )
+        .unwrap();
+        fs::set_permissions(&failing, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let bin_guard = common::EnvVarGuard::set("TASKTER_BIN", flaky.to_str().unwrap());
+        let out = taskter::tools::execute_tool("taskter_task", &json!({"args": ["list"]}))
+            .unwrap()
+            .to_string();
+        assert_eq!(out, "recovered");
+        drop(bin_guard);
+
+        fs::remove_file("attempted").unwrap();
+        let bin_guard = common::EnvVarGuard::set("TASKTER_BIN", failing.to_str().unwrap());
+        let err =
+            taskter::tools::execute_tool("taskter_task", &json!({"args": ["list"]})).unwrap_err();
+        assert!(err.to_string().contains("invalid task"));
+        drop(bin_guard);
+
+        fs::write("config.toml", "[tools]\nsubprocess_retries = 0\n").unwrap();
+        taskter::config::force_reload().unwrap();
+        fs::remove_file("attempted").unwrap();
+        let bin_guard = common::EnvVarGuard::set("TASKTER_BIN", flaky.to_str().unwrap());
+        let err =
+            taskter::tools::execute_tool("taskter_task", &json!({"args": ["list"]})).unwrap_err();
+        assert!(err.to_string().contains("busy"));
+        drop(bin_guard);
+
+        let _bin_guard = common::EnvVarGuard::set("TASKTER_BIN", "/nonexistent/taskter");
+        let err =
+            taskter::tools::execute_tool("taskter_task", &json!({"args": ["list"]})).unwrap_err();
+        assert!(err.to_string().contains("Command not found"));
+    });
+}
+
 #[test]
 fn taskter_tools_tool_lists_builtins() {
     with_temp_dir(|| {
@@ -129,8 +218,9 @@ fn taskter_tools_tool_lists_builtins() {
 
         cargo_bin_cmd!("taskter").arg("init").assert().success();
 
-        let out =
-            taskter::tools::execute_tool("taskter_tools", &json!({"args": ["list"]})).unwrap();
+        let out = taskter::tools::execute_tool("taskter_tools", &json!({"args": ["list"]}))
+            .unwrap()
+            .to_string();
         assert!(out.contains("run_bash"));
         std::env::remove_var("TASKTER_BIN");
     });
@@ -158,6 +248,41 @@ fn run_python_requires_code_argument() {
     with_temp_dir(|| {
         let err = taskter::tools::execute_tool("run_python", &json!({})).unwrap_err();
         assert!(err.to_string().contains("code missing"));
+        assert!(err.to_string().contains("`path`"));
+    });
+}
+
+#[test]
+fn run_python_runs_script_from_path() {
+    with_temp_dir(|| {
+        fs::create_dir_all("scripts").unwrap();
+        fs::write(
+            "scripts/report.py",
+            "total = 40 + 2\nprint(f'total={total}')\n",
+        )
+        .unwrap();
+        let out = taskter::tools::execute_tool("run_python", &json!({"path": "scripts/report.py"}))
+            .unwrap()
+            .to_string();
+        assert_eq!(out, "total=42");
+
+        let err = taskter::tools::execute_tool(
+            "run_python",
+            &json!({"code": "print(1)", "path": "scripts/report.py"}),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not both"));
+
+        let outside = tempfile::NamedTempFile::new().unwrap();
+        let err = taskter::tools::execute_tool(
+            "run_python",
+            &json!({"path": outside.path().to_str().unwrap()}),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains(&format!(
+            "Script {} is outside the project directory",
+            outside.path().display()
+        )));
     });
 }
 
@@ -223,19 +348,22 @@ fn file_ops_create_read_update() {
             "file_ops",
             &json!({"action": "create", "path": "a.txt", "content": "hello"}),
         )
-        .unwrap();
+        .unwrap()
+        .to_string();
         assert_eq!(out, "Created a.txt");
         // Read the file
         let out =
             taskter::tools::execute_tool("file_ops", &json!({"action": "read", "path": "a.txt"}))
-                .unwrap();
+                .unwrap()
+                .to_string();
         assert_eq!(out, "hello");
         // Update the file
         let out = taskter::tools::execute_tool(
             "file_ops",
             &json!({"action": "update", "path": "a.txt", "content": "world"}),
         )
-        .unwrap();
+        .unwrap()
+        .to_string();
         assert_eq!(out, "Updated a.txt");
         let content = fs::read_to_string("a.txt").unwrap();
         assert_eq!(content, "world");
@@ -250,7 +378,8 @@ fn file_ops_search() {
         fs::write("other.txt", "nothing").unwrap();
         let out =
             taskter::tools::execute_tool("file_ops", &json!({"action": "search", "query": "find"}))
-                .unwrap();
+                .unwrap()
+                .to_string();
         assert!(out.contains("match.txt"));
         assert!(!out.contains("other.txt"));
         // Search for non-existing string
@@ -258,7 +387,347 @@ fn file_ops_search() {
             "file_ops",
             &json!({"action": "search", "query": "absent"}),
         )
-        .unwrap();
+        .unwrap()
+        .to_string();
         assert_eq!(out, "No matches found");
     });
 }
+
+#[test]
+fn file_ops_search_skips_ignored_dirs() {
+    with_temp_dir(|| {
+        fs::create_dir_all("node_modules/pkg").unwrap();
+        fs::write("node_modules/pkg/index.js", "needle").unwrap();
+        fs::create_dir_all("src/nested/deeper").unwrap();
+        fs::write("src/nested/deeper/lib.rs", "// needle").unwrap();
+        fs::write("blob.bin", b"needle\0\x01\x02").unwrap();
+        let out = taskter::tools::execute_tool(
+            "file_ops",
+            &json!({"action": "search", "query": "needle"}),
+        )
+        .unwrap()
+        .to_string();
+        assert!(out.contains("lib.rs"));
+        assert!(!out.contains("node_modules"));
+        assert!(!out.contains("blob.bin"));
+    });
+}
+
+#[test]
+fn file_ops_search_results_are_sorted() {
+    with_temp_dir(|| {
+        for name in ["c.txt", "a.txt", "b.txt"] {
+            fs::write(name, "same").unwrap();
+        }
+        let out =
+            taskter::tools::execute_tool("file_ops", &json!({"action": "search", "query": "same"}))
+                .unwrap()
+                .to_string();
+        let lines: Vec<&str> = out.lines().collect();
+        let mut sorted = lines.clone();
+        sorted.sort_unstable();
+        assert_eq!(lines, sorted);
+        assert_eq!(lines.len(), 3);
+    });
+}
+
+#[test]
+fn builtin_declarations_match_embedded_json() {
+    let tools_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tools");
+    for entry in fs::read_dir(tools_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let embedded: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let first = taskter::tools::builtin_declaration(name)
+            .unwrap_or_else(|| panic!("{name} is not registered"));
+        let second = taskter::tools::builtin_declaration(name).unwrap();
+        let first = serde_json::to_value(first).unwrap();
+        assert_eq!(first, serde_json::to_value(second).unwrap());
+        assert_eq!(first, embedded, "declaration for {name} differs from JSON");
+    }
+}
+
+#[test]
+fn disabled_tools_are_hidden_and_rejected() {
+    with_temp_dir(|| {
+        fs::write("config.toml", "[tools]\ndisabled = [\"run_bash\"]\n").unwrap();
+        taskter::config::force_reload().unwrap();
+
+        assert!(!taskter::tools::builtin_names().contains(&"run_bash"));
+        assert!(taskter::tools::builtin_names().contains(&"run_python"));
+        assert!(taskter::tools::builtin_declaration("run_bash").is_none());
+        let err =
+            taskter::tools::execute_tool("run_bash", &json!({"command": "echo hi"})).unwrap_err();
+        assert!(err.to_string().contains("disabled"));
+    });
+}
+
+#[test]
+fn unknown_tool_error_suggests_close_match() {
+    let err = taskter::tools::execute_tool("web_serch", &json!({})).unwrap_err();
+    assert!(err.to_string().contains("did you mean `web_search`?"));
+
+    let err = taskter::tools::execute_tool("completely_unrelated", &json!({})).unwrap_err();
+    assert_eq!(err.to_string(), "Unknown tool: completely_unrelated");
+}
+
+#[test]
+fn tool_metrics_count_calls_and_failures() {
+    with_temp_dir(|| {
+        taskter::tools::execute_tool("run_python", &json!({"code": "print(1)"})).unwrap();
+        taskter::tools::execute_tool("run_python", &json!({"code": "import sys; sys.exit(1)"}))
+            .unwrap_err();
+
+        let metrics = taskter::tools::metrics::load().unwrap();
+        let stats = &metrics["run_python"];
+        assert_eq!(stats.calls, 2);
+        assert_eq!(stats.failures, 1);
+
+        cargo_bin_cmd!("taskter")
+            .args(["tools", "stats"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("run_python: 2 calls, 1 failures"));
+    });
+}
+
+#[test]
+fn tool_metrics_leave_a_corrupt_file_untouched() {
+    with_temp_dir(|| {
+        fs::write(".taskter/tool_metrics.json", "{not json").unwrap();
+
+        taskter::tools::execute_tool("run_python", &json!({"code": "print(1)"})).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(".taskter/tool_metrics.json").unwrap(),
+            "{not json"
+        );
+        assert!(taskter::tools::metrics::load().is_err());
+    });
+}
+
+#[test]
+fn file_tools_resolve_paths_inside_agent_workdir() {
+    with_temp_dir(|| {
+        fs::create_dir("docs").unwrap();
+        let workdir = Some(std::path::Path::new("docs"));
+
+        taskter::tools::execute_tool_in(
+            "project_files",
+            &json!({"action":"create","path":"notes.md","content":"hello"}),
+            workdir,
+        )
+        .unwrap();
+        assert_eq!(fs::read_to_string("docs/notes.md").unwrap(), "hello");
+
+        let out = taskter::tools::execute_tool_in(
+            "project_files",
+            &json!({"action":"read","path":"./notes.md"}),
+            workdir,
+        )
+        .unwrap()
+        .to_string();
+        assert_eq!(out, "hello");
+
+        fs::write("secret.txt", "top secret").unwrap();
+        for path in ["../secret.txt", "sub/../../secret.txt"] {
+            let err = taskter::tools::execute_tool_in(
+                "project_files",
+                &json!({"action":"read","path":path}),
+                workdir,
+            )
+            .unwrap_err();
+            assert!(
+                err.to_string().contains("outside the agent workdir"),
+                "{err}"
+            );
+        }
+        let absolute = std::env::current_dir().unwrap().join("secret.txt");
+        assert!(taskter::tools::execute_tool_in(
+            "project_files",
+            &json!({"action":"read","path":absolute}),
+            workdir,
+        )
+        .is_err());
+    });
+}
+
+#[test]
+fn agent_workdir_must_stay_inside_project() {
+    with_temp_dir(|| {
+        let err = taskter::tools::execute_tool_in(
+            "run_bash",
+            &json!({"command":"pwd"}),
+            Some(std::path::Path::new("..")),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("outside the project directory"));
+
+        fs::create_dir("src").unwrap();
+        let out = taskter::tools::execute_tool_in(
+            "run_bash",
+            &json!({"command":"pwd"}),
+            Some(std::path::Path::new("src")),
+        )
+        .unwrap()
+        .to_string();
+        assert!(out.ends_with("/src"), "{out}");
+    });
+}
+
+#[test]
+fn taskter_task_structured_add_matches_positional_add() {
+    with_temp_dir(|| {
+        let cmd = cargo_bin_cmd!("taskter");
+        let bin = cmd.get_program().to_owned();
+        std::env::set_var("TASKTER_BIN", &bin);
+        cargo_bin_cmd!("taskter").arg("init").assert().success();
+
+        taskter::tools::execute_tool(
+            "taskter_task",
+            &json!({"args": ["add", "--title", "Two words", "--description", "With spaces"]}),
+        )
+        .unwrap();
+        taskter::tools::execute_tool(
+            "taskter_task",
+            &json!({"action": "add", "title": "Two words", "description": "With spaces"}),
+        )
+        .unwrap();
+
+        let board = taskter::store::load_board().unwrap();
+        assert_eq!(board.tasks.len(), 2);
+        let (positional, structured) = (&board.tasks[0], &board.tasks[1]);
+        assert_eq!(structured.title, positional.title);
+        assert_eq!(structured.description, positional.description);
+        assert_eq!(structured.status, positional.status);
+        assert_eq!(structured.title, "Two words");
+
+        let err = taskter::tools::execute_tool("taskter_task", &json!({"action": "add"}))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Missing required field `title`"), "{err}");
+        std::env::remove_var("TASKTER_BIN");
+    });
+}
+
+#[test]
+fn web_search_uses_configured_endpoint_and_user_agent() {
+    with_temp_dir(|| {
+        let mut server = Server::new();
+        let _m = server
+            .mock("GET", "/mirror")
+            .match_query(Matcher::UrlEncoded("q".into(), "rust".into()))
+            .match_header("user-agent", "taskter-tests (ops@example.com)")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"AbstractText":"From mirror","RelatedTopics":[]}"#)
+            .create();
+        fs::write(
+            "config.toml",
+            format!(
+                "[tools]\nenv_proxy = false\n\n[tools.search]\nendpoint = \"{}/mirror\"\nuser_agent = \"taskter-tests (ops@example.com)\"\n",
+                server.url()
+            ),
+        )
+        .unwrap();
+        taskter::config::force_reload().unwrap();
+
+        let out = taskter::tools::execute_tool("web_search", &json!({"query":"rust"}))
+            .unwrap()
+            .to_string();
+        assert_eq!(out, "From mirror");
+        _m.assert();
+    });
+}
+
+#[test]
+fn file_ops_search_honors_taskterignore() {
+    with_temp_dir(|| {
+        fs::write(
+            ".taskterignore",
+            "# local secrets\n*.env\nbuild/\n!keep.env\n",
+        )
+        .unwrap();
+        fs::write("prod.env", "TOKEN=needle").unwrap();
+        fs::write("keep.env", "needle").unwrap();
+        fs::create_dir_all("build/out").unwrap();
+        fs::write("build/out/app.js", "needle").unwrap();
+        fs::create_dir_all("src").unwrap();
+        fs::write("src/main.rs", "// needle").unwrap();
+
+        let out = taskter::tools::execute_tool(
+            "file_ops",
+            &json!({"action": "search", "query": "needle"}),
+        )
+        .unwrap()
+        .to_string();
+        assert!(out.contains("main.rs"), "{out}");
+        assert!(out.contains("keep.env"), "{out}");
+        assert!(!out.contains("prod.env"), "{out}");
+        assert!(!out.contains("build"), "{out}");
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn run_bash_timeout_kills_background_children() {
+    fn running(pid: &str) -> bool {
+        let stat = std::path::Path::new("/proc").join(pid).join("stat");
+        if std::path::Path::new("/proc/self").exists() {
+            // Zombies still answer `kill -0`, so check the state field instead.
+            return fs::read_to_string(stat)
+                .is_ok_and(|s| !s.rsplit(')').next().unwrap_or("").trim().starts_with('Z'));
+        }
+        std::process::Command::new("kill")
+            .args(["-0", pid])
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    with_temp_dir(|| {
+        let start = std::time::Instant::now();
+        let err = taskter::tools::execute_tool(
+            "run_bash",
+            &json!({"command": "sleep 30 & echo $! > child.pid; wait", "timeout_secs": 1}),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+
+        let pid = fs::read_to_string("child.pid").unwrap();
+        let pid = pid.trim();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while running(pid) && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        assert!(!running(pid), "background child {pid} survived the timeout");
+    });
+}
+
+#[test]
+fn run_bash_accepts_a_timeout_too_large_for_a_deadline() {
+    with_temp_dir(|| {
+        let out = taskter::tools::execute_tool(
+            "run_bash",
+            &json!({"command": "echo ok", "timeout_secs": u64::MAX}),
+        )
+        .unwrap()
+        .to_string();
+        assert_eq!(out, "ok");
+    });
+}
+
+#[test]
+fn run_python_timeout_stops_the_script() {
+    with_temp_dir(|| {
+        let start = std::time::Instant::now();
+        let err = taskter::tools::execute_tool(
+            "run_python",
+            &json!({"code": "import time; time.sleep(30)", "timeout_secs": 1}),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+    });
+}
@@ -1,7 +1,7 @@
 #![cfg(feature = "tui")]
 
 use taskter::agent::Agent;
-use taskter::store::{Board, Task, TaskStatus};
+use taskter::store::{Board, KeyResult, Okr, Task, TaskStatus};
 use taskter::tui::app::App;
 
 mod common;
@@ -19,6 +19,7 @@ fn navigation_cycles_through_columns_and_tasks() {
                     status: TaskStatus::ToDo,
                     agent_id: None,
                     comment: None,
+                    ..Default::default()
                 },
                 Task {
                     id: 2,
@@ -27,6 +28,7 @@ fn navigation_cycles_through_columns_and_tasks() {
                     status: TaskStatus::InProgress,
                     agent_id: None,
                     comment: None,
+                    ..Default::default()
                 },
                 Task {
                     id: 3,
@@ -35,8 +37,10 @@ fn navigation_cycles_through_columns_and_tasks() {
                     status: TaskStatus::Done,
                     agent_id: None,
                     comment: None,
+                    ..Default::default()
                 },
             ],
+            ..Default::default()
         };
         let mut app = App::new(board, Vec::<Agent>::new());
         assert_eq!(app.selected_column, 0);
@@ -63,7 +67,9 @@ fn moving_task_updates_status() {
                 status: TaskStatus::ToDo,
                 agent_id: None,
                 comment: None,
+                ..Default::default()
             }],
+            ..Default::default()
         };
         let mut app = App::new(board, Vec::<Agent>::new());
         app.move_task_to_next_column();
@@ -95,7 +101,9 @@ fn unassign_selected_task_clears_agent() {
                 status: TaskStatus::ToDo,
                 agent_id: Some(1),
                 comment: None,
+                ..Default::default()
             }],
+            ..Default::default()
         };
         let mut app = App::new(board, Vec::<Agent>::new());
         assert_eq!(app.board.lock().unwrap().tasks[0].agent_id, Some(1));
@@ -116,6 +124,7 @@ fn moving_task_updates_selection_in_destination_column() {
                     status: TaskStatus::ToDo,
                     agent_id: None,
                     comment: None,
+                    ..Default::default()
                 },
                 Task {
                     id: 2,
@@ -124,6 +133,7 @@ fn moving_task_updates_selection_in_destination_column() {
                     status: TaskStatus::ToDo,
                     agent_id: None,
                     comment: None,
+                    ..Default::default()
                 },
                 Task {
                     id: 3,
@@ -132,8 +142,10 @@ fn moving_task_updates_selection_in_destination_column() {
                     status: TaskStatus::InProgress,
                     agent_id: None,
                     comment: None,
+                    ..Default::default()
                 },
             ],
+            ..Default::default()
         };
         let mut app = App::new(board, Vec::<Agent>::new());
 
@@ -156,3 +168,170 @@ fn moving_task_updates_selection_in_destination_column() {
         assert_eq!(app.selected_task[1].selected(), Some(expected_index));
     });
 }
+
+#[test]
+fn okr_tree_expand_collapse_state() {
+    with_temp_dir(|| {
+        let mut app = App::new(Board::default(), Vec::<Agent>::new());
+        let okr = |objective: &str| Okr {
+            objective: objective.into(),
+            key_results: vec![KeyResult {
+                name: "KR".into(),
+                progress: 0.5,
+            }],
+        };
+        app.set_okrs(vec![okr("First"), okr("Second")]);
+
+        assert!(!app.okr_tree_view);
+        app.toggle_okr_view();
+        assert!(app.okr_tree_view);
+
+        assert!(!app.is_okr_expanded(0));
+        app.toggle_okr_expanded(1);
+        assert!(!app.is_okr_expanded(0));
+        assert!(app.is_okr_expanded(1));
+        app.toggle_okr_expanded(1);
+        assert!(!app.is_okr_expanded(1));
+
+        // Out-of-range indices are ignored.
+        app.toggle_okr_expanded(5);
+        assert!(!app.is_okr_expanded(5));
+
+        app.prev_okr();
+        assert_eq!(app.selected_okr, 1);
+        app.next_okr();
+        assert_eq!(app.selected_okr, 0);
+    });
+}
+
+#[test]
+fn cancelling_execution_untracks_handle_and_resets_task() {
+    with_temp_dir(|| {
+        let board = Board {
+            tasks: vec![Task {
+                id: 1,
+                title: "Long run".into(),
+                description: None,
+                status: TaskStatus::InProgress,
+                agent_id: Some(1),
+                comment: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut app = App::new(board, Vec::<Agent>::new());
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let execution = rt.spawn(std::future::pending::<()>());
+        app.track_execution(1, execution.abort_handle());
+
+        assert!(!app.cancel_execution(2));
+        assert!(app.cancel_execution(1));
+        assert!(app.executions.is_empty());
+        assert!(rt.block_on(execution).unwrap_err().is_cancelled());
+
+        let board = app.board.lock().unwrap();
+        assert_eq!(board.tasks[0].status, TaskStatus::ToDo);
+        assert_eq!(board.tasks[0].agent_id, None);
+    });
+}
+
+#[test]
+fn cancel_execution_leaves_finished_runs_alone() {
+    with_temp_dir(|| {
+        let board = Board {
+            tasks: vec![Task {
+                id: 1,
+                title: "Quick run".into(),
+                description: None,
+                status: TaskStatus::Done,
+                agent_id: Some(1),
+                comment: Some("done".into()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut app = App::new(board, Vec::<Agent>::new());
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let execution = rt.spawn(async {});
+        app.track_execution(1, execution.abort_handle());
+        rt.block_on(execution).unwrap();
+
+        assert!(!app.cancel_execution(1));
+        let board = app.board.lock().unwrap();
+        assert_eq!(board.tasks[0].status, TaskStatus::Done);
+        assert_eq!(board.tasks[0].agent_id, Some(1));
+    });
+}
+
+#[test]
+fn resize_event_requests_redraw_without_changing_view() {
+    use crossterm::event::Event;
+    use taskter::tui::app::View;
+
+    with_temp_dir(|| {
+        let mut app = App::new(Board::default(), Vec::<Agent>::new());
+        app.current_view = View::Logs;
+        app.popup_scroll = 3;
+
+        app.handle_terminal_event(&Event::Resize(80, 24));
+
+        assert!(app.redraw_requested);
+        assert!(matches!(app.current_view, View::Logs));
+        assert_eq!(app.popup_scroll, 3);
+    });
+}
+
+#[test]
+fn moving_task_out_of_done_clears_completion_time() {
+    with_temp_dir(|| {
+        let board = Board {
+            tasks: vec![Task {
+                id: 1,
+                title: "T".into(),
+                description: None,
+                status: TaskStatus::InProgress,
+                agent_id: None,
+                comment: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut app = App::new(board, Vec::<Agent>::new());
+        app.next_column();
+        app.move_task_to_next_column();
+        {
+            let board = app.board.lock().unwrap();
+            assert_eq!(board.tasks[0].status, TaskStatus::Done);
+            assert!(board.tasks[0].completed_at.is_some());
+        }
+        app.next_column();
+        app.move_task_to_next_column();
+        let board = app.board.lock().unwrap();
+        assert_eq!(board.tasks[0].status, TaskStatus::ToDo);
+        assert_eq!(board.tasks[0].completed_at, None);
+    });
+}
+
+#[test]
+fn paste_event_fills_the_field_being_edited() {
+    use crossterm::event::Event;
+    use taskter::tui::app::View;
+
+    with_temp_dir(|| {
+        let mut app = App::new(Board::default(), Vec::<Agent>::new());
+        app.handle_terminal_event(&Event::Paste("ignored".into()));
+        assert!(app.new_task_title.is_empty());
+
+        app.current_view = View::AddTask;
+        app.handle_terminal_event(&Event::Paste("Résumé 🎉\nupdate".into()));
+        assert_eq!(app.new_task_title, "Résumé 🎉 update");
+
+        app.editing_description = true;
+        app.handle_terminal_event(&Event::Paste("details".into()));
+        assert_eq!(app.new_task_description, "details");
+    });
+}